@@ -0,0 +1,245 @@
+use regex::Regex;
+use serde::{Deserialize, Serialize};
+
+use crate::error::{AppError, AppResult, ErrorKind};
+
+/// How serious an audit finding is
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub enum Severity {
+    Info,
+    Warning,
+    Error,
+}
+
+/// One problem found in an article before it is published
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Finding {
+    /// Which check raised it, e.g. `image-alt`
+    pub rule: &'static str,
+    pub severity: Severity,
+    pub message: String,
+    /// 1-based line in the Markdown body; `None` for the frontmatter
+    pub line: Option<usize>,
+}
+
+/// Whether audit findings can stop a publish
+#[derive(Debug, Clone, Copy, Default, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub enum AuditPolicy {
+    /// Report findings and publish anyway
+    #[default]
+    Warn,
+    /// Refuse to publish while any finding is an error
+    BlockOnErrors,
+}
+
+impl AuditPolicy {
+    pub fn blocks(self, findings: &[Finding]) -> bool {
+        self == AuditPolicy::BlockOnErrors && findings.iter().any(|f| f.severity == Severity::Error)
+    }
+
+    /// An error listing the blocking findings, if the policy stops the publish
+    pub fn check(self, findings: &[Finding]) -> AppResult<()> {
+        if !self.blocks(findings) {
+            return Ok(());
+        }
+        let errors: Vec<String> = findings
+            .iter()
+            .filter(|f| f.severity == Severity::Error)
+            .map(|f| match f.line {
+                Some(line) => format!("line {}: {}", line, f.message),
+                None => f.message.clone(),
+            })
+            .collect();
+        Err(AppError::new(
+            ErrorKind::AuditFailed,
+            format!(
+                "Publishing is blocked by {} accessibility error{}",
+                errors.len(),
+                if errors.len() == 1 { "" } else { "s" }
+            ),
+        )
+        .with_details(errors.join("\n")))
+    }
+}
+
+/// Link texts that say nothing about where the link goes
+const VAGUE_LINK_TEXTS: &[&str] = &[
+    "click here",
+    "here",
+    "link",
+    "more",
+    "read more",
+    "learn more",
+    "this",
+    "this link",
+    "this page",
+];
+
+/// Check the Markdown body and cover of an article for images without alt
+/// text, skipped heading levels, vague link text and tables without headers
+pub fn audit_article(markdown: &str, cover: &str, cover_alt: &str) -> AppResult<Vec<Finding>> {
+    let mut findings = Vec::new();
+    if !cover.is_empty() && cover_alt.trim().is_empty() {
+        findings.push(Finding {
+            rule: "cover-alt",
+            severity: Severity::Error,
+            message: "The cover image has no alt text".to_string(),
+            line: None,
+        });
+    }
+
+    let regex = |pattern: &str| {
+        Regex::new(pattern)
+            .map_err(|e| AppError::invalid("Failed to build audit pattern").with_details(e))
+    };
+    let image_re = regex(r"!\[([^\]]*)\]\(")?;
+    let img_tag_re = regex(r"(?i)<img\b[^>]*>")?;
+    let alt_re = regex(r#"(?i)\balt\s*=\s*(?:"([^"]*)"|'([^']*)')"#)?;
+    let link_re = regex(r"(^|[^!\]])\[([^\]]+)\]\(")?;
+    let heading_re = regex(r"^(#{1,6})\s")?;
+
+    // The title is the page's h1, so body headings start below it
+    let mut previous_level = 1;
+    let mut in_code = false;
+    let mut table: Vec<(usize, &str)> = Vec::new();
+    let lines: Vec<&str> = markdown.lines().collect();
+
+    for (index, line) in lines.iter().enumerate() {
+        let number = index + 1;
+        let trimmed = line.trim();
+        if trimmed.starts_with("```") || trimmed.starts_with("~~~") {
+            in_code = !in_code;
+            continue;
+        }
+        if in_code {
+            continue;
+        }
+
+        if trimmed.starts_with('|') {
+            table.push((number, trimmed));
+        } else if !table.is_empty() {
+            audit_table(&table, &mut findings);
+            table.clear();
+        }
+
+        for caps in image_re.captures_iter(line) {
+            if caps[1].trim().is_empty() {
+                findings.push(Finding {
+                    rule: "image-alt",
+                    severity: Severity::Error,
+                    message: "Image has no alt text".to_string(),
+                    line: Some(number),
+                });
+            }
+        }
+        for tag in img_tag_re.find_iter(line) {
+            let alt = alt_re
+                .captures(tag.as_str())
+                .and_then(|caps| caps.get(1).or_else(|| caps.get(2)))
+                .map(|m| m.as_str());
+            // Publishing turns `![](x)` into `<img alt="">`, so an empty alt
+            // must rank the same as a missing one or the syntax would decide
+            // whether the post is blocked
+            if alt.is_none_or(|alt| alt.trim().is_empty()) {
+                findings.push(Finding {
+                    rule: "image-alt",
+                    severity: Severity::Error,
+                    message: "Image has no alt text".to_string(),
+                    line: Some(number),
+                });
+            }
+        }
+
+        for caps in link_re.captures_iter(line) {
+            let text = caps[2]
+                .trim()
+                .trim_matches(|c: char| c.is_ascii_punctuation() || c.is_whitespace())
+                .to_lowercase();
+            if VAGUE_LINK_TEXTS.contains(&text.as_str()) {
+                findings.push(Finding {
+                    rule: "link-text",
+                    severity: Severity::Warning,
+                    message: format!(
+                        "Link text \"{}\" doesn't say where the link goes",
+                        caps[2].trim()
+                    ),
+                    line: Some(number),
+                });
+            }
+        }
+
+        if let Some(caps) = heading_re.captures(line) {
+            let level = caps[1].len();
+            if level > previous_level + 1 {
+                findings.push(Finding {
+                    rule: "heading-order",
+                    severity: Severity::Warning,
+                    message: format!(
+                        "Heading level {} follows level {}, skipping a level",
+                        level, previous_level
+                    ),
+                    line: Some(number),
+                });
+            }
+            previous_level = level;
+        }
+    }
+    if !table.is_empty() {
+        audit_table(&table, &mut findings);
+    }
+
+    audit_html_tables(&lines, &mut findings);
+    Ok(findings)
+}
+
+/// A Markdown table needs a header row: a first row with text, followed by
+/// a `| --- |` delimiter row
+fn audit_table(rows: &[(usize, &str)], findings: &mut Vec<Finding>) {
+    let is_delimiter = |row: &str| {
+        row.trim_matches('|').split('|').all(|cell| {
+            let cell = cell.trim();
+            !cell.is_empty() && cell.chars().all(|c| matches!(c, '-' | ':'))
+        })
+    };
+    let (line, header) = rows[0];
+    let has_delimiter = rows.get(1).is_some_and(|(_, row)| is_delimiter(row));
+    let header_is_empty = header
+        .trim_matches('|')
+        .split('|')
+        .all(|cell| cell.trim().is_empty());
+    if !has_delimiter || header_is_empty {
+        findings.push(Finding {
+            rule: "table-header",
+            severity: Severity::Error,
+            message: "Table has no header row".to_string(),
+            line: Some(line),
+        });
+    }
+}
+
+/// HTML tables need at least one `<th>`
+fn audit_html_tables(lines: &[&str], findings: &mut Vec<Finding>) {
+    let mut open: Option<(usize, bool)> = None;
+    for (index, line) in lines.iter().enumerate() {
+        let lower = line.to_lowercase();
+        if lower.contains("<table") {
+            open = Some((index + 1, false));
+        }
+        if let Some((_, has_header)) = open.as_mut() {
+            *has_header |= lower.contains("<th>") || lower.contains("<th ");
+        }
+        if lower.contains("</table") {
+            if let Some((line, false)) = open.take() {
+                findings.push(Finding {
+                    rule: "table-header",
+                    severity: Severity::Error,
+                    message: "Table has no header cells (<th>)".to_string(),
+                    line: Some(line),
+                });
+            }
+        }
+    }
+}
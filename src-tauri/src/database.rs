@@ -1,10 +1,19 @@
 use chrono::Utc;
 use rusqlite::{params, Connection, OptionalExtension, Result};
 use serde::{Deserialize, Serialize};
-use std::path::PathBuf;
+use similar::{ChangeTag, TextDiff};
+use std::collections::BTreeMap;
+use std::path::{Path, PathBuf};
 use std::sync::Mutex;
 use uuid::Uuid;
 
+use crate::error::{AppError, AppResult, ErrorKind};
+
+/// Minimum time between automatic snapshots of small edits
+const SNAPSHOT_INTERVAL_SECS: i64 = 5 * 60;
+/// Edits touching at least this many characters are snapshotted immediately
+const SNAPSHOT_MIN_CHANGED_CHARS: usize = 200;
+
 /// Draft status in the workflow
 #[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
 #[serde(rename_all = "lowercase")]
@@ -40,6 +49,10 @@ impl From<DraftStatus> for String {
     }
 }
 
+/// Frontmatter fields the editor doesn't manage (e.g. `canonical_url`,
+/// `series`, `author`), written as-is on publish and sync
+pub type ExtraFields = BTreeMap<String, serde_json::Value>;
+
 /// Full draft data for save/load operations
 #[derive(Debug, Clone, Serialize, Deserialize)]
 #[serde(rename_all = "camelCase")]
@@ -52,6 +65,9 @@ pub struct Draft {
     pub description: String,
     pub cover: String,
     pub cover_position: Option<f64>,
+    /// Alt text for the cover image
+    #[serde(default)]
+    pub cover_alt: String,
     pub content: String,      // Lexical JSON as string
     pub text_content: String, // Plain text for word count
     pub created_at: String,
@@ -59,6 +75,14 @@ pub struct Draft {
     pub synced_at: Option<String>,
     pub published_at: Option<String>,
     pub status: DraftStatus,
+    #[serde(default)]
+    pub extra_fields: ExtraFields,
+    /// When the published article was last taken down with `unpublish_draft`
+    #[serde(default)]
+    pub retracted_at: Option<String>,
+    /// Why it was taken down, as given when unpublishing
+    #[serde(default)]
+    pub retraction_reason: Option<String>,
 }
 
 /// Summary for draft list (lighter weight)
@@ -71,46 +95,257 @@ pub struct DraftSummary {
     pub updated_at: String,
 }
 
+/// A numbered schema change, applied once and recorded in `PRAGMA user_version`
+struct Migration {
+    version: u32,
+    description: &'static str,
+    up: fn(&Connection) -> AppResult<()>,
+}
+
+/// All schema migrations, in order. Never edit or reorder a released entry;
+/// append a new one instead.
+const MIGRATIONS: &[Migration] = &[
+    Migration {
+        version: 1,
+        description: "create drafts table",
+        up: Database::migrate_create_drafts,
+    },
+    Migration {
+        version: 2,
+        description: "add version history",
+        up: Database::migrate_add_versions,
+    },
+    Migration {
+        version: 3,
+        description: "add full-text search index",
+        up: Database::migrate_add_search_index,
+    },
+    Migration {
+        version: 4,
+        description: "add trash bin",
+        up: Database::migrate_add_trash,
+    },
+    Migration {
+        version: 5,
+        description: "move tags into tags and draft_tags tables",
+        up: Database::migrate_normalize_tags,
+    },
+    Migration {
+        version: 6,
+        description: "add custom frontmatter fields",
+        up: Database::migrate_add_extra_fields,
+    },
+    Migration {
+        version: 7,
+        description: "add cover alt text",
+        up: Database::migrate_add_cover_alt,
+    },
+    Migration {
+        version: 8,
+        description: "add retraction record",
+        up: Database::migrate_add_retraction,
+    },
+];
+
+/// A draft in the trash bin
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct TrashedDraft {
+    #[serde(flatten)]
+    pub draft: DraftSummary,
+    pub deleted_at: String,
+}
+
+/// A tag and how many drafts use it
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct TagUsage {
+    pub name: String,
+    pub count: i64,
+}
+
+/// A full-text search hit: the draft summary plus highlighted matches
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct DraftSearchResult {
+    #[serde(flatten)]
+    pub draft: DraftSummary,
+    pub title_highlight: String,
+    pub snippet: Option<String>,
+}
+
+/// A stored snapshot of a draft's content
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Version {
+    pub id: String,
+    pub draft_id: String,
+    pub content: String,      // Lexical JSON as string
+    pub text_content: String, // Plain text for diffing
+    pub word_count: i64,
+    pub created_at: String,
+}
+
+/// Summary for version list (no content)
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct VersionSummary {
+    pub id: String,
+    pub draft_id: String,
+    pub word_count: i64,
+    pub created_at: String,
+}
+
+/// A single line of a version diff
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct DiffLine {
+    pub tag: String, // "equal", "insert" or "delete"
+    pub text: String,
+}
+
+/// Line diff between a version and the draft's current content
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct VersionDiff {
+    pub version_id: String,
+    pub draft_id: String,
+    pub added_lines: usize,
+    pub removed_lines: usize,
+    pub lines: Vec<DiffLine>,
+}
+
+/// How many snapshots are kept for each draft
+#[derive(Debug, Clone, Copy)]
+pub struct VersionPolicy {
+    pub max_versions: usize,
+}
+
+impl Default for VersionPolicy {
+    fn default() -> Self {
+        VersionPolicy { max_versions: 10 }
+    }
+}
+
 /// Thread-safe database wrapper
 pub struct Database {
     conn: Mutex<Connection>,
+    version_policy: Mutex<VersionPolicy>,
 }
 
 impl Database {
     /// Create or open the database at the app data directory
-    pub fn new() -> Result<Self, String> {
+    pub fn new() -> AppResult<Self> {
         let db_path = Self::get_db_path()?;
 
         // Ensure parent directory exists
         if let Some(parent) = db_path.parent() {
             std::fs::create_dir_all(parent)
-                .map_err(|e| format!("Failed to create database directory: {}", e))?;
+                .map_err(|e| AppError::io("Failed to create database directory", e))?;
         }
 
         let conn = Connection::open(&db_path)
-            .map_err(|e| format!("Failed to open database: {}", e))?;
+            .map_err(|e| AppError::database("Failed to open database", e))?;
 
         let db = Database {
             conn: Mutex::new(conn),
+            version_policy: Mutex::new(VersionPolicy::default()),
         };
 
-        db.init_schema()?;
+        db.run_migrations(&db_path)?;
         Ok(db)
     }
 
     /// Get the database file path
-    fn get_db_path() -> Result<PathBuf, String> {
+    fn get_db_path() -> AppResult<PathBuf> {
         let data_dir = dirs::data_dir()
-            .ok_or("Could not determine app data directory")?
+            .ok_or_else(|| AppError::new(ErrorKind::Io, "Could not determine app data directory"))?
             .join("com.pranavhari.nibandh");
 
         Ok(data_dir.join("drafts.db"))
     }
 
-    /// Initialize database schema
-    fn init_schema(&self) -> Result<(), String> {
-        let conn = self.conn.lock().map_err(|e| e.to_string())?;
+    /// Bring the schema up to date by applying every pending migration.
+    ///
+    /// The applied version is tracked in `PRAGMA user_version`. Before anything
+    /// runs, the database file is copied next to itself so an upgrade can always
+    /// be undone by hand. Each migration runs in its own transaction; if one
+    /// fails, it is rolled back and the database stays at the previous version.
+    fn run_migrations(&self, db_path: &Path) -> AppResult<()> {
+        let mut conn = self.conn.lock()?;
+
+        let current: u32 = conn
+            .query_row("PRAGMA user_version", [], |row| row.get(0))
+            .map_err(|e| AppError::database("Failed to read schema version", e))?;
+        let latest = MIGRATIONS.last().map(|m| m.version).unwrap_or(0);
+
+        if current > latest {
+            return Err(AppError::new(
+                ErrorKind::Database,
+                format!(
+                    "Database schema version {} is newer than this version of Nibandh supports ({}). Please update the app.",
+                    current, latest
+                ),
+            ));
+        }
+        if current == latest {
+            return Ok(());
+        }
+
+        let backup_path = db_path.with_file_name(format!("drafts.v{}.bak.db", current));
+        let has_data = std::fs::metadata(db_path)
+            .map(|m| m.len() > 0)
+            .unwrap_or(false);
+        if has_data {
+            std::fs::copy(db_path, &backup_path).map_err(|e| {
+                AppError::io(
+                    format!(
+                        "Failed to back up database to {} before migrating",
+                        backup_path.display()
+                    ),
+                    e,
+                )
+            })?;
+        }
+
+        let mut applied = current;
+        for migration in MIGRATIONS.iter().filter(|m| m.version > current) {
+            let tx = conn
+                .transaction()
+                .map_err(|e| AppError::database("Failed to start migration transaction", e))?;
+
+            let result = (migration.up)(&tx)
+                .and_then(|_| {
+                    tx.pragma_update(None, "user_version", migration.version)
+                        .map_err(|e| AppError::database("Failed to record schema version", e))
+                })
+                .and_then(|_| {
+                    tx.commit()
+                        .map_err(|e| AppError::database("Failed to commit migration", e))
+                });
 
+            if let Err(e) = result {
+                return Err(AppError::database(
+                    format!(
+                        "Database migration {} ({}) failed. The database was left at schema version {}; a backup from before the upgrade is at {}",
+                        migration.version,
+                        migration.description,
+                        applied,
+                        backup_path.display()
+                    ),
+                    e,
+                ));
+            }
+
+            applied = migration.version;
+        }
+
+        Ok(())
+    }
+
+    /// Migration 1: drafts table.
+    /// Also adopts databases created before migrations were tracked.
+    fn migrate_create_drafts(conn: &Connection) -> AppResult<()> {
         conn.execute(
             "CREATE TABLE IF NOT EXISTS drafts (
                 id TEXT PRIMARY KEY,
@@ -131,36 +366,361 @@ impl Database {
             )",
             [],
         )
-        .map_err(|e| format!("Failed to create drafts table: {}", e))?;
+        .map_err(|e| AppError::database("Failed to create drafts table", e))?;
 
-        Self::add_column_if_missing(&conn, "drafts", "cover_position", "REAL")?;
+        Self::add_column_if_missing(conn, "drafts", "cover_position", "REAL")?;
 
         // Index for faster listing
         conn.execute(
             "CREATE INDEX IF NOT EXISTS idx_drafts_updated ON drafts(updated_at DESC)",
             [],
         )
-        .map_err(|e| format!("Failed to create index: {}", e))?;
+        .map_err(|e| AppError::database("Failed to create index", e))?;
+
+        Ok(())
+    }
+
+    /// Migration 2: version history
+    fn migrate_add_versions(conn: &Connection) -> AppResult<()> {
+        conn.execute(
+            "CREATE TABLE IF NOT EXISTS versions (
+                id TEXT PRIMARY KEY,
+                draft_id TEXT NOT NULL,
+                content TEXT NOT NULL,
+                text_content TEXT,
+                word_count INTEGER,
+                created_at TEXT
+            )",
+            [],
+        )
+        .map_err(|e| AppError::database("Failed to create versions table", e))?;
+
+        conn.execute(
+            "CREATE INDEX IF NOT EXISTS idx_versions_draft ON versions(draft_id, created_at DESC)",
+            [],
+        )
+        .map_err(|e| AppError::database("Failed to create versions index", e))?;
+
+        Ok(())
+    }
+
+    /// Migration 3: full-text search index
+    fn migrate_add_search_index(conn: &Connection) -> AppResult<()> {
+        conn.execute(
+            "CREATE VIRTUAL TABLE IF NOT EXISTS drafts_fts USING fts5(
+                draft_id UNINDEXED,
+                title,
+                description,
+                text_content,
+                tags,
+                tokenize = 'unicode61 remove_diacritics 2'
+            )",
+            [],
+        )
+        .map_err(|e| AppError::database("Failed to create search index", e))?;
+
+        // Backfill drafts saved before the search index existed. Tags that
+        // aren't valid JSON are left out rather than failing the upgrade.
+        conn.execute(
+            "INSERT INTO drafts_fts (draft_id, title, description, text_content, tags)
+             SELECT id, COALESCE(title, ''), COALESCE(description, ''),
+                    COALESCE(text_content, ''),
+                    COALESCE((SELECT group_concat(value, ' ') FROM json_each(
+                        CASE WHEN json_valid(drafts.tags) THEN drafts.tags ELSE '[]' END
+                    )), '')
+             FROM drafts
+             WHERE id NOT IN (SELECT draft_id FROM drafts_fts)",
+            [],
+        )
+        .map_err(|e| AppError::database("Failed to backfill search index", e))?;
+
+        Ok(())
+    }
+
+    /// Migration 4: soft delete
+    fn migrate_add_trash(conn: &Connection) -> AppResult<()> {
+        Self::add_column_if_missing(conn, "drafts", "deleted_at", "TEXT")?;
+
+        conn.execute(
+            "CREATE INDEX IF NOT EXISTS idx_drafts_deleted ON drafts(deleted_at)",
+            [],
+        )
+        .map_err(|e| AppError::database("Failed to create trash index", e))?;
+
+        Ok(())
+    }
+
+    /// Migration 5: normalized tags.
+    /// Tags move out of the JSON `drafts.tags` column into a `tags` table and a
+    /// `draft_tags` join table that keeps each draft's tag order.
+    fn migrate_normalize_tags(conn: &Connection) -> AppResult<()> {
+        conn.execute(
+            "CREATE TABLE IF NOT EXISTS tags (
+                id INTEGER PRIMARY KEY,
+                name TEXT NOT NULL UNIQUE COLLATE NOCASE
+            )",
+            [],
+        )
+        .map_err(|e| AppError::database("Failed to create tags table", e))?;
+
+        conn.execute(
+            "CREATE TABLE IF NOT EXISTS draft_tags (
+                draft_id TEXT NOT NULL,
+                tag_id INTEGER NOT NULL,
+                position INTEGER NOT NULL,
+                PRIMARY KEY (draft_id, tag_id)
+            )",
+            [],
+        )
+        .map_err(|e| AppError::database("Failed to create draft_tags table", e))?;
+
+        conn.execute(
+            "CREATE INDEX IF NOT EXISTS idx_draft_tags_tag ON draft_tags(tag_id)",
+            [],
+        )
+        .map_err(|e| AppError::database("Failed to create draft_tags index", e))?;
+
+        let valid_tags = "CASE WHEN json_valid(d.tags) THEN d.tags ELSE '[]' END";
+
+        conn.execute(
+            &format!(
+                "INSERT OR IGNORE INTO tags (name)
+                 SELECT DISTINCT trim(j.value) FROM drafts d, json_each({}) j
+                 WHERE trim(j.value) <> ''",
+                valid_tags
+            ),
+            [],
+        )
+        .map_err(|e| AppError::database("Failed to migrate tags", e))?;
+
+        conn.execute(
+            &format!(
+                "INSERT OR IGNORE INTO draft_tags (draft_id, tag_id, position)
+                 SELECT d.id, t.id, j.key FROM drafts d, json_each({}) j
+                 JOIN tags t ON t.name = trim(j.value)",
+                valid_tags
+            ),
+            [],
+        )
+        .map_err(|e| AppError::database("Failed to migrate draft tags", e))?;
+
+        conn.execute("ALTER TABLE drafts DROP COLUMN tags", [])
+            .map_err(|e| AppError::database("Failed to drop legacy tags column", e))?;
+
+        Ok(())
+    }
+
+    /// Replace the tags of a draft, keeping their order
+    fn set_draft_tags(conn: &Connection, draft_id: &str, tags: &[String]) -> AppResult<()> {
+        conn.execute("DELETE FROM draft_tags WHERE draft_id = ?1", params![draft_id])
+            .map_err(|e| AppError::database("Failed to clear draft tags", e))?;
+
+        for (position, tag) in tags.iter().map(|t| t.trim()).filter(|t| !t.is_empty()).enumerate() {
+            conn.execute("INSERT OR IGNORE INTO tags (name) VALUES (?1)", params![tag])
+                .map_err(|e| AppError::database("Failed to save tag", e))?;
+            conn.execute(
+                "INSERT OR IGNORE INTO draft_tags (draft_id, tag_id, position)
+                 SELECT ?1, id, ?2 FROM tags WHERE name = ?3",
+                params![draft_id, position as i64, tag],
+            )
+            .map_err(|e| AppError::database("Failed to save draft tag", e))?;
+        }
+
+        Self::prune_unused_tags(conn)
+    }
 
+    fn query_tags(conn: &Connection, draft_id: &str) -> AppResult<Vec<String>> {
+        let mut stmt = conn
+            .prepare(
+                "SELECT t.name FROM draft_tags dt JOIN tags t ON t.id = dt.tag_id
+                 WHERE dt.draft_id = ?1 ORDER BY dt.position",
+            )
+            .map_err(|e| AppError::database("Failed to prepare statement", e))?;
+
+        let tags = stmt
+            .query_map(params![draft_id], |row| row.get(0))
+            .map_err(|e| AppError::database("Failed to query tags", e))?
+            .filter_map(|r| r.ok())
+            .collect();
+
+        Ok(tags)
+    }
+
+    fn prune_unused_tags(conn: &Connection) -> AppResult<()> {
+        conn.execute(
+            "DELETE FROM tags WHERE id NOT IN (SELECT tag_id FROM draft_tags)",
+            [],
+        )
+        .map_err(|e| AppError::database("Failed to prune tags", e))?;
         Ok(())
     }
 
+    /// Every tag with the number of (non-trashed) drafts using it
+    pub fn tag_usage(&self) -> AppResult<Vec<TagUsage>> {
+        let conn = self.conn.lock()?;
+
+        let mut stmt = conn
+            .prepare(
+                "SELECT t.name, COUNT(d.id) FROM tags t
+                 JOIN draft_tags dt ON dt.tag_id = t.id
+                 LEFT JOIN drafts d ON d.id = dt.draft_id AND d.deleted_at IS NULL
+                 GROUP BY t.id ORDER BY COUNT(d.id) DESC, t.name",
+            )
+            .map_err(|e| AppError::database("Failed to prepare statement", e))?;
+
+        let usage = stmt
+            .query_map([], |row| {
+                Ok(TagUsage {
+                    name: row.get(0)?,
+                    count: row.get(1)?,
+                })
+            })
+            .map_err(|e| AppError::database("Failed to query tags", e))?
+            .filter_map(|r| r.ok())
+            .collect();
+
+        Ok(usage)
+    }
+
+    /// Rename a tag on every draft. Renaming onto an existing tag merges them.
+    /// Returns the number of drafts that were changed.
+    pub fn rename_tag(&self, from: &str, to: &str) -> AppResult<usize> {
+        self.merge_tags(&[from.to_string()], to)
+    }
+
+    /// Replace each of `sources` with `target` on every draft.
+    /// Returns the number of drafts that were changed.
+    pub fn merge_tags(&self, sources: &[String], target: &str) -> AppResult<usize> {
+        let target = target.trim();
+        if target.is_empty() {
+            return Err(AppError::invalid("Tag name cannot be empty"));
+        }
+
+        let mut conn = self.conn.lock()?;
+        let tx = conn
+            .transaction()
+            .map_err(|e| AppError::database("Failed to start transaction", e))?;
+
+        let mut affected: Vec<String> = Vec::new();
+        for source in sources.iter().map(|s| s.trim()) {
+            let source_id: Option<i64> = tx
+                .query_row("SELECT id FROM tags WHERE name = ?1", params![source], |row| {
+                    row.get(0)
+                })
+                .optional()
+                .map_err(|e| AppError::database("Failed to look up tag", e))?;
+            let source_id = match source_id {
+                Some(id) => id,
+                None => continue,
+            };
+
+            let mut stmt = tx
+                .prepare("SELECT draft_id FROM draft_tags WHERE tag_id = ?1")
+                .map_err(|e| AppError::database("Failed to prepare statement", e))?;
+            let draft_ids: Vec<String> = stmt
+                .query_map(params![source_id], |row| row.get(0))
+                .map_err(|e| AppError::database("Failed to query tagged drafts", e))?
+                .filter_map(|r| r.ok())
+                .collect();
+            drop(stmt);
+
+            let target_id: Option<i64> = tx
+                .query_row("SELECT id FROM tags WHERE name = ?1", params![target], |row| {
+                    row.get(0)
+                })
+                .optional()
+                .map_err(|e| AppError::database("Failed to look up tag", e))?;
+
+            match target_id {
+                Some(target_id) if target_id != source_id => {
+                    tx.execute(
+                        "INSERT OR IGNORE INTO draft_tags (draft_id, tag_id, position)
+                         SELECT draft_id, ?1, position FROM draft_tags WHERE tag_id = ?2",
+                        params![target_id, source_id],
+                    )
+                    .map_err(|e| AppError::database("Failed to merge tags", e))?;
+                    tx.execute("DELETE FROM draft_tags WHERE tag_id = ?1", params![source_id])
+                        .map_err(|e| AppError::database("Failed to merge tags", e))?;
+                    tx.execute("DELETE FROM tags WHERE id = ?1", params![source_id])
+                        .map_err(|e| AppError::database("Failed to merge tags", e))?;
+                }
+                // A plain rename, including a change of case only
+                _ => {
+                    tx.execute(
+                        "UPDATE tags SET name = ?1 WHERE id = ?2",
+                        params![target, source_id],
+                    )
+                    .map_err(|e| AppError::database("Failed to rename tag", e))?;
+                }
+            }
+
+            for id in draft_ids {
+                if !affected.contains(&id) {
+                    affected.push(id);
+                }
+            }
+        }
+
+        for id in &affected {
+            Self::reindex_draft(&tx, id)?;
+        }
+
+        tx.commit()
+            .map_err(|e| AppError::database("Failed to commit tag changes", e))?;
+
+        Ok(affected.len())
+    }
+
+    /// Rebuild the search index row of a single draft from the drafts table
+    fn reindex_draft(conn: &Connection, id: &str) -> AppResult<()> {
+        conn.execute("DELETE FROM drafts_fts WHERE draft_id = ?1", params![id])
+            .map_err(|e| AppError::database("Failed to clear search index", e))?;
+
+        conn.execute(
+            &format!(
+                "INSERT INTO drafts_fts (draft_id, title, description, text_content, tags)
+                 SELECT {} FROM drafts WHERE id = ?1",
+                FTS_SOURCE_COLUMNS
+            ),
+            params![id],
+        )
+        .map_err(|e| AppError::database("Failed to update search index", e))?;
+
+        Ok(())
+    }
+
+    /// Migration 6: custom frontmatter fields, stored as a JSON object
+    fn migrate_add_extra_fields(conn: &Connection) -> AppResult<()> {
+        Self::add_column_if_missing(conn, "drafts", "extra_fields", "TEXT")
+    }
+
+    /// Migration 7: cover image alt text
+    fn migrate_add_cover_alt(conn: &Connection) -> AppResult<()> {
+        Self::add_column_if_missing(conn, "drafts", "cover_alt", "TEXT")
+    }
+
+    /// Migration 8: when and why a published article was unpublished
+    fn migrate_add_retraction(conn: &Connection) -> AppResult<()> {
+        Self::add_column_if_missing(conn, "drafts", "retracted_at", "TEXT")?;
+        Self::add_column_if_missing(conn, "drafts", "retraction_reason", "TEXT")
+    }
+
     fn add_column_if_missing(
         conn: &Connection,
         table: &str,
         column: &str,
         column_type: &str,
-    ) -> Result<(), String> {
+    ) -> AppResult<()> {
         let mut stmt = conn
             .prepare(&format!("PRAGMA table_info({})", table))
-            .map_err(|e| format!("Failed to read table info: {}", e))?;
+            .map_err(|e| AppError::database("Failed to read table info", e))?;
         let mut rows = stmt
             .query([])
-            .map_err(|e| format!("Failed to query table info: {}", e))?;
+            .map_err(|e| AppError::database("Failed to query table info", e))?;
 
         while let Some(row) = rows
             .next()
-            .map_err(|e| format!("Failed to read table info row: {}", e))?
+            .map_err(|e| AppError::database("Failed to read table info row", e))?
         {
             let name: String = row.get(1).unwrap_or_default();
             if name == column {
@@ -172,38 +732,67 @@ impl Database {
             &format!("ALTER TABLE {} ADD COLUMN {} {}", table, column, column_type),
             [],
         )
-        .map_err(|e| format!("Failed to add column {}: {}", column, e))?;
+        .map_err(|e| AppError::database(format!("Failed to add column {}", column), e))?;
 
         Ok(())
     }
 
+    /// Replace the version retention policy (driven by settings)
+    pub fn set_version_policy(&self, policy: VersionPolicy) -> AppResult<()> {
+        let mut current = self.version_policy.lock()?;
+        *current = policy;
+        Ok(())
+    }
+
     /// Save a draft (create or update)
-    pub fn save_draft(&self, mut draft: Draft) -> Result<Draft, String> {
-        let conn = self.conn.lock().map_err(|e| e.to_string())?;
+    pub fn save_draft(&self, draft: Draft) -> AppResult<Draft> {
+        self.write_draft(draft, false)
+    }
+
+    /// Save a draft read from the blog repo. Its `updated_at` (the file's
+    /// `last_updated`) is kept so a later import or pull can tell which side is newer.
+    pub fn save_imported_draft(&self, draft: Draft) -> AppResult<Draft> {
+        self.write_draft(draft, true)
+    }
+
+    fn write_draft(&self, mut draft: Draft, keep_timestamps: bool) -> AppResult<Draft> {
+        let policy = *self.version_policy.lock()?;
+        let mut conn = self.conn.lock()?;
+        let tx = conn
+            .transaction()
+            .map_err(|e| AppError::database("Failed to start transaction", e))?;
         let now = Utc::now().to_rfc3339();
 
         // Generate ID if new draft
         if draft.id.is_empty() {
             draft.id = Uuid::new_v4().to_string();
+            if !keep_timestamps {
+                draft.created_at = now.clone();
+            }
+        } else {
+            Self::snapshot_if_changed(&tx, &draft, &now, policy)?;
+        }
+        if draft.created_at.is_empty() {
             draft.created_at = now.clone();
         }
-        draft.updated_at = now;
+        if !keep_timestamps || draft.updated_at.is_empty() {
+            draft.updated_at = now;
+        }
 
-        let tags_json = serde_json::to_string(&draft.tags)
-            .map_err(|e| format!("Failed to serialize tags: {}", e))?;
         let status_str: String = draft.status.clone().into();
+        let extra_fields = serde_json::to_string(&draft.extra_fields)
+            .map_err(|e| AppError::database("Failed to serialize custom fields", e))?;
 
-        conn.execute(
+        tx.execute(
             "INSERT INTO drafts (
-                id, slug, title, date, tags, description, cover,
+                id, slug, title, date, description, cover,
                 cover_position, content, text_content, created_at, updated_at,
-                synced_at, published_at, status
-            ) VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9, ?10, ?11, ?12, ?13, ?14, ?15)
+                synced_at, published_at, status, extra_fields, cover_alt
+            ) VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9, ?10, ?11, ?12, ?13, ?14, ?15, ?16)
             ON CONFLICT(id) DO UPDATE SET
                 slug = excluded.slug,
                 title = excluded.title,
                 date = excluded.date,
-                tags = excluded.tags,
                 description = excluded.description,
                 cover = excluded.cover,
                 cover_position = excluded.cover_position,
@@ -212,13 +801,14 @@ impl Database {
                 updated_at = excluded.updated_at,
                 synced_at = excluded.synced_at,
                 published_at = excluded.published_at,
-                status = excluded.status",
+                status = excluded.status,
+                extra_fields = excluded.extra_fields,
+                cover_alt = excluded.cover_alt",
             params![
                 draft.id,
                 draft.slug,
                 draft.title,
                 draft.date,
-                tags_json,
                 draft.description,
                 draft.cover,
                 draft.cover_position,
@@ -229,63 +819,358 @@ impl Database {
                 draft.synced_at,
                 draft.published_at,
                 status_str,
+                extra_fields,
+                draft.cover_alt,
             ],
         )
-        .map_err(|e| format!("Failed to save draft: {}", e))?;
+        .map_err(|e| AppError::database("Failed to save draft", e))?;
+
+        Self::set_draft_tags(&tx, &draft.id, &draft.tags)?;
+        Self::reindex_draft(&tx, &draft.id)?;
+
+        tx.commit()
+            .map_err(|e| AppError::database("Failed to commit draft", e))?;
 
         Ok(draft)
     }
 
+    /// Snapshot the stored content of a draft before it is overwritten,
+    /// if the incoming content differs from it meaningfully
+    fn snapshot_if_changed(
+        conn: &Connection,
+        draft: &Draft,
+        now: &str,
+        policy: VersionPolicy,
+    ) -> AppResult<()> {
+        let stored: Option<(String, String)> = conn
+            .query_row(
+                "SELECT content, COALESCE(text_content, '') FROM drafts WHERE id = ?1",
+                params![draft.id],
+                |row| Ok((row.get(0)?, row.get(1)?)),
+            )
+            .optional()
+            .map_err(|e| AppError::database("Failed to read stored draft", e))?;
+
+        let (stored_content, stored_text) = match stored {
+            Some(stored) => stored,
+            None => return Ok(()),
+        };
+
+        if stored_content == draft.content || stored_text.trim().is_empty() {
+            return Ok(());
+        }
+
+        let last_snapshot: Option<(String, String)> = conn
+            .query_row(
+                "SELECT content, created_at FROM versions
+                 WHERE draft_id = ?1 ORDER BY created_at DESC LIMIT 1",
+                params![draft.id],
+                |row| Ok((row.get(0)?, row.get(1)?)),
+            )
+            .optional()
+            .map_err(|e| AppError::database("Failed to read latest version", e))?;
+
+        let due = match &last_snapshot {
+            None => true,
+            Some((content, _)) if *content == stored_content => false,
+            Some((_, created_at)) => {
+                let elapsed = chrono::DateTime::parse_from_rfc3339(created_at)
+                    .map(|t| Utc::now().signed_duration_since(t).num_seconds())
+                    .unwrap_or(i64::MAX);
+                elapsed >= SNAPSHOT_INTERVAL_SECS
+                    || changed_chars(&stored_text, &draft.text_content)
+                        >= SNAPSHOT_MIN_CHANGED_CHARS
+            }
+        };
+
+        if due {
+            Self::insert_version(conn, &draft.id, &stored_content, &stored_text, now, policy)?;
+        }
+
+        Ok(())
+    }
+
+    /// Insert a snapshot and prune the draft's history down to the policy limit
+    fn insert_version(
+        conn: &Connection,
+        draft_id: &str,
+        content: &str,
+        text_content: &str,
+        now: &str,
+        policy: VersionPolicy,
+    ) -> AppResult<()> {
+        conn.execute(
+            "INSERT INTO versions (id, draft_id, content, text_content, word_count, created_at)
+             VALUES (?1, ?2, ?3, ?4, ?5, ?6)",
+            params![
+                Uuid::new_v4().to_string(),
+                draft_id,
+                content,
+                text_content,
+                text_content.split_whitespace().count() as i64,
+                now,
+            ],
+        )
+        .map_err(|e| AppError::database("Failed to save version", e))?;
+
+        conn.execute(
+            "DELETE FROM versions WHERE draft_id = ?1 AND id NOT IN (
+                SELECT id FROM versions WHERE draft_id = ?1
+                ORDER BY created_at DESC LIMIT ?2
+            )",
+            params![draft_id, policy.max_versions.max(1) as i64],
+        )
+        .map_err(|e| AppError::database("Failed to prune versions", e))?;
+
+        Ok(())
+    }
+
+    /// List the versions of a draft (newest first)
+    pub fn list_versions(&self, draft_id: &str) -> AppResult<Vec<VersionSummary>> {
+        let conn = self.conn.lock()?;
+
+        let mut stmt = conn
+            .prepare(
+                "SELECT id, draft_id, COALESCE(word_count, 0), created_at
+                 FROM versions WHERE draft_id = ?1 ORDER BY created_at DESC",
+            )
+            .map_err(|e| AppError::database("Failed to prepare statement", e))?;
+
+        let versions = stmt
+            .query_map(params![draft_id], |row| {
+                Ok(VersionSummary {
+                    id: row.get(0)?,
+                    draft_id: row.get(1)?,
+                    word_count: row.get(2)?,
+                    created_at: row.get(3)?,
+                })
+            })
+            .map_err(|e| AppError::database("Failed to query versions", e))?
+            .filter_map(|r| r.ok())
+            .collect();
+
+        Ok(versions)
+    }
+
+    /// Get a single version by ID
+    pub fn get_version(&self, id: &str) -> AppResult<Option<Version>> {
+        let conn = self.conn.lock()?;
+        Self::query_version(&conn, id)
+    }
+
+    fn query_version(conn: &Connection, id: &str) -> AppResult<Option<Version>> {
+        conn.query_row(
+            "SELECT id, draft_id, content, COALESCE(text_content, ''),
+                    COALESCE(word_count, 0), created_at
+             FROM versions WHERE id = ?1",
+            params![id],
+            |row| {
+                Ok(Version {
+                    id: row.get(0)?,
+                    draft_id: row.get(1)?,
+                    content: row.get(2)?,
+                    text_content: row.get(3)?,
+                    word_count: row.get(4)?,
+                    created_at: row.get(5)?,
+                })
+            },
+        )
+        .optional()
+        .map_err(|e| AppError::database("Failed to get version", e))
+    }
+
+    /// Diff a version against the draft's current plain-text content
+    pub fn diff_version(&self, id: &str) -> AppResult<VersionDiff> {
+        let conn = self.conn.lock()?;
+
+        let version = Self::query_version(&conn, id)?
+            .ok_or_else(|| AppError::not_found(format!("Version not found: {}", id)))?;
+
+        let current_text: String = conn
+            .query_row(
+                "SELECT COALESCE(text_content, '') FROM drafts WHERE id = ?1",
+                params![version.draft_id],
+                |row| row.get(0),
+            )
+            .optional()
+            .map_err(|e| AppError::database("Failed to get draft", e))?
+            .ok_or_else(|| AppError::not_found(format!("Draft not found: {}", version.draft_id)))?;
+
+        let diff = TextDiff::from_lines(&version.text_content, &current_text);
+        let mut added_lines = 0;
+        let mut removed_lines = 0;
+        let lines = diff
+            .iter_all_changes()
+            .map(|change| {
+                let tag = match change.tag() {
+                    ChangeTag::Equal => "equal",
+                    ChangeTag::Insert => {
+                        added_lines += 1;
+                        "insert"
+                    }
+                    ChangeTag::Delete => {
+                        removed_lines += 1;
+                        "delete"
+                    }
+                };
+                DiffLine {
+                    tag: tag.to_string(),
+                    text: change.value().trim_end_matches('\n').to_string(),
+                }
+            })
+            .collect();
+
+        Ok(VersionDiff {
+            version_id: version.id,
+            draft_id: version.draft_id,
+            added_lines,
+            removed_lines,
+            lines,
+        })
+    }
+
+    /// Restore a version as the draft's content.
+    /// The current content is kept as a new version first, so nothing is lost.
+    pub fn restore_version(&self, id: &str) -> AppResult<Draft> {
+        let policy = *self.version_policy.lock()?;
+        let mut conn = self.conn.lock()?;
+        let tx = conn
+            .transaction()
+            .map_err(|e| AppError::database("Failed to start transaction", e))?;
+
+        let version = Self::query_version(&tx, id)?
+            .ok_or_else(|| AppError::not_found(format!("Version not found: {}", id)))?;
+
+        let (current_content, current_text): (String, String) = tx
+            .query_row(
+                "SELECT content, COALESCE(text_content, '') FROM drafts WHERE id = ?1",
+                params![version.draft_id],
+                |row| Ok((row.get(0)?, row.get(1)?)),
+            )
+            .optional()
+            .map_err(|e| AppError::database("Failed to get draft", e))?
+            .ok_or_else(|| AppError::not_found(format!("Draft not found: {}", version.draft_id)))?;
+
+        let now = Utc::now().to_rfc3339();
+        if current_content != version.content {
+            // The restored version itself must survive pruning
+            let policy = VersionPolicy {
+                max_versions: policy.max_versions.max(2),
+            };
+            Self::insert_version(
+                &tx,
+                &version.draft_id,
+                &current_content,
+                &current_text,
+                &now,
+                policy,
+            )?;
+        }
+
+        tx.execute(
+            "UPDATE drafts SET content = ?1, text_content = ?2, updated_at = ?3 WHERE id = ?4",
+            params![version.content, version.text_content, now, version.draft_id],
+        )
+        .map_err(|e| AppError::database("Failed to restore version", e))?;
+
+        Self::reindex_draft(&tx, &version.draft_id)?;
+
+        tx.commit()
+            .map_err(|e| AppError::database("Failed to commit restore", e))?;
+        drop(conn);
+
+        self.get_draft(&version.draft_id)?
+            .ok_or_else(|| AppError::not_found(format!("Draft not found: {}", version.draft_id)))
+    }
+
     /// Get a single draft by ID
-    pub fn get_draft(&self, id: &str) -> Result<Option<Draft>, String> {
-        let conn = self.conn.lock().map_err(|e| e.to_string())?;
+    pub fn get_draft(&self, id: &str) -> AppResult<Option<Draft>> {
+        let conn = self.conn.lock()?;
 
         let mut stmt = conn
             .prepare(
-                "SELECT id, slug, title, date, tags, description, cover,
+                "SELECT id, slug, title, date, description, cover,
                         cover_position, content, text_content, created_at, updated_at,
-                        synced_at, published_at, status
+                        synced_at, published_at, status, extra_fields, cover_alt,
+                        retracted_at, retraction_reason
                  FROM drafts WHERE id = ?1",
             )
-            .map_err(|e| format!("Failed to prepare statement: {}", e))?;
+            .map_err(|e| AppError::database("Failed to prepare statement", e))?;
 
         let draft = stmt
             .query_row(params![id], |row| {
-                let tags_json: String = row.get(4)?;
-                let tags: Vec<String> = serde_json::from_str(&tags_json).unwrap_or_default();
-                let status_str: String = row.get(14)?;
+                let status_str: String = row.get(13)?;
+                let extra_fields: Option<String> = row.get(14)?;
+                let cover_alt: Option<String> = row.get(15)?;
 
                 Ok(Draft {
                     id: row.get(0)?,
                     slug: row.get(1)?,
                     title: row.get(2)?,
                     date: row.get(3)?,
-                    tags,
-                    description: row.get(5)?,
-                    cover: row.get(6)?,
-                    cover_position: row.get(7)?,
-                    content: row.get(8)?,
-                    text_content: row.get(9)?,
-                    created_at: row.get(10)?,
-                    updated_at: row.get(11)?,
-                    synced_at: row.get(12)?,
-                    published_at: row.get(13)?,
+                    tags: Vec::new(),
+                    description: row.get(4)?,
+                    cover: row.get(5)?,
+                    cover_position: row.get(6)?,
+                    cover_alt: cover_alt.unwrap_or_default(),
+                    content: row.get(7)?,
+                    text_content: row.get(8)?,
+                    created_at: row.get(9)?,
+                    updated_at: row.get(10)?,
+                    synced_at: row.get(11)?,
+                    published_at: row.get(12)?,
                     status: DraftStatus::from(status_str),
+                    extra_fields: extra_fields
+                        .and_then(|json| serde_json::from_str(&json).ok())
+                        .unwrap_or_default(),
+                    retracted_at: row.get(16)?,
+                    retraction_reason: row.get(17)?,
                 })
             })
             .optional()
-            .map_err(|e| format!("Failed to get draft: {}", e))?;
+            .map_err(|e| AppError::database("Failed to get draft", e))?;
 
-        Ok(draft)
+        match draft {
+            Some(mut draft) => {
+                draft.tags = Self::query_tags(&conn, &draft.id)?;
+                Ok(Some(draft))
+            }
+            None => Ok(None),
+        }
+    }
+
+    /// Find the (non-trashed) draft linked to a published slug
+    pub fn find_draft_by_slug(&self, slug: &str) -> AppResult<Option<Draft>> {
+        let conn = self.conn.lock()?;
+
+        let id: Option<String> = conn
+            .query_row(
+                "SELECT id FROM drafts WHERE slug = ?1 AND deleted_at IS NULL
+                 ORDER BY updated_at DESC LIMIT 1",
+                params![slug],
+                |row| row.get(0),
+            )
+            .optional()
+            .map_err(|e| AppError::database("Failed to find draft", e))?;
+
+        drop(conn);
+
+        match id {
+            Some(ref draft_id) => self.get_draft(draft_id),
+            None => Ok(None),
+        }
     }
 
     /// List all drafts (summary only, sorted by updated_at desc)
-    pub fn list_drafts(&self) -> Result<Vec<DraftSummary>, String> {
-        let conn = self.conn.lock().map_err(|e| e.to_string())?;
+    pub fn list_drafts(&self) -> AppResult<Vec<DraftSummary>> {
+        let conn = self.conn.lock()?;
 
         let mut stmt = conn
-            .prepare("SELECT id, title, status, updated_at FROM drafts ORDER BY updated_at DESC")
-            .map_err(|e| format!("Failed to prepare statement: {}", e))?;
+            .prepare(
+                "SELECT id, title, status, updated_at FROM drafts
+                 WHERE deleted_at IS NULL ORDER BY updated_at DESC",
+            )
+            .map_err(|e| AppError::database("Failed to prepare statement", e))?;
 
         let drafts = stmt
             .query_map([], |row| {
@@ -297,38 +1182,243 @@ impl Database {
                     updated_at: row.get(3)?,
                 })
             })
-            .map_err(|e| format!("Failed to query drafts: {}", e))?
+            .map_err(|e| AppError::database("Failed to query drafts", e))?
+            .filter_map(|r| r.ok())
+            .collect();
+
+        Ok(drafts)
+    }
+
+    /// Full-text search over title, description, text and tags.
+    /// Results are ranked by relevance and can be narrowed by tag and status;
+    /// a blank query just applies the filters.
+    pub fn search_drafts(
+        &self,
+        query: &str,
+        tag: Option<&str>,
+        status: Option<DraftStatus>,
+    ) -> AppResult<Vec<DraftSearchResult>> {
+        let conn = self.conn.lock()?;
+        let status_str: Option<String> = status.map(String::from);
+
+        let map_row = |row: &rusqlite::Row| -> Result<DraftSearchResult> {
+            let status_str: String = row.get(2)?;
+            Ok(DraftSearchResult {
+                draft: DraftSummary {
+                    id: row.get(0)?,
+                    title: row.get(1)?,
+                    status: DraftStatus::from(status_str),
+                    updated_at: row.get(3)?,
+                },
+                title_highlight: row.get::<_, Option<String>>(4)?.unwrap_or_default(),
+                snippet: row.get(5)?,
+            })
+        };
+
+        let results = match fts_match_expression(query) {
+            Some(expr) => {
+                let mut stmt = conn
+                    .prepare(
+                        "SELECT d.id, d.title, d.status, d.updated_at,
+                                highlight(drafts_fts, 1, '<mark>', '</mark>'),
+                                snippet(drafts_fts, -1, '<mark>', '</mark>', '…', 16)
+                         FROM drafts_fts
+                         JOIN drafts d ON d.id = drafts_fts.draft_id
+                         WHERE drafts_fts MATCH ?1
+                           AND d.deleted_at IS NULL
+                           AND (?2 IS NULL OR EXISTS (
+                                SELECT 1 FROM draft_tags dt JOIN tags t ON t.id = dt.tag_id
+                                WHERE dt.draft_id = d.id AND t.name = ?2))
+                           AND (?3 IS NULL OR d.status = ?3)
+                         ORDER BY bm25(drafts_fts, 0.0, 10.0, 5.0, 1.0, 3.0)
+                         LIMIT 100",
+                    )
+                    .map_err(|e| AppError::database("Failed to prepare search", e))?;
+                let rows = stmt
+                    .query_map(params![expr, tag, status_str], map_row)
+                    .map_err(|e| AppError::database("Failed to search drafts", e))?;
+                rows.filter_map(|r| r.ok()).collect()
+            }
+            None => {
+                let mut stmt = conn
+                    .prepare(
+                        "SELECT d.id, d.title, d.status, d.updated_at, d.title, NULL
+                         FROM drafts d
+                         WHERE d.deleted_at IS NULL
+                           AND (?1 IS NULL OR EXISTS (
+                                SELECT 1 FROM draft_tags dt JOIN tags t ON t.id = dt.tag_id
+                                WHERE dt.draft_id = d.id AND t.name = ?1))
+                           AND (?2 IS NULL OR d.status = ?2)
+                         ORDER BY d.updated_at DESC",
+                    )
+                    .map_err(|e| AppError::database("Failed to prepare search", e))?;
+                let rows = stmt
+                    .query_map(params![tag, status_str], map_row)
+                    .map_err(|e| AppError::database("Failed to search drafts", e))?;
+                rows.filter_map(|r| r.ok()).collect()
+            }
+        };
+
+        Ok(results)
+    }
+
+    /// Move a draft to the trash (hidden from lists and search until restored)
+    pub fn delete_draft(&self, id: &str) -> AppResult<bool> {
+        let conn = self.conn.lock()?;
+        let now = Utc::now().to_rfc3339();
+
+        let rows_affected = conn
+            .execute(
+                "UPDATE drafts SET deleted_at = ?1 WHERE id = ?2 AND deleted_at IS NULL",
+                params![now, id],
+            )
+            .map_err(|e| AppError::database("Failed to delete draft", e))?;
+
+        Ok(rows_affected > 0)
+    }
+
+    /// List drafts in the trash (most recently deleted first)
+    pub fn list_trash(&self) -> AppResult<Vec<TrashedDraft>> {
+        let conn = self.conn.lock()?;
+
+        let mut stmt = conn
+            .prepare(
+                "SELECT id, title, status, updated_at, deleted_at FROM drafts
+                 WHERE deleted_at IS NOT NULL ORDER BY deleted_at DESC",
+            )
+            .map_err(|e| AppError::database("Failed to prepare statement", e))?;
+
+        let drafts = stmt
+            .query_map([], |row| {
+                let status_str: String = row.get(2)?;
+                Ok(TrashedDraft {
+                    draft: DraftSummary {
+                        id: row.get(0)?,
+                        title: row.get(1)?,
+                        status: DraftStatus::from(status_str),
+                        updated_at: row.get(3)?,
+                    },
+                    deleted_at: row.get(4)?,
+                })
+            })
+            .map_err(|e| AppError::database("Failed to query trash", e))?
             .filter_map(|r| r.ok())
             .collect();
 
         Ok(drafts)
     }
 
-    /// Delete a draft by ID
-    pub fn delete_draft(&self, id: &str) -> Result<bool, String> {
-        let conn = self.conn.lock().map_err(|e| e.to_string())?;
+    /// Take a draft out of the trash
+    pub fn restore_draft(&self, id: &str) -> AppResult<bool> {
+        let conn = self.conn.lock()?;
 
         let rows_affected = conn
-            .execute("DELETE FROM drafts WHERE id = ?1", params![id])
-            .map_err(|e| format!("Failed to delete draft: {}", e))?;
+            .execute(
+                "UPDATE drafts SET deleted_at = NULL WHERE id = ?1 AND deleted_at IS NOT NULL",
+                params![id],
+            )
+            .map_err(|e| AppError::database("Failed to restore draft", e))?;
 
         Ok(rows_affected > 0)
     }
 
+    /// Permanently delete a trashed draft along with its history
+    pub fn purge_draft(&self, id: &str) -> AppResult<bool> {
+        let mut conn = self.conn.lock()?;
+        let tx = conn
+            .transaction()
+            .map_err(|e| AppError::database("Failed to start transaction", e))?;
+
+        let purged = Self::purge_where(&tx, "id = ?1 AND deleted_at IS NOT NULL", params![id])?;
+
+        tx.commit()
+            .map_err(|e| AppError::database("Failed to commit purge", e))?;
+
+        Ok(purged > 0)
+    }
+
+    /// Permanently delete drafts that have been in the trash longer than
+    /// `retention_days`. A retention of 0 keeps trashed drafts forever.
+    pub fn purge_expired_trash(&self, retention_days: u32) -> AppResult<usize> {
+        if retention_days == 0 {
+            return Ok(0);
+        }
+
+        let mut conn = self.conn.lock()?;
+        let tx = conn
+            .transaction()
+            .map_err(|e| AppError::database("Failed to start transaction", e))?;
+        let cutoff = (Utc::now() - chrono::Duration::days(retention_days as i64)).to_rfc3339();
+
+        let purged = Self::purge_where(
+            &tx,
+            "deleted_at IS NOT NULL AND deleted_at < ?1",
+            params![cutoff],
+        )?;
+
+        tx.commit()
+            .map_err(|e| AppError::database("Failed to commit purge", e))?;
+
+        Ok(purged)
+    }
+
+    /// Hard-delete the drafts matching `condition`, plus their versions and
+    /// search index rows
+    fn purge_where(
+        conn: &Connection,
+        condition: &str,
+        params: &[&dyn rusqlite::ToSql],
+    ) -> AppResult<usize> {
+        conn.execute(
+            &format!(
+                "DELETE FROM versions WHERE draft_id IN (SELECT id FROM drafts WHERE {})",
+                condition
+            ),
+            params,
+        )
+        .map_err(|e| AppError::database("Failed to delete versions", e))?;
+
+        conn.execute(
+            &format!(
+                "DELETE FROM drafts_fts WHERE draft_id IN (SELECT id FROM drafts WHERE {})",
+                condition
+            ),
+            params,
+        )
+        .map_err(|e| AppError::database("Failed to clear search index", e))?;
+
+        conn.execute(
+            &format!(
+                "DELETE FROM draft_tags WHERE draft_id IN (SELECT id FROM drafts WHERE {})",
+                condition
+            ),
+            params,
+        )
+        .map_err(|e| AppError::database("Failed to delete draft tags", e))?;
+
+        let purged = conn
+            .execute(&format!("DELETE FROM drafts WHERE {}", condition), params)
+            .map_err(|e| AppError::database("Failed to purge drafts", e))?;
+
+        Self::prune_unused_tags(conn)?;
+
+        Ok(purged)
+    }
+
     /// Get the most recently updated draft
-    pub fn get_latest_draft(&self) -> Result<Option<Draft>, String> {
-        let conn = self.conn.lock().map_err(|e| e.to_string())?;
+    pub fn get_latest_draft(&self) -> AppResult<Option<Draft>> {
+        let conn = self.conn.lock()?;
 
         let mut stmt = conn
             .prepare(
-                "SELECT id FROM drafts ORDER BY updated_at DESC LIMIT 1",
+                "SELECT id FROM drafts WHERE deleted_at IS NULL ORDER BY updated_at DESC LIMIT 1",
             )
-            .map_err(|e| format!("Failed to prepare statement: {}", e))?;
+            .map_err(|e| AppError::database("Failed to prepare statement", e))?;
 
         let id: Option<String> = stmt
             .query_row([], |row| row.get::<_, String>(0))
             .optional()
-            .map_err(|e| format!("Failed to get latest draft: {}", e))?;
+            .map_err(|e| AppError::database("Failed to get latest draft", e))?;
 
         drop(stmt);
         drop(conn);
@@ -340,8 +1430,8 @@ impl Database {
     }
 
     /// Update draft status
-    pub fn update_status(&self, id: &str, status: DraftStatus) -> Result<(), String> {
-        let conn = self.conn.lock().map_err(|e| e.to_string())?;
+    pub fn update_status(&self, id: &str, status: DraftStatus) -> AppResult<()> {
+        let conn = self.conn.lock()?;
         let status_str: String = status.into();
         let now = Utc::now().to_rfc3339();
 
@@ -349,12 +1439,220 @@ impl Database {
             "UPDATE drafts SET status = ?1, updated_at = ?2 WHERE id = ?3",
             params![status_str, now, id],
         )
-        .map_err(|e| format!("Failed to update status: {}", e))?;
+        .map_err(|e| AppError::database("Failed to update status", e))?;
 
         Ok(())
     }
+
+    /// Set a draft back to `Draft` after its article was unpublished, recording
+    /// when and why. Returns the updated draft, if it exists.
+    pub fn retract_draft(&self, id: &str, reason: &str) -> AppResult<Option<Draft>> {
+        let conn = self.conn.lock()?;
+        let status_str: String = DraftStatus::Draft.into();
+        let now = Utc::now().to_rfc3339();
+        let reason = Some(reason.trim()).filter(|reason| !reason.is_empty());
+
+        conn.execute(
+            "UPDATE drafts SET status = ?1, retracted_at = ?2, retraction_reason = ?3,
+                    updated_at = ?2
+             WHERE id = ?4",
+            params![status_str, now, reason, id],
+        )
+        .map_err(|e| AppError::database("Failed to record retraction", e))?;
+
+        drop(conn);
+        self.get_draft(id)
+    }
+
+    /// Record how far the remote copy of a draft has been seen, without
+    /// touching `updated_at` (which tracks local edits)
+    pub fn mark_synced(&self, id: &str, synced_at: &str) -> AppResult<()> {
+        let conn = self.conn.lock()?;
+
+        conn.execute(
+            "UPDATE drafts SET synced_at = ?1 WHERE id = ?2",
+            params![synced_at, id],
+        )
+        .map_err(|e| AppError::database("Failed to update sync time", e))?;
+
+        Ok(())
+    }
+}
+
+/// Columns of the drafts table feeding the search index (tags flattened to words)
+const FTS_SOURCE_COLUMNS: &str = "id, COALESCE(title, ''), COALESCE(description, ''),
+    COALESCE(text_content, ''),
+    COALESCE((SELECT group_concat(t.name, ' ') FROM draft_tags dt
+              JOIN tags t ON t.id = dt.tag_id WHERE dt.draft_id = drafts.id), '')";
+
+/// Turn user input into a safe FTS5 expression: every word is quoted
+/// (so operators and punctuation are literal) and the last one is a prefix
+/// match, which keeps results updating while the user types.
+fn fts_match_expression(query: &str) -> Option<String> {
+    let terms: Vec<String> = query
+        .split_whitespace()
+        .map(|term| term.replace('"', ""))
+        .filter(|term| !term.is_empty())
+        .map(|term| format!("\"{}\"", term))
+        .collect();
+
+    if terms.is_empty() {
+        return None;
+    }
+
+    Some(format!("{}*", terms.join(" ")))
+}
+
+/// Rough size of an edit: characters outside the common prefix and suffix
+fn changed_chars(old: &str, new: &str) -> usize {
+    let old: Vec<char> = old.chars().collect();
+    let new: Vec<char> = new.chars().collect();
+    let prefix = old.iter().zip(&new).take_while(|(a, b)| a == b).count();
+    let suffix = old[prefix..]
+        .iter()
+        .rev()
+        .zip(new[prefix..].iter().rev())
+        .take_while(|(a, b)| a == b)
+        .count();
+    old.len().max(new.len()) - prefix - suffix
 }
 
 // Make Database Send + Sync for Tauri state management
 unsafe impl Send for Database {}
 unsafe impl Sync for Database {}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// The drafts table as it was before migrations were tracked (version 0)
+    const BASELINE_SCHEMA: &str = "CREATE TABLE drafts (
+        id TEXT PRIMARY KEY,
+        slug TEXT,
+        title TEXT,
+        date TEXT,
+        tags TEXT,
+        description TEXT,
+        cover TEXT,
+        cover_position REAL,
+        content TEXT,
+        text_content TEXT,
+        created_at TEXT,
+        updated_at TEXT,
+        synced_at TEXT,
+        published_at TEXT,
+        status TEXT DEFAULT 'draft'
+    )";
+
+    fn baseline(path: &Path) -> Database {
+        let conn = Connection::open(path).unwrap();
+        conn.execute(BASELINE_SCHEMA, []).unwrap();
+        for (id, title, tags) in [
+            ("a", "Tagged", "[\"Rust\", \" Tauri \", \"rust\"]"),
+            ("b", "Broken tags", "not json"),
+        ] {
+            conn.execute(
+                "INSERT INTO drafts (id, slug, title, date, tags, description, cover,
+                     content, text_content, created_at, updated_at, status)
+                 VALUES (?1, ?1, ?2, '2024-01-02', ?3, '', '', '{}', 'Body',
+                     '2024-01-02T00:00:00Z', '2024-01-02T00:00:00Z', 'synced')",
+                params![id, title, tags],
+            )
+            .unwrap();
+        }
+        Database {
+            conn: Mutex::new(conn),
+            version_policy: Mutex::new(VersionPolicy::default()),
+        }
+    }
+
+    fn user_version(db: &Database) -> u32 {
+        db.conn
+            .lock()
+            .unwrap()
+            .query_row("PRAGMA user_version", [], |row| row.get(0))
+            .unwrap()
+    }
+
+    fn has_column(db: &Database, table: &str, column: &str) -> bool {
+        let conn = db.conn.lock().unwrap();
+        let mut stmt = conn
+            .prepare(&format!("PRAGMA table_info({})", table))
+            .unwrap();
+        let columns: Vec<String> = stmt
+            .query_map([], |row| row.get(1))
+            .unwrap()
+            .map(|name| name.unwrap())
+            .collect();
+        columns.iter().any(|name| name == column)
+    }
+
+    #[test]
+    fn baseline_database_is_migrated_with_a_backup() {
+        let dir = std::env::temp_dir().join(format!("nibandh-db-test-{}", Uuid::new_v4()));
+        std::fs::create_dir_all(&dir).unwrap();
+        let path = dir.join("drafts.db");
+        let db = baseline(&path);
+        assert_eq!(user_version(&db), 0);
+
+        db.run_migrations(&path).unwrap();
+        let latest = MIGRATIONS.last().unwrap().version;
+        assert_eq!(user_version(&db), latest);
+
+        // Tags left the drafts table for draft_tags, in their original order
+        assert!(!has_column(&db, "drafts", "tags"));
+        let draft = db.get_draft("a").unwrap().unwrap();
+        assert_eq!(draft.title, "Tagged");
+        assert_eq!(draft.tags, ["Rust", "Tauri"]);
+        assert_eq!(draft.status, DraftStatus::Synced);
+        assert_eq!(draft.cover_alt, "");
+        assert!(db.get_draft("b").unwrap().unwrap().tags.is_empty());
+
+        // The backup is the database as it was before the upgrade
+        let backup = Connection::open(dir.join("drafts.v0.bak.db")).unwrap();
+        let version: u32 = backup
+            .query_row("PRAGMA user_version", [], |row| row.get(0))
+            .unwrap();
+        assert_eq!(version, 0);
+        let tags: String = backup
+            .query_row("SELECT tags FROM drafts WHERE id = 'a'", [], |row| {
+                row.get(0)
+            })
+            .unwrap();
+        assert_eq!(tags, "[\"Rust\", \" Tauri \", \"rust\"]");
+
+        // Nothing is pending the second time, so no new backup is made
+        db.run_migrations(&path).unwrap();
+        assert!(!dir.join(format!("drafts.v{}.bak.db", latest)).exists());
+
+        drop(backup);
+        drop(db);
+        let _ = std::fs::remove_dir_all(&dir);
+    }
+
+    #[test]
+    fn in_memory_database_is_migrated() {
+        let path = Path::new(":memory:");
+        let db = baseline(path);
+        db.run_migrations(path).unwrap();
+        assert_eq!(user_version(&db), MIGRATIONS.last().unwrap().version);
+        assert!(has_column(&db, "drafts", "retracted_at"));
+        assert_eq!(db.get_draft("a").unwrap().unwrap().tags, ["Rust", "Tauri"]);
+        assert_eq!(db.list_drafts().unwrap().len(), 2);
+    }
+
+    #[test]
+    fn newer_schema_is_refused() {
+        let path = Path::new(":memory:");
+        let db = baseline(path);
+        let newer = MIGRATIONS.last().unwrap().version + 1;
+        db.conn
+            .lock()
+            .unwrap()
+            .pragma_update(None, "user_version", newer)
+            .unwrap();
+        assert!(db.run_migrations(path).is_err());
+        assert_eq!(user_version(&db), newer);
+    }
+}
+
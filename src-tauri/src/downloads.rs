@@ -0,0 +1,168 @@
+use futures::stream::{self, StreamExt};
+use reqwest::header::{ETAG, IF_NONE_MATCH};
+use reqwest::{Client, StatusCode};
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+use std::collections::HashMap;
+use std::fs;
+use std::path::PathBuf;
+use std::time::Duration;
+
+use crate::error::{AppError, AppResult, ErrorKind};
+use crate::images::ImageOptions;
+
+/// Downloads remote images with a size cap and a check that the bytes are an
+/// image, keeping responses that carry an ETag in an on-disk cache so
+/// republishing only downloads images that changed
+pub struct ImageDownloader {
+    client: Client,
+    cache_dir: Option<PathBuf>,
+    max_bytes: u64,
+    concurrency: usize,
+}
+
+/// Sidecar of a cached download, next to the bytes
+#[derive(Serialize, Deserialize)]
+struct CacheEntry {
+    url: String,
+    etag: String,
+}
+
+impl ImageDownloader {
+    pub fn new(options: &ImageOptions) -> AppResult<Self> {
+        let client = Client::builder()
+            .timeout(Duration::from_secs(20))
+            .build()
+            .map_err(|e| AppError::network("Failed to create HTTP client", e))?;
+        Ok(ImageDownloader {
+            client,
+            cache_dir: dirs::cache_dir()
+                .map(|dir| dir.join("com.pranavhari.nibandh").join("images")),
+            // 0 turns the limit off, like `max_width`
+            max_bytes: match options.max_download_mb {
+                0 => u64::MAX,
+                mb => mb.saturating_mul(1024 * 1024),
+            },
+            concurrency: options.download_concurrency.max(1),
+        })
+    }
+
+    /// Download every URL, `download_concurrency` at a time
+    pub async fn download_all<'a>(&self, urls: &[&'a str]) -> HashMap<&'a str, AppResult<Vec<u8>>> {
+        stream::iter(urls.iter().copied())
+            .map(|url| async move { (url, self.download(url).await) })
+            .buffer_unordered(self.concurrency)
+            .collect()
+            .await
+    }
+
+    /// Download one image, or reuse the cached copy when the server reports
+    /// it unchanged
+    pub async fn download(&self, url: &str) -> AppResult<Vec<u8>> {
+        let cached = self.cached(url);
+        let mut request = self.client.get(url);
+        if let Some((etag, _)) = &cached {
+            request = request.header(IF_NONE_MATCH, etag);
+        }
+        let mut response = request
+            .send()
+            .await
+            .map_err(|e| AppError::network("Failed to download image", e))?;
+
+        if response.status() == StatusCode::NOT_MODIFIED {
+            if let Some((_, bytes)) = cached {
+                return Ok(bytes);
+            }
+        }
+        if !response.status().is_success() {
+            return Err(AppError::new(
+                ErrorKind::Network,
+                format!("Failed to download image: HTTP {}", response.status()),
+            ));
+        }
+
+        let too_large = || {
+            AppError::new(
+                ErrorKind::Image,
+                format!("Image is larger than {} MB", self.max_bytes / (1024 * 1024)),
+            )
+        };
+        if response
+            .content_length()
+            .is_some_and(|length| length > self.max_bytes)
+        {
+            return Err(too_large());
+        }
+        let etag = response
+            .headers()
+            .get(ETAG)
+            .and_then(|v| v.to_str().ok())
+            .map(str::to_string);
+
+        // Read in chunks so a server that lies about its length is cut off
+        let mut bytes = Vec::new();
+        while let Some(chunk) = response
+            .chunk()
+            .await
+            .map_err(|e| AppError::network("Failed to read image bytes", e))?
+        {
+            if (bytes.len() + chunk.len()) as u64 > self.max_bytes {
+                return Err(too_large());
+            }
+            bytes.extend_from_slice(&chunk);
+        }
+
+        if image::guess_format(&bytes).is_err() {
+            return Err(AppError::new(
+                ErrorKind::Image,
+                "Downloaded file is not a recognized image format",
+            ));
+        }
+        if let Some(etag) = etag {
+            self.store(url, &etag, &bytes);
+        }
+        Ok(bytes)
+    }
+
+    /// Sidecar and bytes paths for `url` in the cache
+    fn cache_paths(&self, url: &str) -> Option<(PathBuf, PathBuf)> {
+        let key: String = Sha256::digest(url.as_bytes())
+            .iter()
+            .map(|b| format!("{:02x}", b))
+            .collect();
+        let dir = self.cache_dir.as_ref()?;
+        Some((
+            dir.join(format!("{}.json", key)),
+            dir.join(format!("{}.bin", key)),
+        ))
+    }
+
+    /// The ETag and bytes of the cached copy of `url`
+    fn cached(&self, url: &str) -> Option<(String, Vec<u8>)> {
+        let (entry_path, bytes_path) = self.cache_paths(url)?;
+        let entry: CacheEntry = serde_json::from_slice(&fs::read(entry_path).ok()?).ok()?;
+        if entry.url != url {
+            return None;
+        }
+        Some((entry.etag, fs::read(bytes_path).ok()?))
+    }
+
+    /// Cache a download; failures only cost a download next time
+    fn store(&self, url: &str, etag: &str, bytes: &[u8]) {
+        let Some((entry_path, bytes_path)) = self.cache_paths(url) else {
+            return;
+        };
+        let entry = CacheEntry {
+            url: url.to_string(),
+            etag: etag.to_string(),
+        };
+        let stored = entry_path
+            .parent()
+            .is_some_and(|dir| fs::create_dir_all(dir).is_ok())
+            && fs::write(&bytes_path, bytes).is_ok()
+            && serde_json::to_vec(&entry).is_ok_and(|json| fs::write(&entry_path, json).is_ok());
+        if !stored {
+            eprintln!("Failed to cache image {}", url);
+        }
+    }
+}
@@ -0,0 +1,200 @@
+use serde::Serialize;
+use std::fmt;
+use std::process::Output;
+
+/// Stable error categories the frontend can match on to offer a recovery
+/// action. Serialized as camelCase strings; don't rename existing variants.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub enum ErrorKind {
+    /// The configured repository path does not exist
+    RepoNotFound,
+    /// The path exists but is not a git repository
+    NotAGitRepo,
+    /// Reading or writing settings failed
+    Settings,
+    /// SQLite or migration failure
+    Database,
+    /// Filesystem error
+    Io,
+    /// A draft, version or other record does not exist
+    NotFound,
+    /// Bad input from the caller (empty tag, invalid data URL, ...)
+    InvalidInput,
+    /// An image could not be decoded or encoded
+    Image,
+    /// The remote could not be reached
+    Network,
+    /// `git` could not be started or failed for an unclassified reason
+    Git,
+    /// `git commit` found no changes
+    NothingToCommit,
+    /// The remote rejected a push (usually because it has newer commits)
+    PushRejected,
+    /// A merge, pull or checkout stopped on conflicting changes
+    MergeConflict,
+    /// Git could not authenticate with the remote
+    GitAuthFailed,
+    /// The GitHub CLI is not installed
+    GhNotInstalled,
+    /// The GitHub CLI is installed but not logged in
+    GhNotAuthenticated,
+    /// Creating or merging a pull request failed
+    PullRequest,
+    /// The pre-publish audit found errors and the publish policy blocks on them
+    AuditFailed,
+    /// A blocking pre-publish validation rule failed
+    ValidationFailed,
+}
+
+/// Error returned by every command and `Database` method
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct AppError {
+    pub kind: ErrorKind,
+    pub message: String,
+    /// Extra context such as the underlying error or git stderr
+    pub details: Option<String>,
+}
+
+pub type AppResult<T> = Result<T, AppError>;
+
+impl AppError {
+    pub fn new(kind: ErrorKind, message: impl Into<String>) -> Self {
+        AppError {
+            kind,
+            message: message.into(),
+            details: None,
+        }
+    }
+
+    pub fn with_details(mut self, details: impl fmt::Display) -> Self {
+        let details = details.to_string();
+        self.details = Some(details.trim().to_string()).filter(|d| !d.is_empty());
+        self
+    }
+
+    pub fn database(message: impl Into<String>, err: impl fmt::Display) -> Self {
+        Self::new(ErrorKind::Database, message).with_details(err)
+    }
+
+    pub fn io(message: impl Into<String>, err: impl fmt::Display) -> Self {
+        Self::new(ErrorKind::Io, message).with_details(err)
+    }
+
+    pub fn image(message: impl Into<String>, err: impl fmt::Display) -> Self {
+        Self::new(ErrorKind::Image, message).with_details(err)
+    }
+
+    pub fn network(message: impl Into<String>, err: impl fmt::Display) -> Self {
+        Self::new(ErrorKind::Network, message).with_details(err)
+    }
+
+    pub fn settings(message: impl Into<String>, err: impl fmt::Display) -> Self {
+        Self::new(ErrorKind::Settings, message).with_details(err)
+    }
+
+    pub fn invalid(message: impl Into<String>) -> Self {
+        Self::new(ErrorKind::InvalidInput, message)
+    }
+
+    pub fn not_found(message: impl Into<String>) -> Self {
+        Self::new(ErrorKind::NotFound, message)
+    }
+
+    pub fn repo_not_found(path: impl fmt::Display) -> Self {
+        Self::new(
+            ErrorKind::RepoNotFound,
+            format!("Repository path does not exist: {}", path),
+        )
+    }
+
+    /// `git` or `gh` could not be started at all
+    pub fn spawn(program: &str, err: std::io::Error) -> Self {
+        let kind = match (program, err.kind()) {
+            ("gh", std::io::ErrorKind::NotFound) => ErrorKind::GhNotInstalled,
+            _ => ErrorKind::Git,
+        };
+        Self::new(kind, format!("Failed to run {}", program)).with_details(err)
+    }
+
+    /// A `git` command exited with an error; its stderr decides the kind
+    pub fn git(message: impl Into<String>, output: &Output) -> Self {
+        let stdout = String::from_utf8_lossy(&output.stdout);
+        let stderr = String::from_utf8_lossy(&output.stderr);
+        let kind = classify_git(&format!("{}\n{}", stdout, stderr));
+        Self::new(kind, message).with_details(stderr)
+    }
+
+    /// A `gh` command exited with an error
+    pub fn gh(message: impl Into<String>, output: &Output) -> Self {
+        let stderr = String::from_utf8_lossy(&output.stderr);
+        let lower = stderr.to_lowercase();
+        let kind = if lower.contains("gh auth login")
+            || lower.contains("not logged in")
+            || lower.contains("authentication")
+        {
+            ErrorKind::GhNotAuthenticated
+        } else {
+            match classify_git(&stderr) {
+                ErrorKind::Git => ErrorKind::PullRequest,
+                kind => kind,
+            }
+        };
+        Self::new(kind, message).with_details(stderr)
+    }
+}
+
+fn classify_git(output: &str) -> ErrorKind {
+    let lower = output.to_lowercase();
+    if lower.contains("nothing to commit") || lower.contains("nothing added to commit") {
+        ErrorKind::NothingToCommit
+    } else if lower.contains("[rejected]")
+        || lower.contains("non-fast-forward")
+        || lower.contains("fetch first")
+        || lower.contains("failed to push some refs")
+    {
+        ErrorKind::PushRejected
+    } else if lower.contains("conflict") || lower.contains("would be overwritten") {
+        ErrorKind::MergeConflict
+    } else if lower.contains("authentication failed")
+        || lower.contains("permission denied")
+        || lower.contains("could not read username")
+        || lower.contains("repository not found")
+    {
+        ErrorKind::GitAuthFailed
+    } else if lower.contains("could not resolve host")
+        || lower.contains("unable to access")
+        || lower.contains("connection timed out")
+        || lower.contains("network is unreachable")
+    {
+        ErrorKind::Network
+    } else if lower.contains("not a git repository") {
+        ErrorKind::NotAGitRepo
+    } else {
+        ErrorKind::Git
+    }
+}
+
+impl fmt::Display for AppError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match &self.details {
+            Some(details) => write!(f, "{}: {}", self.message, details),
+            None => write!(f, "{}", self.message),
+        }
+    }
+}
+
+impl std::error::Error for AppError {}
+
+impl From<rusqlite::Error> for AppError {
+    fn from(err: rusqlite::Error) -> Self {
+        AppError::database("Database error", err)
+    }
+}
+
+impl<T> From<std::sync::PoisonError<T>> for AppError {
+    fn from(err: std::sync::PoisonError<T>) -> Self {
+        AppError::database("Database lock poisoned", err)
+    }
+}
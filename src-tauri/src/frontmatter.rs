@@ -0,0 +1,486 @@
+use chrono::{DateTime, NaiveDate, Utc};
+use serde::Serialize;
+use serde_yaml::{Mapping, Value};
+
+use crate::database::ExtraFields;
+use crate::error::{AppError, AppResult};
+use crate::ssg::{DateStyle, FrontmatterFormat, SiteProfile};
+
+/// The frontmatter fields Nibandh writes when publishing or syncing a draft
+#[derive(Debug, Clone, Default)]
+pub struct ArticleMeta {
+    pub title: String,
+    pub date: String,
+    pub tags: Vec<String>,
+    pub description: String,
+    pub cover: String,
+    pub cover_position: Option<f64>,
+    pub cover_alt: String,
+    pub last_updated: Option<String>,
+    pub draft_id: Option<String>,
+    /// Every other field, e.g. `canonical_url` or `series`
+    pub extra: ExtraFields,
+}
+
+/// Frontmatter of a draft synced to its drafts branch
+#[derive(Debug, Serialize)]
+pub struct DraftFrontmatter<'a> {
+    pub title: &'a str,
+    pub date: &'a str,
+    pub tags: &'a [String],
+    pub description: &'a str,
+    pub cover: &'a str,
+    pub cover_position: f64,
+    #[serde(skip_serializing_if = "str::is_empty")]
+    pub cover_alt: &'a str,
+    pub last_updated: &'a str,
+    pub draft_id: &'a str,
+    /// Written after the fields above; keys they already use are skipped
+    #[serde(skip)]
+    pub extra: &'a ExtraFields,
+}
+
+impl DraftFrontmatter<'_> {
+    /// Render the `---` delimited block, followed by a blank line
+    pub fn render(&self) -> AppResult<String> {
+        let mut mapping = match serde_yaml::to_value(self) {
+            Ok(Value::Mapping(mapping)) => mapping,
+            Ok(_) => Mapping::new(),
+            Err(e) => {
+                return Err(AppError::invalid("Failed to write frontmatter").with_details(e))
+            }
+        };
+        for (key, value) in self.extra {
+            if mapping.contains_key(key.as_str()) {
+                continue;
+            }
+            let value = serde_yaml::to_value(value)
+                .map_err(|e| AppError::invalid("Failed to write frontmatter").with_details(e))?;
+            mapping.insert(Value::from(key.as_str()), value);
+        }
+        Ok(format!("---\n{}---\n\n", write_yaml(&mapping)))
+    }
+}
+
+/// Split a Markdown document into its YAML frontmatter and body.
+/// Returns `None` when the document has no `---` delimited frontmatter block.
+pub fn split_frontmatter(content: &str) -> Option<(&str, &str)> {
+    split_delimited(content, "---")
+}
+
+/// Split off a `+++` delimited TOML frontmatter block (Hugo, Zola)
+fn split_toml_frontmatter(content: &str) -> Option<(&str, &str)> {
+    split_delimited(content, "+++")
+}
+
+fn split_delimited<'a>(content: &'a str, delimiter: &str) -> Option<(&'a str, &'a str)> {
+    let rest = content.strip_prefix(delimiter)?;
+    let rest = rest
+        .strip_prefix('\n')
+        .or_else(|| rest.strip_prefix("\r\n"))?;
+
+    let mut offset = 0;
+    for line in rest.split_inclusive('\n') {
+        if line.trim_end_matches(['\r', '\n']) == delimiter {
+            let yaml = &rest[..offset];
+            let body = &rest[offset + line.len()..];
+            return Some((yaml, body));
+        }
+        offset += line.len();
+    }
+
+    None
+}
+
+/// Parse the frontmatter of a Markdown document into a YAML mapping.
+/// TOML frontmatter is converted to the same shape.
+pub fn parse_frontmatter(content: &str) -> Option<Mapping> {
+    let value = if let Some((yaml, _)) = split_frontmatter(content) {
+        serde_yaml::from_str::<Value>(yaml).ok()?
+    } else {
+        let (toml, _) = split_toml_frontmatter(content)?;
+        toml_to_yaml(toml::Value::Table(toml.parse().ok()?))
+    };
+    match value {
+        Value::Mapping(mapping) => Some(mapping),
+        _ => None,
+    }
+}
+
+fn toml_to_yaml(value: toml::Value) -> Value {
+    match value {
+        toml::Value::String(s) => Value::String(s),
+        toml::Value::Integer(i) => Value::from(i),
+        toml::Value::Float(f) => Value::from(f),
+        toml::Value::Boolean(b) => Value::Bool(b),
+        // Dates are read as their text, like quoted YAML dates
+        toml::Value::Datetime(datetime) => Value::String(datetime.to_string()),
+        toml::Value::Array(items) => Value::Sequence(items.into_iter().map(toml_to_yaml).collect()),
+        toml::Value::Table(table) => Value::Mapping(
+            table
+                .into_iter()
+                .map(|(key, value)| (Value::String(key), toml_to_yaml(value)))
+                .collect(),
+        ),
+    }
+}
+
+/// Parse a Markdown document written by Nibandh into its metadata and body,
+/// reading fields under the names `profile` writes them with. Synced drafts
+/// use `SiteProfile::drafts()`.
+pub fn parse_article<'a>(
+    content: &'a str,
+    profile: &SiteProfile,
+) -> Option<(ArticleMeta, &'a str)> {
+    let (_, body) = split_frontmatter(content).or_else(|| split_toml_frontmatter(content))?;
+    let mapping = parse_frontmatter(content)?;
+    let names = &profile.fields;
+    let field = |key: &str| lookup(&mapping, key).and_then(scalar);
+
+    let date = field(names.date).unwrap_or_default();
+    // Drafts hold a bare day; Hugo and Jekyll dates carry a time
+    let date = match parse_timestamp(&date) {
+        Some(time) if profile.date_style != DateStyle::Date => {
+            time.format("%Y-%m-%d").to_string()
+        }
+        _ => date,
+    };
+    let cover = match lookup(&mapping, names.cover) {
+        Some(Value::Sequence(items)) => items.first().and_then(scalar),
+        Some(value) => scalar(value),
+        None => None,
+    };
+
+    let meta = ArticleMeta {
+        title: field(names.title).unwrap_or_default(),
+        date,
+        tags: tags_from_mapping(&mapping, names.tags),
+        description: field(names.description).unwrap_or_default(),
+        cover: cover.unwrap_or_default(),
+        cover_position: names
+            .cover_position
+            .and_then(field)
+            .and_then(|p| p.parse().ok()),
+        cover_alt: field(names.cover_alt).unwrap_or_default(),
+        last_updated: names.updated.and_then(field).filter(|v| !v.is_empty()),
+        draft_id: field("draft_id").filter(|v| !v.is_empty()),
+        extra: extra_fields(&mapping, profile),
+    };
+
+    Some((meta, body.trim_start_matches(['\r', '\n'])))
+}
+
+/// The value at a dotted `key` (`taxonomies.tags`)
+fn lookup<'a>(mapping: &'a Mapping, key: &str) -> Option<&'a Value> {
+    match key.split_once('.') {
+        None => mapping.get(key),
+        Some((parent, rest)) => match mapping.get(parent)? {
+            Value::Mapping(child) => lookup(child, rest),
+            _ => None,
+        },
+    }
+}
+
+/// Remove a dotted `key`, and the tables it leaves empty
+fn remove_key(mapping: &mut Mapping, key: &str) {
+    match key.split_once('.') {
+        None => {
+            mapping.remove(key);
+        }
+        Some((parent, rest)) => {
+            if let Some(Value::Mapping(child)) = mapping.get_mut(parent) {
+                remove_key(child, rest);
+                if child.is_empty() {
+                    mapping.remove(parent);
+                }
+            }
+        }
+    }
+}
+
+/// Every field `parse_article` doesn't read itself, as JSON values. Fields in
+/// the profile's custom table (Zola's `[extra]`) come back at the top level,
+/// where the draft keeps them.
+fn extra_fields(mapping: &Mapping, profile: &SiteProfile) -> ExtraFields {
+    let names = &profile.fields;
+    let mut rest = mapping.clone();
+    let managed = [
+        Some(names.title),
+        Some(names.date),
+        names.updated,
+        Some(names.tags),
+        Some(names.description),
+        Some(names.cover),
+        names.cover_position,
+        Some(names.cover_alt),
+        Some("draft_id"),
+    ];
+    for key in managed.into_iter().flatten() {
+        remove_key(&mut rest, key);
+    }
+    for (key, _) in profile.fixed {
+        rest.remove(*key);
+    }
+    if let Some(table) = profile.custom_table {
+        if let Some(Value::Mapping(custom)) = rest.remove(table) {
+            for (key, value) in custom {
+                if !rest.contains_key(&key) {
+                    rest.insert(key, value);
+                }
+            }
+        }
+    }
+
+    rest.iter()
+        .filter_map(|(key, value)| {
+            Some((key.as_str()?.to_string(), serde_json::to_value(value).ok()?))
+        })
+        .collect()
+}
+
+/// Read a scalar field as a string (numbers and booleans included)
+fn scalar(value: &Value) -> Option<String> {
+    match value {
+        Value::String(s) => Some(s.clone()),
+        Value::Number(n) => Some(n.to_string()),
+        Value::Bool(b) => Some(b.to_string()),
+        _ => None,
+    }
+}
+
+/// Read the profile's tags field, accepting either a list or a
+/// comma-separated string
+pub fn read_tags(content: &str, profile: &SiteProfile) -> Vec<String> {
+    parse_frontmatter(content)
+        .map(|mapping| tags_from_mapping(&mapping, profile.fields.tags))
+        .unwrap_or_default()
+}
+
+fn tags_from_mapping(mapping: &Mapping, key: &str) -> Vec<String> {
+    let tags = match lookup(mapping, key) {
+        Some(Value::Sequence(items)) => items
+            .iter()
+            .filter_map(|item| match item {
+                Value::String(s) => Some(s.clone()),
+                Value::Number(n) => Some(n.to_string()),
+                _ => None,
+            })
+            .collect(),
+        Some(Value::String(s)) => s.split(',').map(|t| t.to_string()).collect(),
+        _ => Vec::new(),
+    };
+
+    tags.into_iter()
+        .map(|t: String| t.trim().to_string())
+        .filter(|t| !t.is_empty())
+        .collect()
+}
+
+/// Write `mapping` as block YAML. Strings are always double-quoted, so values
+/// like `2026-01-15` stay strings for YAML 1.1 parsers such as js-yaml.
+pub fn write_yaml(mapping: &Mapping) -> String {
+    let mut out = String::new();
+    write_mapping(&mut out, mapping, 0);
+    out
+}
+
+fn write_mapping(out: &mut String, mapping: &Mapping, indent: usize) {
+    for (key, value) in mapping {
+        out.push_str(&" ".repeat(indent));
+        match key {
+            Value::String(k) if is_plain_key(k) => out.push_str(k),
+            _ => out.push_str(&flow_value(key)),
+        }
+        out.push(':');
+        match value {
+            Value::Mapping(child) if !child.is_empty() => {
+                out.push('\n');
+                write_mapping(out, child, indent + 2);
+            }
+            _ => {
+                out.push(' ');
+                out.push_str(&flow_value(value));
+                out.push('\n');
+            }
+        }
+    }
+}
+
+fn flow_value(value: &Value) -> String {
+    match value {
+        Value::Null => "null".to_string(),
+        Value::Bool(b) => b.to_string(),
+        Value::Number(n) => n.to_string(),
+        Value::String(s) => quote_yaml(s),
+        Value::Sequence(items) => format!(
+            "[{}]",
+            items.iter().map(flow_value).collect::<Vec<_>>().join(", ")
+        ),
+        Value::Mapping(mapping) => format!(
+            "{{{}}}",
+            mapping
+                .iter()
+                .map(|(k, v)| format!("{}: {}", flow_value(k), flow_value(v)))
+                .collect::<Vec<_>>()
+                .join(", ")
+        ),
+        Value::Tagged(tagged) => flow_value(&tagged.value),
+    }
+}
+
+fn is_plain_key(key: &str) -> bool {
+    !key.is_empty()
+        && key
+            .chars()
+            .all(|c| c.is_ascii_alphanumeric() || c == '_' || c == '-')
+}
+
+/// A YAML double-quoted scalar. Line and paragraph separators are escaped
+/// too, since YAML treats them as line breaks.
+fn quote_yaml(s: &str) -> String {
+    let mut out = String::with_capacity(s.len() + 2);
+    out.push('"');
+    for c in s.chars() {
+        match c {
+            '"' => out.push_str("\\\""),
+            '\\' => out.push_str("\\\\"),
+            '\n' => out.push_str("\\n"),
+            '\r' => out.push_str("\\r"),
+            '\t' => out.push_str("\\t"),
+            '\u{85}' => out.push_str("\\N"),
+            '\u{2028}' => out.push_str("\\L"),
+            '\u{2029}' => out.push_str("\\P"),
+            c if c.is_control() || c == '\u{feff}' => {
+                out.push_str(&format!("\\u{:04x}", c as u32))
+            }
+            c => out.push(c),
+        }
+    }
+    out.push('"');
+    out
+}
+
+/// Parse a frontmatter or database timestamp: RFC 3339 or a bare `YYYY-MM-DD`
+pub fn parse_timestamp(value: &str) -> Option<DateTime<Utc>> {
+    let value = value.trim();
+    if let Ok(time) = DateTime::parse_from_rfc3339(value) {
+        return Some(time.with_timezone(&Utc));
+    }
+    // Jekyll's `2026-01-15 09:30:00 +0000`
+    if let Ok(time) = DateTime::parse_from_str(value, "%Y-%m-%d %H:%M:%S %z") {
+        return Some(time.with_timezone(&Utc));
+    }
+    NaiveDate::parse_from_str(value, "%Y-%m-%d")
+        .ok()
+        .and_then(|date| date.and_hms_opt(0, 0, 0))
+        .map(|time| time.and_utc())
+}
+
+/// Rewrite the profile's tags field of a document with `update`, leaving every
+/// other line untouched. YAML and TOML frontmatter are both handled, since
+/// Hugo reads either. Returns `None` if the document has no tags or nothing
+/// changed.
+pub fn rewrite_tags(
+    content: &str,
+    profile: &SiteProfile,
+    update: impl FnOnce(&[String]) -> Vec<String>,
+) -> Option<String> {
+    let tags = read_tags(content, profile);
+    if tags.is_empty() {
+        return None;
+    }
+
+    let updated = update(&tags);
+    if updated == tags {
+        return None;
+    }
+
+    let mut lines: Vec<String> = content.lines().map(str::to_string).collect();
+    let (format, end) = [FrontmatterFormat::Yaml, FrontmatterFormat::Toml]
+        .into_iter()
+        .find_map(|format| Some((format, format.end(&lines)?)))?;
+    let (start, key) = find_tags_key(&lines, end, format, profile.fields.tags)?;
+    let value = match format {
+        FrontmatterFormat::Yaml => format!(
+            "[{}]",
+            updated
+                .iter()
+                .map(|t| quote_yaml(t))
+                .collect::<Vec<_>>()
+                .join(", ")
+        ),
+        FrontmatterFormat::Toml => {
+            toml::Value::Array(updated.into_iter().map(toml::Value::String).collect())
+                .to_string()
+        }
+    };
+
+    // Drop the existing value, including block-style YAML list items and
+    // multi-line TOML arrays below the key
+    let mut stop = start + 1;
+    match format {
+        FrontmatterFormat::Yaml => {
+            while stop < end && (lines[stop].starts_with([' ', '-']) || lines[stop].is_empty()) {
+                stop += 1;
+            }
+        }
+        FrontmatterFormat::Toml => {
+            let opened = lines[start].matches('[').count();
+            let mut closed = lines[start].matches(']').count();
+            while closed < opened && stop < end {
+                closed += lines[stop].matches(']').count();
+                stop += 1;
+            }
+        }
+    }
+    lines.splice(start..stop.min(end), [format.line(key, &value)]);
+
+    let mut rewritten = lines.join("\n");
+    if content.ends_with('\n') {
+        rewritten.push('\n');
+    }
+    Some(rewritten)
+}
+
+/// The line holding the dotted `key` in the frontmatter ending at `end`, and
+/// the key as written there. In TOML, `taxonomies.tags` is `tags` under
+/// `[taxonomies]` or the dotted key before any table.
+fn find_tags_key<'a>(
+    lines: &[String],
+    end: usize,
+    format: FrontmatterFormat,
+    key: &'a str,
+) -> Option<(usize, &'a str)> {
+    let (table, name) = match key.rsplit_once('.') {
+        Some((table, name)) => (Some(table), name),
+        None => (None, key),
+    };
+    if format == FrontmatterFormat::Yaml {
+        // No YAML profile nests its tags
+        return match table {
+            None => format.find_key(lines, end, key).map(|index| (index, key)),
+            Some(_) => None,
+        };
+    }
+
+    let mut section: Option<&str> = None;
+    for (index, line) in lines.iter().enumerate().take(end).skip(1) {
+        let trimmed = line.trim();
+        if let Some(header) = trimmed.strip_prefix('[').and_then(|h| h.strip_suffix(']')) {
+            section = Some(header.trim());
+            continue;
+        }
+        let matches = |wanted: &str| {
+            trimmed
+                .strip_prefix(wanted)
+                .is_some_and(|rest| rest.trim_start().starts_with('='))
+        };
+        if section == table && matches(name) {
+            return Some((index, name));
+        }
+        if section.is_none() && table.is_some() && matches(key) {
+            return Some((index, key));
+        }
+    }
+    None
+}
@@ -0,0 +1,621 @@
+use serde::{Deserialize, Serialize};
+use std::fs;
+use std::path::{Path, PathBuf};
+use uuid::Uuid;
+
+use crate::error::{AppError, AppResult, ErrorKind};
+
+mod cli;
+mod libgit2;
+
+pub use cli::CliBackend;
+pub use libgit2::Libgit2Backend;
+
+/// Which `GitBackend` commands use, chosen in settings
+#[derive(Debug, Clone, Copy, Default, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub enum GitBackendKind {
+    /// Shell out to the `git` executable
+    #[default]
+    Cli,
+    /// Run git in-process with libgit2
+    Libgit2,
+}
+
+/// Result of a commit attempt
+#[derive(Debug, Clone, PartialEq)]
+pub enum CommitOutcome {
+    Committed,
+    NothingToCommit,
+}
+
+/// The git operations publish, sync and pull need. `dir` is the repository
+/// or worktree to operate on; revisions use git's usual syntax.
+pub trait GitBackend: Send + Sync {
+    /// Uncommitted changes in porcelain format (`XY path`), untracked included
+    fn status(&self, dir: &Path) -> AppResult<Vec<String>>;
+
+    /// The checked-out branch, or `None` when HEAD is detached or unborn
+    fn current_branch(&self, dir: &Path) -> AppResult<Option<String>>;
+
+    /// The commit id `rev` points to, or `None` if it doesn't exist
+    fn resolve(&self, dir: &Path, rev: &str) -> Option<String>;
+
+    /// Whether `ancestor` is reachable from (or equal to) `descendant`
+    fn is_ancestor(&self, dir: &Path, ancestor: &str, descendant: &str) -> AppResult<bool>;
+
+    /// Create the local branch `name` at `target`, or move it there. Fails if
+    /// the branch is checked out in any worktree.
+    fn branch(&self, dir: &Path, name: &str, target: &str) -> AppResult<()>;
+
+    /// Make the local branch `name` track `upstream` (e.g. `origin/main`)
+    fn set_upstream(&self, dir: &Path, name: &str, upstream: &str) -> AppResult<()>;
+
+    /// Check out `rev` with a detached HEAD, discarding local changes
+    fn checkout(&self, dir: &Path, rev: &str) -> AppResult<()>;
+
+    /// Stage additions, changes and deletions under `paths`
+    fn add(&self, dir: &Path, paths: &[&str]) -> AppResult<()>;
+
+    /// Commit the index to HEAD
+    fn commit(&self, dir: &Path, message: &str) -> AppResult<CommitOutcome>;
+
+    /// Fetch `refspecs` from `remote`, pruning refs deleted there
+    fn fetch(&self, dir: &Path, remote: &str, refspecs: &[&str]) -> AppResult<()>;
+
+    /// Push a single `src:dst` refspec to `remote`
+    fn push(&self, dir: &Path, remote: &str, refspec: &str) -> AppResult<()>;
+
+    /// Add a worktree at `path` with a detached HEAD at `rev`
+    fn add_worktree(&self, repo_path: &Path, path: &Path, rev: &str) -> AppResult<()>;
+
+    /// Remove the worktree at `path`, including its files
+    fn remove_worktree(&self, repo_path: &Path, path: &Path) -> AppResult<()>;
+
+    /// Contents of `path` at `rev`, or `None` if it doesn't exist there
+    fn read_file(&self, dir: &Path, rev: &str, path: &str) -> Option<Vec<u8>>;
+
+    /// Files under the directory `path` at `rev`, recursively, relative to
+    /// `path`; empty if the directory isn't there
+    fn list_files(&self, dir: &Path, rev: &str, path: &str) -> AppResult<Vec<String>>;
+
+    /// Names of the refs under `prefix` (e.g. `refs/remotes/origin/drafts/`),
+    /// with the prefix stripped
+    fn list_refs(&self, dir: &Path, prefix: &str) -> AppResult<Vec<String>>;
+}
+
+pub fn backend(kind: GitBackendKind) -> Box<dyn GitBackend> {
+    match kind {
+        GitBackendKind::Cli => Box::new(CliBackend),
+        GitBackendKind::Libgit2 => Box::new(Libgit2Backend),
+    }
+}
+
+/// The first of `candidates` that names an existing commit
+pub fn first_existing_rev(git: &dyn GitBackend, dir: &Path, candidates: &[&str]) -> Option<String> {
+    candidates
+        .iter()
+        .find(|rev| git.resolve(dir, rev).is_some())
+        .map(|rev| rev.to_string())
+}
+
+/// Refspec that fetches `branch` into its `{remote}/` remote-tracking ref
+pub fn tracking_refspec(remote: &str, branch: &str) -> String {
+    format!("+refs/heads/{1}:refs/remotes/{0}/{1}", remote, branch)
+}
+
+/// A temporary worktree owned by the app, used to commit to a branch without
+/// touching the user's checkout or uncommitted changes.
+///
+/// The worktree has a detached HEAD, so it works even when the branch is
+/// checked out elsewhere. It is removed when dropped, on success or failure.
+pub struct Worktree<'a> {
+    git: &'a dyn GitBackend,
+    repo_path: PathBuf,
+    remote: String,
+    path: PathBuf,
+    branch: String,
+}
+
+impl<'a> Worktree<'a> {
+    /// Fetch `branch` and any `{remote}/` start points, then start a worktree
+    /// at the first of `start_points` that exists. When that is the local
+    /// `branch` and it is behind `{remote}/{branch}`, the worktree fast-forwards.
+    pub fn open(
+        git: &'a dyn GitBackend,
+        repo_path: &Path,
+        remote: &str,
+        branch: &str,
+        start_points: &[&str],
+    ) -> AppResult<Worktree<'a>> {
+        // Missing remote branches are expected; errors show up at push time
+        let _ = git.fetch(repo_path, remote, &[&tracking_refspec(remote, branch)]);
+        let remote_prefix = format!("{}/", remote);
+        for start in start_points
+            .iter()
+            .filter_map(|s| s.strip_prefix(&remote_prefix))
+        {
+            if start != branch {
+                let _ = git.fetch(repo_path, remote, &[&tracking_refspec(remote, start)]);
+            }
+        }
+
+        let remote_branch = format!("{}/{}", remote, branch);
+        let mut candidates = start_points.to_vec();
+        candidates.push("HEAD");
+        let start = first_existing_rev(git, repo_path, &candidates)
+            .ok_or_else(|| AppError::not_found(format!("No commit to start {} from", branch)))?;
+
+        let root = std::env::temp_dir().join("nibandh-worktrees");
+        fs::create_dir_all(&root).map_err(|e| AppError::io("Failed to create worktree dir", e))?;
+        let path = root.join(format!("{}-{}", branch.replace('/', "-"), Uuid::new_v4()));
+
+        git.add_worktree(repo_path, &path, &start)?;
+
+        let worktree = Worktree {
+            git,
+            repo_path: repo_path.to_path_buf(),
+            remote: remote.to_string(),
+            path,
+            branch: branch.to_string(),
+        };
+
+        if start == branch && git.resolve(repo_path, &remote_branch).is_some() {
+            if git.is_ancestor(repo_path, branch, &remote_branch)? {
+                git.checkout(&worktree.path, &remote_branch)?;
+            } else if !git.is_ancestor(repo_path, &remote_branch, branch)? {
+                return Err(AppError::new(
+                    ErrorKind::MergeConflict,
+                    format!("{} and {} have diverged", branch, remote_branch),
+                ));
+            }
+        }
+
+        Ok(worktree)
+    }
+
+    pub fn path(&self) -> &Path {
+        &self.path
+    }
+
+    pub fn add(&self, paths: &[&str]) -> AppResult<()> {
+        self.git.add(&self.path, paths)
+    }
+
+    pub fn commit(&self, message: &str) -> AppResult<CommitOutcome> {
+        self.git.commit(&self.path, message)
+    }
+
+    /// Push the worktree's HEAD to `{remote}/{branch}` and fast-forward the
+    /// local branch to match, unless it is checked out somewhere else
+    pub fn push(&self) -> AppResult<()> {
+        self.git.push(
+            &self.path,
+            &self.remote,
+            &format!("HEAD:refs/heads/{}", self.branch),
+        )?;
+
+        let local = format!("refs/heads/{}", self.branch);
+        let fast_forward = self.git.resolve(&self.path, &local).is_none()
+            || self
+                .git
+                .is_ancestor(&self.path, &local, "HEAD")
+                .unwrap_or(false);
+        if fast_forward && self.git.branch(&self.path, &self.branch, "HEAD").is_ok() {
+            let _ = self.git.set_upstream(
+                &self.path,
+                &self.branch,
+                &format!("{}/{}", self.remote, self.branch),
+            );
+        }
+
+        Ok(())
+    }
+}
+
+impl Drop for Worktree<'_> {
+    fn drop(&mut self) {
+        if let Err(e) = self.git.remove_worktree(&self.repo_path, &self.path) {
+            eprintln!("Failed to remove worktree: {}", e);
+        }
+        if self.path.exists() {
+            let _ = fs::remove_dir_all(&self.path);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::process::Command;
+
+    /// A bare `origin.git` with one commit on `main`, and a clone of it in
+    /// `repo`, in a temporary directory that is removed when dropped
+    struct Fixture {
+        dir: PathBuf,
+    }
+
+    impl Fixture {
+        fn new() -> Fixture {
+            let dir = std::env::temp_dir().join(format!("nibandh-git-test-{}", Uuid::new_v4()));
+            fs::create_dir_all(&dir).unwrap();
+            let fixture = Fixture { dir };
+            fixture.git(&["init", "-q", "--bare", "origin.git"]);
+            fixture.git(&["clone", "-q", "origin.git", "repo"]);
+            fixture.git(&["-C", "repo", "config", "user.name", "Nibandh"]);
+            fixture.git(&["-C", "repo", "config", "user.email", "nibandh@example.com"]);
+            fixture.git(&["-C", "repo", "checkout", "-q", "-b", "main"]);
+            fs::write(fixture.repo().join("README.md"), "hello\n").unwrap();
+            fixture.git(&["-C", "repo", "add", "README.md"]);
+            fixture.git(&["-C", "repo", "commit", "-q", "-m", "Initial commit"]);
+            fixture.git(&["-C", "repo", "push", "-q", "origin", "main"]);
+            fixture
+        }
+
+        fn repo(&self) -> PathBuf {
+            self.dir.join("repo")
+        }
+
+        /// Run the `git` executable in the fixture directory and return its output
+        fn git(&self, args: &[&str]) -> String {
+            let output = Command::new("git")
+                .current_dir(&self.dir)
+                .args(args)
+                .output()
+                .unwrap();
+            assert!(
+                output.status.success(),
+                "git {:?}: {}",
+                args,
+                String::from_utf8_lossy(&output.stderr)
+            );
+            String::from_utf8_lossy(&output.stdout).trim().to_string()
+        }
+
+        /// The commit a branch of the bare remote points to
+        fn remote_head(&self, branch: &str) -> String {
+            self.git(&["-C", "origin.git", "rev-parse", branch])
+        }
+    }
+
+    impl Drop for Fixture {
+        fn drop(&mut self) {
+            let _ = fs::remove_dir_all(&self.dir);
+        }
+    }
+
+    fn commit_push_and_read_back(git: &dyn GitBackend) {
+        let fixture = Fixture::new();
+        let repo = fixture.repo();
+        let before = fixture.remote_head("main");
+
+        let path = {
+            let worktree =
+                Worktree::open(git, &repo, "origin", "main", &["origin/main", "main"]).unwrap();
+            let path = worktree.path().to_path_buf();
+            assert_eq!(
+                fs::read_to_string(path.join("README.md")).unwrap(),
+                "hello\n"
+            );
+
+            fs::create_dir_all(path.join("content")).unwrap();
+            fs::write(path.join("content/post.md"), "post\n").unwrap();
+            worktree.add(&["content"]).unwrap();
+            assert_eq!(
+                worktree.commit("Add post").unwrap(),
+                CommitOutcome::Committed
+            );
+            worktree.add(&["content"]).unwrap();
+            assert_eq!(
+                worktree.commit("Add post again").unwrap(),
+                CommitOutcome::NothingToCommit
+            );
+            worktree.push().unwrap();
+            path
+        };
+        assert!(!path.exists());
+        assert_eq!(
+            fixture
+                .git(&["-C", "repo", "worktree", "list"])
+                .lines()
+                .count(),
+            1
+        );
+        // The user's checkout is left alone
+        assert!(!repo.join("content").exists());
+
+        let after = fixture.remote_head("main");
+        assert_ne!(after, before);
+        assert_eq!(
+            fixture.git(&["-C", "origin.git", "log", "-1", "--format=%s", "main"]),
+            "Add post"
+        );
+
+        git.fetch(&repo, "origin", &[&tracking_refspec("origin", "main")])
+            .unwrap();
+        assert_eq!(git.resolve(&repo, "origin/main"), Some(after));
+        assert_eq!(
+            git.read_file(&repo, "origin/main", "content/post.md"),
+            Some(b"post\n".to_vec())
+        );
+        assert_eq!(
+            git.read_file(&repo, "origin/main", "content/missing.md"),
+            None
+        );
+        assert_eq!(git.read_file(&repo, &before, "content/post.md"), None);
+        assert_eq!(
+            git.list_files(&repo, "origin/main", "content").unwrap(),
+            ["post.md"]
+        );
+        assert!(git
+            .list_files(&repo, "origin/main", "missing")
+            .unwrap()
+            .is_empty());
+
+        // A new branch is created on the remote from the fallback start point
+        {
+            let worktree = Worktree::open(
+                git,
+                &repo,
+                "origin",
+                "drafts/hello",
+                &["origin/drafts/hello", "origin/main"],
+            )
+            .unwrap();
+            fs::write(worktree.path().join("draft.md"), "draft\n").unwrap();
+            worktree.add(&["draft.md"]).unwrap();
+            assert_eq!(
+                worktree.commit("Sync draft").unwrap(),
+                CommitOutcome::Committed
+            );
+            worktree.push().unwrap();
+        }
+        git.fetch(
+            &repo,
+            "origin",
+            &[&tracking_refspec("origin", "drafts/hello")],
+        )
+        .unwrap();
+        assert_eq!(
+            git.list_refs(&repo, "refs/remotes/origin/drafts/").unwrap(),
+            ["hello"]
+        );
+        assert_eq!(
+            git.read_file(&repo, "origin/drafts/hello", "content/post.md"),
+            Some(b"post\n".to_vec())
+        );
+        assert_eq!(
+            git.resolve(&repo, "refs/heads/drafts/hello"),
+            Some(fixture.remote_head("drafts/hello"))
+        );
+    }
+
+    #[test]
+    fn cli_backend_commits_pushes_and_reads_back() {
+        commit_push_and_read_back(&CliBackend);
+    }
+
+    #[test]
+    fn libgit2_backend_commits_pushes_and_reads_back() {
+        commit_push_and_read_back(&Libgit2Backend);
+    }
+
+    fn publish_bundle(git: &dyn GitBackend) {
+        use crate::downloads::ImageDownloader;
+        use crate::images::ImageOptions;
+        use crate::layout::RepoLayout;
+        use crate::publish::{self, PublishDraftArgs};
+        use crate::ssg::SiteGenerator;
+        use base64::{engine::general_purpose::STANDARD, Engine};
+
+        let fixture = Fixture::new();
+        let repo = fixture.repo();
+        let layout = RepoLayout::default();
+        let profile = SiteGenerator::Hugo.profile();
+        let options = ImageOptions::default();
+
+        let mut png = Vec::new();
+        image::RgbImage::new(4, 4)
+            .write_to(&mut std::io::Cursor::new(&mut png), image::ImageFormat::Png)
+            .unwrap();
+        let args: PublishDraftArgs = serde_json::from_value(serde_json::json!({
+            "slug": "hello",
+            "title": "Hello",
+            "date": "2026-01-15",
+            "tags": ["Rust"],
+            "description": "A first post",
+            "cover": format!("data:image/png;base64,{}", STANDARD.encode(&png)),
+            "coverPosition": null,
+            "coverAlt": "A square",
+            "updatedAt": null,
+            "content": "Body",
+            "commitMessage": "Publish hello",
+            "repoPath": repo.to_string_lossy(),
+        }))
+        .unwrap();
+
+        let worktree =
+            Worktree::open(git, &repo, "origin", "main", &["origin/main", "main"]).unwrap();
+        let downloader = ImageDownloader::new(&options).unwrap();
+        let article = futures::executor::block_on(publish::prepare_article(
+            &args,
+            &downloader,
+            &layout,
+            &profile,
+            &options,
+            &|_: &str| None,
+        ))
+        .unwrap();
+        let written = publish::write_article(worktree.path(), &layout, &article).unwrap();
+        assert_eq!(
+            written,
+            ["content/articles/hello/index.md", "content/articles/hello"]
+        );
+        assert!(!worktree.path().join(&layout.public_images_dir).exists());
+
+        let written: Vec<&str> = written.iter().map(String::as_str).collect();
+        worktree.add(&written).unwrap();
+        assert_eq!(
+            worktree.commit(&args.commit_message).unwrap(),
+            CommitOutcome::Committed
+        );
+        worktree.push().unwrap();
+        drop(worktree);
+
+        let files = fixture.git(&["-C", "origin.git", "ls-tree", "-r", "--name-only", "main"]);
+        let files: Vec<&str> = files.lines().collect();
+        assert!(
+            files.contains(&"content/articles/hello/index.md"),
+            "{:?}",
+            files
+        );
+        assert!(
+            files
+                .iter()
+                .any(|f| f.starts_with("content/articles/hello/") && f.ends_with(".webp")),
+            "{:?}",
+            files
+        );
+        assert!(
+            !files.iter().any(|f| f.starts_with("public/")),
+            "{:?}",
+            files
+        );
+    }
+
+    #[test]
+    fn cli_backend_publishes_a_page_bundle() {
+        publish_bundle(&CliBackend);
+    }
+
+    #[test]
+    fn libgit2_backend_publishes_a_page_bundle() {
+        publish_bundle(&Libgit2Backend);
+    }
+
+    fn rename_with_redirect(git: &dyn GitBackend) {
+        use crate::layout::RepoLayout;
+        use crate::publish::{PublishMode, PublishStrategy};
+        use crate::redirects::{RedirectFormat, RedirectOptions};
+        use crate::slug::{published_slugs, rename_article};
+        use crate::ssg::SiteGenerator;
+
+        let fixture = Fixture::new();
+        let repo = fixture.repo();
+        let layout = RepoLayout::default();
+        let profile = SiteGenerator::default().profile();
+        let strategy = PublishStrategy {
+            mode: PublishMode::DirectPush,
+            ..Default::default()
+        };
+        let redirects = RedirectOptions {
+            format: RedirectFormat::Netlify,
+            file: String::new(),
+        };
+
+        let image = "hello-0123456789abcdef.webp";
+        for (path, content) in [
+            (
+                "content/articles/hello.md".to_string(),
+                format!("---\ntitle: \"Hello\"\n---\n![a](/images/{})\n", image),
+            ),
+            (
+                "content/articles/bundle/index.md".to_string(),
+                "---\n---\n".to_string(),
+            ),
+            (format!("content/images/{}", image), "x".to_string()),
+            (format!("public/images/{}", image), "x".to_string()),
+        ] {
+            let path = repo.join(path);
+            fs::create_dir_all(path.parent().unwrap()).unwrap();
+            fs::write(path, content).unwrap();
+        }
+        fixture.git(&["-C", "repo", "add", "-A"]);
+        fixture.git(&["-C", "repo", "commit", "-q", "-m", "Add articles"]);
+        fixture.git(&["-C", "repo", "push", "-q", "origin", "main"]);
+
+        // Published elsewhere: only the remote knows about it
+        {
+            let worktree =
+                Worktree::open(git, &repo, "origin", "main", &["origin/main", "main"]).unwrap();
+            fs::write(worktree.path().join("content/articles/fresh.md"), "x").unwrap();
+            worktree.add(&["content/articles"]).unwrap();
+            worktree.commit("Add fresh").unwrap();
+            worktree.push().unwrap();
+        }
+        assert!(!repo.join("content/articles/fresh.md").exists());
+        let mut slugs: Vec<String> = published_slugs(git, &repo, &layout, &strategy)
+            .unwrap()
+            .into_iter()
+            .collect();
+        slugs.sort();
+        assert_eq!(slugs, ["bundle", "fresh", "hello"]);
+
+        let rename = |old: &str, new: &str| {
+            rename_article(
+                git, &repo, &layout, &profile, &strategy, &redirects, old, new,
+            )
+        };
+        assert!(rename("hello", "bundle").is_err());
+        assert!(rename("missing", "other").is_err());
+        let renamed = rename("hello", "greetings").unwrap();
+        assert_eq!(renamed.old_path, "content/articles/hello.md");
+        assert_eq!(renamed.new_path, "content/articles/greetings.md");
+        assert_eq!(
+            renamed.images,
+            [
+                "content/images/greetings-0123456789abcdef.webp",
+                "public/images/greetings-0123456789abcdef.webp"
+            ]
+        );
+
+        assert_eq!(
+            fixture.git(&["-C", "origin.git", "log", "-1", "--format=%s", "main"]),
+            "Rename hello to greetings"
+        );
+        let files = fixture.git(&["-C", "origin.git", "ls-tree", "-r", "--name-only", "main"]);
+        let files: Vec<&str> = files.lines().collect();
+        assert_eq!(
+            files,
+            [
+                "README.md",
+                "content/articles/bundle/index.md",
+                "content/articles/fresh.md",
+                "content/articles/greetings.md",
+                "content/images/greetings-0123456789abcdef.webp",
+                "public/_redirects",
+                "public/images/greetings-0123456789abcdef.webp",
+            ]
+        );
+        assert_eq!(
+            fixture.git(&[
+                "-C",
+                "origin.git",
+                "show",
+                "main:content/articles/greetings.md"
+            ]),
+            "---\ntitle: \"Hello\"\n---\n![a](/images/greetings-0123456789abcdef.webp)"
+        );
+        assert_eq!(
+            fixture.git(&["-C", "origin.git", "show", "main:public/_redirects"]),
+            "/articles/hello /articles/greetings 301"
+        );
+
+        // Renaming again moves the existing redirect instead of chaining it
+        rename("greetings", "welcome").unwrap();
+        assert_eq!(
+            fixture.git(&["-C", "origin.git", "show", "main:public/_redirects"]),
+            "/articles/hello /articles/welcome 301\n/articles/greetings /articles/welcome 301"
+        );
+    }
+
+    #[test]
+    fn cli_backend_renames_an_article() {
+        rename_with_redirect(&CliBackend);
+    }
+
+    #[test]
+    fn libgit2_backend_renames_an_article() {
+        rename_with_redirect(&Libgit2Backend);
+    }
+}
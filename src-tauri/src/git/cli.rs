@@ -0,0 +1,157 @@
+use std::path::Path;
+use std::process::{Command, Output};
+
+use super::{CommitOutcome, GitBackend};
+use crate::error::{AppError, AppResult};
+
+/// Runs the `git` executable found on PATH
+pub struct CliBackend;
+
+impl CliBackend {
+    fn command(&self, dir: &Path, args: &[&str]) -> AppResult<Output> {
+        Command::new("git")
+            .current_dir(dir)
+            .args(args)
+            // Keep messages in English so errors classify the same everywhere
+            .env("LC_ALL", "C")
+            .output()
+            .map_err(|e| AppError::spawn("git", e))
+    }
+
+    /// Run git, failing with a classified error if it exits non-zero
+    fn run(&self, dir: &Path, args: &[&str]) -> AppResult<Output> {
+        let output = self.command(dir, args)?;
+        if !output.status.success() {
+            return Err(AppError::git(format!("git {} failed", args[0]), &output));
+        }
+        Ok(output)
+    }
+
+    fn stdout(&self, dir: &Path, args: &[&str]) -> AppResult<String> {
+        self.run(dir, args)
+            .map(|output| String::from_utf8_lossy(&output.stdout).to_string())
+    }
+}
+
+impl GitBackend for CliBackend {
+    fn status(&self, dir: &Path) -> AppResult<Vec<String>> {
+        let stdout = self.stdout(dir, &["status", "--porcelain"])?;
+        Ok(stdout.lines().map(str::to_string).collect())
+    }
+
+    fn current_branch(&self, dir: &Path) -> AppResult<Option<String>> {
+        let output = self.command(dir, &["symbolic-ref", "--quiet", "--short", "HEAD"])?;
+        Ok(output
+            .status
+            .success()
+            .then(|| String::from_utf8_lossy(&output.stdout).trim().to_string()))
+    }
+
+    fn resolve(&self, dir: &Path, rev: &str) -> Option<String> {
+        let commit = format!("{}^{{commit}}", rev);
+        self.stdout(dir, &["rev-parse", "--verify", "--quiet", &commit])
+            .ok()
+            .map(|id| id.trim().to_string())
+    }
+
+    fn is_ancestor(&self, dir: &Path, ancestor: &str, descendant: &str) -> AppResult<bool> {
+        let output = self.command(dir, &["merge-base", "--is-ancestor", ancestor, descendant])?;
+        match output.status.code() {
+            Some(0) => Ok(true),
+            Some(1) => Ok(false),
+            _ => Err(AppError::git("git merge-base failed", &output)),
+        }
+    }
+
+    fn branch(&self, dir: &Path, name: &str, target: &str) -> AppResult<()> {
+        self.run(dir, &["branch", "--force", "--no-track", name, target])
+            .map(|_| ())
+    }
+
+    fn set_upstream(&self, dir: &Path, name: &str, upstream: &str) -> AppResult<()> {
+        let upstream = format!("--set-upstream-to={}", upstream);
+        self.run(dir, &["branch", &upstream, name]).map(|_| ())
+    }
+
+    fn checkout(&self, dir: &Path, rev: &str) -> AppResult<()> {
+        self.run(dir, &["checkout", "--force", "--detach", rev])
+            .map(|_| ())
+    }
+
+    fn add(&self, dir: &Path, paths: &[&str]) -> AppResult<()> {
+        let mut args = vec!["add", "--all", "--"];
+        args.extend_from_slice(paths);
+        self.run(dir, &args).map(|_| ())
+    }
+
+    fn commit(&self, dir: &Path, message: &str) -> AppResult<CommitOutcome> {
+        // Exit code 0 means the index matches HEAD
+        let diff = self.command(dir, &["diff", "--cached", "--quiet"])?;
+        if diff.status.success() {
+            return Ok(CommitOutcome::NothingToCommit);
+        }
+
+        self.run(dir, &["commit", "--quiet", "-m", message])?;
+        Ok(CommitOutcome::Committed)
+    }
+
+    fn fetch(&self, dir: &Path, remote: &str, refspecs: &[&str]) -> AppResult<()> {
+        let mut args = vec!["fetch", "--prune", remote];
+        args.extend_from_slice(refspecs);
+        self.run(dir, &args).map(|_| ())
+    }
+
+    fn push(&self, dir: &Path, remote: &str, refspec: &str) -> AppResult<()> {
+        self.run(dir, &["push", remote, refspec]).map(|_| ())
+    }
+
+    fn add_worktree(&self, repo_path: &Path, path: &Path, rev: &str) -> AppResult<()> {
+        let path = path.to_string_lossy();
+        self.run(repo_path, &["worktree", "add", "--detach", &path, rev])
+            .map(|_| ())
+    }
+
+    fn remove_worktree(&self, repo_path: &Path, path: &Path) -> AppResult<()> {
+        let path = path.to_string_lossy();
+        let removed = self.run(repo_path, &["worktree", "remove", "--force", &path]);
+        self.run(repo_path, &["worktree", "prune"])?;
+        removed.map(|_| ())
+    }
+
+    fn read_file(&self, dir: &Path, rev: &str, path: &str) -> Option<Vec<u8>> {
+        self.run(dir, &["show", &format!("{}:{}", rev, path)])
+            .ok()
+            .map(|output| output.stdout)
+    }
+
+    fn list_files(&self, dir: &Path, rev: &str, path: &str) -> AppResult<Vec<String>> {
+        let path = path.trim_matches('/');
+        let prefix = if path.is_empty() {
+            String::new()
+        } else {
+            format!("{}/", path)
+        };
+        let mut args = vec!["ls-tree", "-r", "-z", "--name-only", "--full-tree", rev];
+        if !path.is_empty() {
+            args.extend(["--", &prefix]);
+        }
+        let stdout = self.stdout(dir, &args)?;
+        Ok(stdout
+            .split('\0')
+            .filter_map(|name| name.strip_prefix(&prefix))
+            .filter(|name| !name.is_empty())
+            .map(str::to_string)
+            .collect())
+    }
+
+    fn list_refs(&self, dir: &Path, prefix: &str) -> AppResult<Vec<String>> {
+        // for-each-ref matches whole path components, so list the parent
+        let pattern = prefix.rsplit_once('/').map_or(prefix, |(parent, _)| parent);
+        let stdout = self.stdout(dir, &["for-each-ref", "--format=%(refname)", pattern])?;
+        Ok(stdout
+            .lines()
+            .filter_map(|name| name.strip_prefix(prefix))
+            .map(str::to_string)
+            .collect())
+    }
+}
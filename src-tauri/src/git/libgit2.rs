@@ -0,0 +1,392 @@
+use git2::{
+    build::CheckoutBuilder, BranchType, Cred, CredentialType, ErrorClass, ErrorCode, FetchOptions,
+    FetchPrune, IndexAddOption, ObjectType, PushOptions, RemoteCallbacks, Repository,
+    StatusOptions, TreeWalkMode, TreeWalkResult, WorktreeAddOptions, WorktreePruneOptions,
+};
+use std::cell::RefCell;
+use std::path::Path;
+
+use super::{CommitOutcome, GitBackend};
+use crate::error::{AppError, AppResult, ErrorKind};
+
+/// Runs git in-process with libgit2, independent of the installed git version
+/// and locale. Credentials come from the SSH agent or git's credential helper.
+pub struct Libgit2Backend;
+
+fn open(dir: &Path) -> AppResult<Repository> {
+    Repository::open(dir).map_err(|e| error("Failed to open repository", e))
+}
+
+fn error(message: &str, err: git2::Error) -> AppError {
+    let kind = match (err.code(), err.class()) {
+        (ErrorCode::Auth, _) => ErrorKind::GitAuthFailed,
+        (ErrorCode::NotFastForward, _) => ErrorKind::PushRejected,
+        (ErrorCode::Conflict | ErrorCode::MergeConflict, _) => ErrorKind::MergeConflict,
+        (ErrorCode::NotFound, ErrorClass::Repository) => ErrorKind::NotAGitRepo,
+        (_, ErrorClass::Net | ErrorClass::Http | ErrorClass::Ssl) => ErrorKind::Network,
+        (_, ErrorClass::Ssh) => ErrorKind::GitAuthFailed,
+        _ => ErrorKind::Git,
+    };
+    AppError::new(kind, message).with_details(err.message())
+}
+
+/// Callbacks that try the SSH agent, then git's credential helper, then the
+/// default credentials, giving up after a few attempts
+fn callbacks<'a>(repo: &Repository) -> RemoteCallbacks<'a> {
+    let config = repo.config().ok();
+    let mut attempts = 0;
+    let mut callbacks = RemoteCallbacks::new();
+    callbacks.credentials(move |url, username, allowed| {
+        attempts += 1;
+        if attempts > 3 {
+            return Err(git2::Error::from_str("authentication failed"));
+        }
+        if allowed.contains(CredentialType::SSH_KEY) {
+            Cred::ssh_key_from_agent(username.unwrap_or("git"))
+        } else if allowed.contains(CredentialType::USER_PASS_PLAINTEXT) {
+            match &config {
+                Some(config) => Cred::credential_helper(config, url, username),
+                None => Cred::default(),
+            }
+        } else {
+            Cred::default()
+        }
+    });
+    callbacks
+}
+
+fn status_code(status: git2::Status) -> String {
+    let index = if status.is_index_new() {
+        'A'
+    } else if status.is_index_modified() {
+        'M'
+    } else if status.is_index_deleted() {
+        'D'
+    } else if status.is_index_renamed() {
+        'R'
+    } else {
+        ' '
+    };
+    let worktree = if status.is_wt_modified() {
+        'M'
+    } else if status.is_wt_deleted() {
+        'D'
+    } else if status.is_wt_renamed() {
+        'R'
+    } else {
+        ' '
+    };
+    if status.is_wt_new() {
+        "??".to_string()
+    } else if status.is_conflicted() {
+        "UU".to_string()
+    } else {
+        format!("{}{}", index, worktree)
+    }
+}
+
+fn worktree_name(path: &Path) -> AppResult<String> {
+    path.file_name()
+        .map(|name| name.to_string_lossy().to_string())
+        .ok_or_else(|| AppError::invalid(format!("Invalid worktree path: {}", path.display())))
+}
+
+impl GitBackend for Libgit2Backend {
+    fn status(&self, dir: &Path) -> AppResult<Vec<String>> {
+        let repo = open(dir)?;
+        let mut options = StatusOptions::new();
+        options.include_untracked(true).recurse_untracked_dirs(true);
+        let statuses = repo
+            .statuses(Some(&mut options))
+            .map_err(|e| error("Failed to read status", e))?;
+
+        Ok(statuses
+            .iter()
+            .filter(|entry| !entry.status().is_ignored())
+            .map(|entry| {
+                format!(
+                    "{} {}",
+                    status_code(entry.status()),
+                    entry.path().unwrap_or_default()
+                )
+            })
+            .collect())
+    }
+
+    fn current_branch(&self, dir: &Path) -> AppResult<Option<String>> {
+        let repo = open(dir)?;
+        let head = match repo.head() {
+            Ok(head) => head,
+            Err(e) if e.code() == ErrorCode::UnbornBranch => return Ok(None),
+            Err(e) => return Err(error("Failed to read HEAD", e)),
+        };
+        Ok(head
+            .is_branch()
+            .then(|| head.shorthand().map(str::to_string))
+            .flatten())
+    }
+
+    fn resolve(&self, dir: &Path, rev: &str) -> Option<String> {
+        let repo = Repository::open(dir).ok()?;
+        let commit = repo.revparse_single(rev).ok()?.peel_to_commit().ok()?;
+        Some(commit.id().to_string())
+    }
+
+    fn is_ancestor(&self, dir: &Path, ancestor: &str, descendant: &str) -> AppResult<bool> {
+        let repo = open(dir)?;
+        let commit_id = |rev: &str| {
+            repo.revparse_single(rev)
+                .and_then(|object| object.peel_to_commit())
+                .map(|commit| commit.id())
+                .map_err(|e| error(&format!("Failed to resolve {}", rev), e))
+        };
+        let ancestor = commit_id(ancestor)?;
+        let descendant = commit_id(descendant)?;
+        if ancestor == descendant {
+            return Ok(true);
+        }
+        repo.graph_descendant_of(descendant, ancestor)
+            .map_err(|e| error("Failed to compare commits", e))
+    }
+
+    fn branch(&self, dir: &Path, name: &str, target: &str) -> AppResult<()> {
+        let repo = open(dir)?;
+        let commit = repo
+            .revparse_single(target)
+            .and_then(|object| object.peel_to_commit())
+            .map_err(|e| error(&format!("Failed to resolve {}", target), e))?;
+        repo.branch(name, &commit, true)
+            .map(|_| ())
+            .map_err(|e| error(&format!("Failed to update branch {}", name), e))
+    }
+
+    fn set_upstream(&self, dir: &Path, name: &str, upstream: &str) -> AppResult<()> {
+        let repo = open(dir)?;
+        let mut branch = repo
+            .find_branch(name, BranchType::Local)
+            .map_err(|e| error(&format!("Failed to find branch {}", name), e))?;
+        branch
+            .set_upstream(Some(upstream))
+            .map_err(|e| error("Failed to set upstream", e))
+    }
+
+    fn checkout(&self, dir: &Path, rev: &str) -> AppResult<()> {
+        let repo = open(dir)?;
+        let commit = repo
+            .revparse_single(rev)
+            .and_then(|object| object.peel_to_commit())
+            .map_err(|e| error(&format!("Failed to resolve {}", rev), e))?;
+        repo.checkout_tree(commit.as_object(), Some(CheckoutBuilder::new().force()))
+            .and_then(|_| repo.set_head_detached(commit.id()))
+            .map_err(|e| error(&format!("Failed to check out {}", rev), e))
+    }
+
+    fn add(&self, dir: &Path, paths: &[&str]) -> AppResult<()> {
+        let repo = open(dir)?;
+        let pathspecs: Vec<&str> = paths.iter().map(|p| p.trim_end_matches('/')).collect();
+        let mut index = repo.index().map_err(|e| error("Failed to read index", e))?;
+        index
+            .add_all(&pathspecs, IndexAddOption::DEFAULT, None)
+            .and_then(|_| index.update_all(&pathspecs, None))
+            .and_then(|_| index.write())
+            .map_err(|e| error("Failed to stage files", e))
+    }
+
+    fn commit(&self, dir: &Path, message: &str) -> AppResult<CommitOutcome> {
+        let repo = open(dir)?;
+        let mut index = repo.index().map_err(|e| error("Failed to read index", e))?;
+        let tree_id = index
+            .write_tree()
+            .map_err(|e| error("Failed to write tree", e))?;
+
+        let parent = match repo.head() {
+            Ok(head) => Some(
+                head.peel_to_commit()
+                    .map_err(|e| error("Failed to read HEAD", e))?,
+            ),
+            Err(e) if e.code() == ErrorCode::UnbornBranch => None,
+            Err(e) => return Err(error("Failed to read HEAD", e)),
+        };
+        if parent.as_ref().map(|p| p.tree_id()) == Some(tree_id) {
+            return Ok(CommitOutcome::NothingToCommit);
+        }
+
+        let tree = repo
+            .find_tree(tree_id)
+            .map_err(|e| error("Failed to read tree", e))?;
+        let signature = repo
+            .signature()
+            .map_err(|e| error("Set user.name and user.email in your git config", e))?;
+        let parents: Vec<_> = parent.iter().collect();
+        repo.commit(
+            Some("HEAD"),
+            &signature,
+            &signature,
+            message,
+            &tree,
+            &parents,
+        )
+        .map_err(|e| error("Failed to commit", e))?;
+
+        Ok(CommitOutcome::Committed)
+    }
+
+    fn fetch(&self, dir: &Path, remote: &str, refspecs: &[&str]) -> AppResult<()> {
+        let repo = open(dir)?;
+        let mut remote = repo
+            .find_remote(remote)
+            .map_err(|e| error(&format!("Remote {} not found", remote), e))?;
+        let mut options = FetchOptions::new();
+        options
+            .remote_callbacks(callbacks(&repo))
+            .prune(FetchPrune::On);
+        remote
+            .fetch(refspecs, Some(&mut options), None)
+            .map_err(|e| error("git fetch failed", e))
+    }
+
+    fn push(&self, dir: &Path, remote: &str, refspec: &str) -> AppResult<()> {
+        let repo = open(dir)?;
+        let mut remote = repo
+            .find_remote(remote)
+            .map_err(|e| error(&format!("Remote {} not found", remote), e))?;
+
+        // The remote reports per-ref rejections here rather than as an error
+        let rejected = RefCell::new(None);
+        let mut callbacks = callbacks(&repo);
+        callbacks.push_update_reference(|name, status| {
+            if let Some(status) = status {
+                *rejected.borrow_mut() = Some(format!("{}: {}", name, status));
+            }
+            Ok(())
+        });
+
+        let mut options = PushOptions::new();
+        options.remote_callbacks(callbacks);
+        remote
+            .push(&[refspec], Some(&mut options))
+            .map_err(|e| error("git push failed", e))?;
+
+        match rejected.take() {
+            Some(reason) => {
+                Err(AppError::new(ErrorKind::PushRejected, "git push failed").with_details(reason))
+            }
+            None => Ok(()),
+        }
+    }
+
+    fn add_worktree(&self, repo_path: &Path, path: &Path, rev: &str) -> AppResult<()> {
+        let repo = open(repo_path)?;
+        let name = worktree_name(path)?;
+        let commit = repo
+            .revparse_single(rev)
+            .and_then(|object| object.peel_to_commit())
+            .map_err(|e| error(&format!("Failed to resolve {}", rev), e))?;
+
+        // libgit2 can only add a worktree on a branch, so use a throwaway one
+        // and detach HEAD before deleting it
+        let temp_branch = format!("nibandh-worktree-{}", name);
+        let mut branch = repo
+            .branch(&temp_branch, &commit, true)
+            .map_err(|e| error("Failed to create worktree branch", e))?;
+        let mut options = WorktreeAddOptions::new();
+        options.reference(Some(branch.get()));
+        let added = repo
+            .worktree(&name, path, Some(&options))
+            .and_then(|worktree| {
+                let detached = Repository::open(path)
+                    .and_then(|checkout| checkout.set_head_detached(commit.id()));
+                if detached.is_err() {
+                    // The worktree still has the branch checked out, which would
+                    // keep it from being deleted
+                    let _ = worktree.prune(Some(
+                        WorktreePruneOptions::new()
+                            .valid(true)
+                            .locked(true)
+                            .working_tree(true),
+                    ));
+                }
+                detached
+            });
+        let deleted = branch.delete();
+
+        added.map_err(|e| error("Failed to add worktree", e))?;
+        deleted.map_err(|e| {
+            // Don't leave a worktree behind that the caller doesn't know about
+            let _ = self.remove_worktree(repo_path, path);
+            error(&format!("Failed to delete branch {}", temp_branch), e)
+        })
+    }
+
+    fn remove_worktree(&self, repo_path: &Path, path: &Path) -> AppResult<()> {
+        let repo = open(repo_path)?;
+        let worktree = repo
+            .find_worktree(&worktree_name(path)?)
+            .map_err(|e| error("Failed to find worktree", e))?;
+        worktree
+            .prune(Some(
+                WorktreePruneOptions::new()
+                    .valid(true)
+                    .locked(true)
+                    .working_tree(true),
+            ))
+            .map_err(|e| error("Failed to remove worktree", e))
+    }
+
+    fn read_file(&self, dir: &Path, rev: &str, path: &str) -> Option<Vec<u8>> {
+        let repo = Repository::open(dir).ok()?;
+        let object = repo.revparse_single(&format!("{}:{}", rev, path)).ok()?;
+        let blob = object.peel_to_blob().ok()?;
+        Some(blob.content().to_vec())
+    }
+
+    fn list_files(&self, dir: &Path, rev: &str, path: &str) -> AppResult<Vec<String>> {
+        let repo = open(dir)?;
+        let tree = repo
+            .revparse_single(rev)
+            .and_then(|object| object.peel_to_tree())
+            .map_err(|e| error(&format!("Failed to read {}", rev), e))?;
+        let path = path.trim_matches('/');
+        let tree = if path.is_empty() {
+            tree
+        } else {
+            match tree.get_path(Path::new(path)) {
+                Ok(entry) => entry
+                    .to_object(&repo)
+                    .and_then(|object| object.peel_to_tree())
+                    .map_err(|e| error(&format!("Failed to read {}", path), e))?,
+                Err(_) => return Ok(Vec::new()),
+            }
+        };
+
+        let mut files = Vec::new();
+        tree.walk(TreeWalkMode::PreOrder, |parent, entry| {
+            if entry.kind() == Some(ObjectType::Blob) {
+                if let Some(name) = entry.name() {
+                    files.push(format!("{}{}", parent, name));
+                }
+            }
+            TreeWalkResult::Ok
+        })
+        .map_err(|e| error(&format!("Failed to read {}", path), e))?;
+        Ok(files)
+    }
+
+    fn list_refs(&self, dir: &Path, prefix: &str) -> AppResult<Vec<String>> {
+        let repo = open(dir)?;
+        let references = repo
+            .references()
+            .map_err(|e| error("Failed to list refs", e))?;
+        let mut names: Vec<String> = references
+            .filter_map(|reference| reference.ok())
+            .filter_map(|reference| {
+                reference
+                    .name()
+                    .and_then(|name| name.strip_prefix(prefix))
+                    .map(str::to_string)
+            })
+            .collect();
+        names.sort();
+        Ok(names)
+    }
+}
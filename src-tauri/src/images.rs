@@ -0,0 +1,618 @@
+use base64::{engine::general_purpose::STANDARD, Engine};
+use image::codecs::gif::{GifDecoder, GifEncoder, Repeat};
+use image::codecs::{avif::AvifEncoder, webp::WebPEncoder};
+use image::{
+    imageops::FilterType, AnimationDecoder, ColorType, DynamicImage, Frame, GenericImageView,
+    ImageDecoder, ImageEncoder, ImageFormat, ImageReader,
+};
+use regex::Regex;
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+use std::collections::{HashMap, HashSet};
+use std::fs;
+use std::io::Cursor;
+use std::path::Path;
+
+use crate::downloads::ImageDownloader;
+use crate::error::{AppError, AppResult, ErrorKind};
+
+/// How decodable images are re-encoded
+#[derive(Debug, Clone, Copy, Default, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub enum ImageEncoding {
+    /// Lossless WebP for screenshots and line art, lossy WebP for photos
+    #[default]
+    Auto,
+    Lossless,
+    /// WebP at `quality`
+    Lossy,
+    /// AVIF at `quality`
+    Avif,
+}
+
+impl ImageEncoding {
+    fn extension(self) -> &'static str {
+        match self {
+            ImageEncoding::Avif => "avif",
+            _ => "webp",
+        }
+    }
+}
+
+/// Screenshots and line art have few distinct colors or transparency, and
+/// lose the least from lossless encoding; photos have thousands of colors
+const GRAPHIC_MAX_COLORS: usize = 256;
+
+/// How images are resized and encoded when they are prepared, from settings
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase", default)]
+pub struct ImageOptions {
+    pub encoding: ImageEncoding,
+    /// Quality for lossy WebP and AVIF, from 1 to 100
+    pub quality: u8,
+    /// Larger images are scaled down to fit; 0 for no limit
+    pub max_width: u32,
+    pub max_height: u32,
+    /// Widths of the smaller copies offered through `srcset`; empty for none
+    pub breakpoints: Vec<u32>,
+    /// The `sizes` attribute written next to `srcset`
+    pub sizes: String,
+    /// Remote images downloaded at the same time
+    pub download_concurrency: usize,
+    /// Remote images larger than this are refused; 0 for no limit
+    pub max_download_mb: u64,
+}
+
+impl Default for ImageOptions {
+    fn default() -> Self {
+        ImageOptions {
+            encoding: ImageEncoding::Auto,
+            quality: 80,
+            max_width: 2400,
+            max_height: 2400,
+            breakpoints: vec![480, 960, 1600],
+            sizes: "(max-width: 768px) 100vw, 768px".to_string(),
+            download_concurrency: 4,
+            max_download_mb: 20,
+        }
+    }
+}
+
+impl ImageOptions {
+    /// The same limits, without responsive variants (covers, synced drafts)
+    pub fn without_variants(&self) -> ImageOptions {
+        ImageOptions {
+            breakpoints: Vec::new(),
+            ..self.clone()
+        }
+    }
+
+    /// `image` scaled down to fit the maximum dimensions, keeping its aspect ratio
+    fn fit(&self, image: DynamicImage) -> DynamicImage {
+        let limit = |max: u32| if max == 0 { u32::MAX } else { max };
+        let (max_width, max_height) = (limit(self.max_width), limit(self.max_height));
+        if image.width() > max_width || image.height() > max_height {
+            image.resize(max_width, max_height, FilterType::Lanczos3)
+        } else {
+            image
+        }
+    }
+
+    /// The encoding used for `image`, resolving `Auto` by its content
+    fn encoding_for(&self, image: &DynamicImage) -> ImageEncoding {
+        match self.encoding {
+            ImageEncoding::Auto if is_graphic(image) => ImageEncoding::Lossless,
+            ImageEncoding::Auto => ImageEncoding::Lossy,
+            encoding => encoding,
+        }
+    }
+}
+
+/// Whether `image` looks like a screenshot or line art: it has transparent
+/// pixels or only a few distinct colors
+fn is_graphic(image: &DynamicImage) -> bool {
+    let mut colors = HashSet::new();
+    for (_, _, pixel) in image.pixels() {
+        if pixel.0[3] < u8::MAX {
+            return true;
+        }
+        if colors.insert(pixel.0) && colors.len() > GRAPHIC_MAX_COLORS {
+            return false;
+        }
+    }
+    true
+}
+
+/// An image decoded and encoded in memory, ready to be written
+#[derive(Debug, Clone)]
+pub struct PreparedImage {
+    pub filename: String,
+    pub bytes: Vec<u8>,
+    /// Width and height in pixels, when the image could be decoded
+    pub dimensions: Option<(u32, u32)>,
+    /// Narrower copies for `srcset`, narrowest first
+    pub variants: Vec<PreparedImage>,
+    pub encoding: ImageEncoding,
+    /// Size of the image as pasted or downloaded
+    pub original_size: usize,
+}
+
+/// What re-encoding did to one image, for the publish result
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ImageReport {
+    pub filename: String,
+    pub encoding: ImageEncoding,
+    pub original_bytes: usize,
+    pub written_bytes: usize,
+    /// Negative when the encoded file is larger than the original
+    pub saved_bytes: i64,
+}
+
+impl PreparedImage {
+    /// Resize a decoded image to fit `options` and encode it as WebP or AVIF,
+    /// with a variant for each breakpoint narrower than the result.
+    /// `original_size` is the size of the undecoded source.
+    fn encode(
+        name_prefix: &str,
+        image: DynamicImage,
+        original_size: usize,
+        options: &ImageOptions,
+    ) -> AppResult<Self> {
+        let image = options.fit(image);
+        let encoding = options.encoding_for(&image);
+        let extension = encoding.extension();
+        let bytes = encode_image(&image, encoding, options.quality)?;
+        let filename = content_name(name_prefix, &bytes, extension);
+        let stem = &filename[..filename.len() - extension.len() - 1];
+
+        let mut widths: Vec<u32> = options
+            .breakpoints
+            .iter()
+            .copied()
+            .filter(|width| *width > 0 && *width < image.width())
+            .collect();
+        widths.sort_unstable();
+        widths.dedup();
+        let variants = widths
+            .into_iter()
+            .map(|width| {
+                let resized = image.resize(width, u32::MAX, FilterType::Lanczos3);
+                let bytes = encode_image(&resized, encoding, options.quality)?;
+                Ok(PreparedImage {
+                    filename: format!("{}-{}w.{}", stem, width, extension),
+                    original_size: bytes.len(),
+                    bytes,
+                    dimensions: Some((resized.width(), resized.height())),
+                    variants: Vec::new(),
+                    encoding,
+                })
+            })
+            .collect::<AppResult<Vec<_>>>()?;
+
+        Ok(PreparedImage {
+            dimensions: Some((image.width(), image.height())),
+            filename,
+            bytes,
+            variants,
+            encoding,
+            original_size,
+        })
+    }
+
+    pub fn report(&self) -> ImageReport {
+        ImageReport {
+            filename: self.filename.clone(),
+            encoding: self.encoding,
+            original_bytes: self.original_size,
+            written_bytes: self.bytes.len(),
+            saved_bytes: self.original_size as i64 - self.bytes.len() as i64,
+        }
+    }
+
+    /// This image followed by its variants
+    pub fn files(&self) -> impl Iterator<Item = &PreparedImage> {
+        std::iter::once(self).chain(&self.variants)
+    }
+}
+
+/// File name for an image: a hash of its bytes, after `name_prefix` unless
+/// that is empty. The same image always gets the same name, so republishing
+/// only touches images that really changed.
+pub fn content_name(name_prefix: &str, bytes: &[u8], extension: &str) -> String {
+    let hash: String = Sha256::digest(bytes)[..8]
+        .iter()
+        .map(|b| format!("{:02x}", b))
+        .collect();
+    if name_prefix.is_empty() {
+        format!("{}.{}", hash, extension)
+    } else {
+        format!("{}-{}.{}", name_prefix, hash, extension)
+    }
+}
+
+/// Where an inline image in the Markdown came from
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum ImageOrigin {
+    Pasted,     // data URL
+    Downloaded, // http(s) URL
+}
+
+/// An inline image that was prepared and linked into the Markdown
+#[derive(Debug, Clone)]
+pub struct InlineImage {
+    pub image: PreparedImage,
+    pub origin: ImageOrigin,
+}
+
+/// Markdown with its inline images replaced by links to prepared files.
+/// Remote images that failed to prepare keep their link; pasted ones are
+/// dropped, since their data URL would publish the unsanitized bytes.
+#[derive(Debug, Clone)]
+pub struct InlineImages {
+    pub content: String,
+    pub images: Vec<InlineImage>,
+    pub failures: Vec<String>,
+}
+
+/// Decode a base64 data URL image and re-encode it following `options`.
+/// The file is named by `content_name`.
+pub fn prepare_data_url_image(
+    data_url: &str,
+    name_prefix: &str,
+    options: &ImageOptions,
+) -> AppResult<PreparedImage> {
+    // Parse data URL: data:image/jpeg;base64,/9j/4AAQ...
+    let parts: Vec<&str> = data_url.splitn(2, ',').collect();
+    if parts.len() != 2 {
+        return Err(AppError::invalid("Invalid data URL format"));
+    }
+
+    let image_data = STANDARD
+        .decode(parts[1])
+        .map_err(|e| AppError::invalid("Failed to decode base64").with_details(e))?;
+
+    prepare_image_bytes(&image_data, name_prefix, options)
+}
+
+/// Download a remote image and re-encode it following `options`
+pub async fn fetch_remote_image(
+    downloader: &ImageDownloader,
+    url: &str,
+    name_prefix: &str,
+    options: &ImageOptions,
+) -> AppResult<PreparedImage> {
+    let bytes = downloader.download(url).await?;
+    prepare_image_bytes(&bytes, name_prefix, options)
+}
+
+/// Decode image bytes and re-encode them following `options`. Animated
+/// GIFs stay GIFs, since only their first frame would survive as WebP or AVIF.
+pub fn prepare_image_bytes(
+    bytes: &[u8],
+    name_prefix: &str,
+    options: &ImageOptions,
+) -> AppResult<PreparedImage> {
+    if let Some(image) = prepare_animated_gif(bytes, name_prefix, options)? {
+        return Ok(image);
+    }
+    let image = decode_image(bytes)?;
+    PreparedImage::encode(name_prefix, image, bytes.len(), options)
+}
+
+/// Re-encode an animated GIF frame by frame, scaled down to fit `options`.
+/// Rewriting it drops comments and application data (XMP) like re-encoding
+/// any other image does. `None` for anything but a GIF with several frames.
+fn prepare_animated_gif(
+    bytes: &[u8],
+    name_prefix: &str,
+    options: &ImageOptions,
+) -> AppResult<Option<PreparedImage>> {
+    if image::guess_format(bytes).ok() != Some(ImageFormat::Gif) {
+        return Ok(None);
+    }
+    let frames = GifDecoder::new(Cursor::new(bytes))
+        .and_then(|decoder| decoder.into_frames().collect_frames())
+        .map_err(decode_error)?;
+    if frames.len() < 2 {
+        return Ok(None);
+    }
+
+    // Frames come composited onto the full canvas, so they resize alike
+    let frames: Vec<Frame> = frames
+        .into_iter()
+        .map(|frame| {
+            let delay = frame.delay();
+            let image = options.fit(DynamicImage::ImageRgba8(frame.into_buffer()));
+            Frame::from_parts(image.to_rgba8(), 0, 0, delay)
+        })
+        .collect();
+    let dimensions = frames[0].buffer().dimensions();
+
+    let mut buffer = Vec::new();
+    {
+        let mut encoder = GifEncoder::new(&mut buffer);
+        encoder
+            .set_repeat(Repeat::Infinite)
+            .and_then(|_| encoder.encode_frames(frames))
+            .map_err(|e| AppError::image("Failed to encode gif", e))?;
+    }
+
+    Ok(Some(PreparedImage {
+        filename: content_name(name_prefix, &buffer, "gif"),
+        dimensions: Some(dimensions),
+        variants: Vec::new(),
+        encoding: ImageEncoding::Lossless,
+        original_size: bytes.len(),
+        bytes: buffer,
+    }))
+}
+
+/// Decode an image and turn it upright following its EXIF orientation.
+/// Only pixels survive re-encoding, so EXIF (GPS, camera, timestamps), XMP
+/// and color profile data are all dropped. Images that can't be decoded
+/// can't be cleaned, so they are refused rather than copied as-is.
+fn decode_image(bytes: &[u8]) -> AppResult<DynamicImage> {
+    let mut decoder = ImageReader::new(Cursor::new(bytes))
+        .with_guessed_format()
+        .map_err(|e| decode_error(e.into()))?
+        .into_decoder()
+        .map_err(decode_error)?;
+    let orientation = decoder.orientation().map_err(decode_error)?;
+    let mut image = DynamicImage::from_decoder(decoder).map_err(decode_error)?;
+    image.apply_orientation(orientation);
+    Ok(image)
+}
+
+fn decode_error(e: image::ImageError) -> AppError {
+    AppError::image("Image could not be decoded to strip its metadata", e)
+}
+
+/// AVIF encoder speed, from 1 (slowest, smallest) to 10
+const AVIF_SPEED: u8 = 6;
+
+fn encode_image(image: &DynamicImage, encoding: ImageEncoding, quality: u8) -> AppResult<Vec<u8>> {
+    let rgba = image.to_rgba8();
+    let (width, height) = rgba.dimensions();
+    let quality = quality.clamp(1, 100);
+    let mut buffer = Vec::new();
+    match encoding {
+        ImageEncoding::Auto | ImageEncoding::Lossless => WebPEncoder::new_lossless(&mut buffer)
+            .encode(&rgba, width, height, ColorType::Rgba8.into())
+            .map_err(|e| AppError::image("Failed to encode webp", e))?,
+        ImageEncoding::Lossy => {
+            let encoded = webp::Encoder::from_rgba(&rgba, width, height)
+                .encode_simple(false, f32::from(quality))
+                .map_err(|e| AppError::image("Failed to encode webp", format!("{:?}", e)))?;
+            buffer.extend_from_slice(&encoded);
+        }
+        ImageEncoding::Avif => {
+            AvifEncoder::new_with_speed_quality(&mut buffer, AVIF_SPEED, quality)
+                .write_image(&rgba, width, height, ColorType::Rgba8.into())
+                .map_err(|e| AppError::image("Failed to encode avif", e))?
+        }
+    }
+
+    Ok(buffer)
+}
+
+/// Write a prepared image into `dir`, unless an identical file is already there
+pub fn write_image(dir: &Path, image: &PreparedImage) -> AppResult<()> {
+    let path = dir.join(&image.filename);
+    if has_contents(&path, &image.bytes) {
+        return Ok(());
+    }
+    fs::create_dir_all(dir).map_err(|e| AppError::io("Failed to create images dir", e))?;
+    fs::write(path, &image.bytes).map_err(|e| AppError::io("Failed to write image", e))
+}
+
+/// Copy `filename` from `source_dir` to `target_dir`. Returns false when the
+/// target already had the same contents and was left alone.
+pub fn copy_image(source_dir: &Path, target_dir: &Path, filename: &str) -> AppResult<bool> {
+    let bytes = fs::read(source_dir.join(filename))
+        .map_err(|e| AppError::io(format!("Failed to read {}", filename), e))?;
+    let target_path = target_dir.join(filename);
+    if has_contents(&target_path, &bytes) {
+        return Ok(false);
+    }
+    if let Some(parent) = target_path.parent() {
+        fs::create_dir_all(parent).map_err(|e| AppError::io("Failed to create images dir", e))?;
+    }
+    fs::write(&target_path, bytes)
+        .map_err(|e| AppError::io(format!("Failed to copy {}", filename), e))?;
+    Ok(true)
+}
+
+pub fn copy_to_public_images(
+    public_images_dir: &Path,
+    images_dir: &Path,
+    filename: &str,
+) -> AppResult<()> {
+    copy_image(images_dir, public_images_dir, filename).map(|_| ())
+}
+
+fn has_contents(path: &Path, bytes: &[u8]) -> bool {
+    fs::metadata(path).is_ok_and(|meta| meta.len() == bytes.len() as u64)
+        && fs::read(path).is_ok_and(|existing| existing == bytes)
+}
+
+/// Prepare every pasted (data URL) or remote image in the Markdown and point
+/// it at `{path_prefix}/{filename}`, or at the bare file name when the prefix
+/// is empty (page bundles). With `html`, images are linked with `<img>` tags
+/// carrying `srcset`, `sizes`, `width` and `height`. Remote images are all
+/// downloaded up front, concurrently. An image used more than once is
+/// prepared and listed once. Nothing is written to disk.
+pub async fn prepare_inline_images(
+    content: &str,
+    downloader: Option<&ImageDownloader>,
+    name_prefix: &str,
+    path_prefix: &str,
+    options: &ImageOptions,
+    html: bool,
+) -> AppResult<InlineImages> {
+    let re = Regex::new(
+        r#"!\[([^\]]*)\]\((data:image[^)]+|https?://[^)]+)\)|<img([^>]*?)src="(data:image[^"]+|https?://[^"]+)"([^>]*)>"#,
+    )
+    .map_err(|e| AppError::invalid("Failed to build image regex").with_details(e))?;
+    let mut output = String::with_capacity(content.len());
+    let mut images = Vec::new();
+    let mut failures = Vec::new();
+    let mut last_index = 0;
+    let mut image_index = 1;
+    let prefix = path_prefix.trim_end_matches('/');
+
+    let matches: Vec<_> = re.captures_iter(content).collect();
+    let mut remote: Vec<&str> = matches
+        .iter()
+        .filter_map(|caps| caps.get(2).or_else(|| caps.get(4)))
+        .map(|src| src.as_str())
+        .filter(|src| src.starts_with("http"))
+        .collect();
+    remote.sort_unstable();
+    remote.dedup();
+    let downloads = match downloader {
+        Some(downloader) => downloader.download_all(&remote).await,
+        None => HashMap::new(),
+    };
+    let mut prepared_by_src: HashMap<&str, AppResult<PreparedImage>> = HashMap::new();
+
+    for caps in &matches {
+        let full = caps.get(0).unwrap();
+        output.push_str(&content[last_index..full.start()]);
+        last_index = full.end();
+
+        let src = match caps.get(2).or_else(|| caps.get(4)) {
+            Some(src) => src.as_str(),
+            None => {
+                output.push_str(full.as_str());
+                continue;
+            }
+        };
+
+        let origin = if src.starts_with("http") {
+            ImageOrigin::Downloaded
+        } else {
+            ImageOrigin::Pasted
+        };
+        let prepared = prepared_by_src.entry(src).or_insert_with(|| match origin {
+            ImageOrigin::Downloaded => match downloads.get(src) {
+                Some(Ok(bytes)) => prepare_image_bytes(bytes, name_prefix, options),
+                Some(Err(e)) => Err(e.clone()),
+                None => Err(AppError::new(
+                    ErrorKind::Network,
+                    "No HTTP client available for remote image",
+                )),
+            },
+            ImageOrigin::Pasted => prepare_data_url_image(src, name_prefix, options),
+        });
+
+        let image = match prepared.clone() {
+            Ok(image) => image,
+            Err(err) => {
+                eprintln!("Failed to prepare inline image: {}", err);
+                if origin == ImageOrigin::Pasted {
+                    failures.push(format!("Image {} was dropped: {}", image_index, err));
+                } else {
+                    failures.push(format!("Image {} was left unchanged: {}", image_index, err));
+                    output.push_str(full.as_str());
+                }
+                image_index += 1;
+                continue;
+            }
+        };
+
+        let url = |filename: &str| {
+            if path_prefix.is_empty() {
+                filename.to_string()
+            } else {
+                format!("{}/{}", prefix, filename)
+            }
+        };
+        let image_path = url(&image.filename);
+        let attributes = if html {
+            responsive_attributes(&image, &url, &options.sizes)
+        } else {
+            String::new()
+        };
+        if let Some(alt) = caps.get(1) {
+            if !attributes.is_empty() {
+                output.push_str(&format!(
+                    "<img src=\"{}\" alt=\"{}\"{}>",
+                    image_path,
+                    escape_attribute(alt.as_str()),
+                    attributes
+                ));
+            } else {
+                output.push_str(&format!("![{}]({})", alt.as_str(), image_path));
+            }
+        } else {
+            let before = caps.get(3).map(|m| m.as_str()).unwrap_or("");
+            let after = caps.get(5).map(|m| m.as_str()).unwrap_or("");
+            // Keep a self-closing slash last
+            let (after, close) = match after.strip_suffix('/') {
+                Some(after) => (after.trim_end(), " /"),
+                None => (after, ""),
+            };
+            output.push_str(&format!(
+                "<img{}src=\"{}\"{}{}{}>",
+                before, image_path, after, attributes, close
+            ));
+        }
+
+        if !images
+            .iter()
+            .any(|i: &InlineImage| i.image.filename == image.filename)
+        {
+            images.push(InlineImage { image, origin });
+        }
+        image_index += 1;
+    }
+
+    output.push_str(&content[last_index..]);
+
+    Ok(InlineImages {
+        content: output,
+        images,
+        failures,
+    })
+}
+
+/// `srcset`, `sizes`, `width` and `height` attributes for `image`, each with
+/// a leading space; `url` maps a file name to its link
+fn responsive_attributes(
+    image: &PreparedImage,
+    url: &dyn Fn(&str) -> String,
+    sizes: &str,
+) -> String {
+    let Some((width, height)) = image.dimensions else {
+        return String::new();
+    };
+    let mut attributes = String::new();
+    if !image.variants.is_empty() {
+        let srcset = image
+            .variants
+            .iter()
+            .chain(std::iter::once(image))
+            .filter_map(|file| {
+                file.dimensions
+                    .map(|(w, _)| format!("{} {}w", url(&file.filename), w))
+            })
+            .collect::<Vec<_>>()
+            .join(", ");
+        attributes.push_str(&format!(
+            " srcset=\"{}\" sizes=\"{}\"",
+            srcset,
+            escape_attribute(sizes)
+        ));
+    }
+    attributes.push_str(&format!(" width=\"{}\" height=\"{}\"", width, height));
+    attributes
+}
+
+fn escape_attribute(value: &str) -> String {
+    value
+        .replace('&', "&amp;")
+        .replace('"', "&quot;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+}
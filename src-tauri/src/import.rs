@@ -0,0 +1,302 @@
+use base64::{engine::general_purpose::STANDARD, Engine};
+use serde::Serialize;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use crate::database::{Database, Draft, DraftStatus};
+use crate::error::{AppError, AppResult};
+use crate::frontmatter::{parse_article, parse_timestamp, ArticleMeta};
+use crate::layout::RepoLayout;
+use crate::markdown::MarkdownImporter;
+use crate::slug::list_articles;
+use crate::ssg::SiteProfile;
+
+/// A file that was not imported, and why
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ImportSkip {
+    pub path: String,
+    pub reason: String,
+}
+
+/// Outcome of importing a blog repo into the local database
+#[derive(Debug, Clone, Default, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ImportResult {
+    pub created: usize,
+    pub updated: usize,
+    pub unchanged: usize,
+    pub skipped: Vec<ImportSkip>,
+}
+
+/// What a Markdown file in the repo represents
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum SourceKind {
+    Published, // {articles_dir}/{slug}.md
+    Synced,    // {drafts_dir}/{slug}.md
+}
+
+/// Import synced drafts and then published articles, so a published article
+/// wins over an older draft copy. Articles are read the way `profile` writes
+/// them: Hugo bundles, Jekyll's dated file names and the generator's own
+/// field names.
+pub fn import_repo(
+    db: &Database,
+    repo_path: &Path,
+    layout: &RepoLayout,
+    profile: &SiteProfile,
+) -> AppResult<ImportResult> {
+    let mut result = ImportResult::default();
+
+    let drafts: Vec<(PathBuf, String)> =
+        markdown_files(&RepoLayout::dir(repo_path, &layout.drafts_dir))
+            .into_iter()
+            .filter_map(|path| {
+                let slug = path.file_stem()?.to_str()?.to_string();
+                Some((path, slug))
+            })
+            .collect();
+    let articles: Vec<(PathBuf, String)> = list_articles(repo_path, layout)
+        .into_iter()
+        .map(|article| (RepoLayout::dir(repo_path, &article.path(layout)), article.slug))
+        .collect();
+    let drafts_profile = SiteProfile::drafts();
+    let sources = [
+        (drafts, SourceKind::Synced, &drafts_profile),
+        (articles, SourceKind::Published, profile),
+    ];
+
+    for (files, kind, profile) in sources {
+        for (path, slug) in files {
+            let display_path = path.to_string_lossy().to_string();
+            let content = match fs::read_to_string(&path) {
+                Ok(content) => content,
+                Err(e) => {
+                    result.skipped.push(ImportSkip {
+                        path: display_path,
+                        reason: format!("Failed to read file: {}", e),
+                    });
+                    continue;
+                }
+            };
+
+            let article_dir = path.parent().unwrap_or(repo_path);
+            let load_image = |src: &str| resolve_repo_image(repo_path, layout, article_dir, src);
+            let imported = import_file(
+                db,
+                &load_image,
+                &display_path,
+                &slug,
+                &content,
+                kind,
+                profile,
+                &mut result,
+            );
+            if let Err(e) = imported {
+                result.skipped.push(ImportSkip {
+                    path: display_path,
+                    reason: e.to_string(),
+                });
+            }
+        }
+    }
+
+    Ok(result)
+}
+
+#[allow(clippy::too_many_arguments)]
+fn import_file(
+    db: &Database,
+    load_image: &dyn Fn(&str) -> Option<String>,
+    path: &str,
+    slug: &str,
+    content: &str,
+    kind: SourceKind,
+    profile: &SiteProfile,
+    result: &mut ImportResult,
+) -> AppResult<()> {
+    let (meta, body) =
+        parse_article(content, profile).ok_or_else(|| AppError::invalid("No frontmatter found"))?;
+
+    let existing = match &meta.draft_id {
+        Some(id) => db.get_draft(id)?,
+        None => None,
+    };
+    let existing = match existing {
+        Some(draft) => Some(draft),
+        None => db.find_draft_by_slug(slug)?,
+    };
+
+    let draft = draft_from_file(slug, &meta, body, kind, load_image)?;
+
+    match existing {
+        None => {
+            // Keep the id from the frontmatter so later syncs still match
+            db.save_imported_draft(Draft {
+                id: meta.draft_id.clone().unwrap_or_default(),
+                ..draft
+            })?;
+            result.created += 1;
+        }
+        Some(local) => {
+            let local_time = parse_timestamp(&local.updated_at);
+            let file_time = parse_timestamp(&draft.updated_at);
+            match (local_time, file_time) {
+                (Some(local_time), Some(file_time)) if local_time > file_time => {
+                    result.skipped.push(ImportSkip {
+                        path: path.to_string(),
+                        reason: "Local draft has newer changes".to_string(),
+                    });
+                    return Ok(());
+                }
+                (Some(local_time), Some(file_time))
+                    if local_time == file_time && local.content == draft.content =>
+                {
+                    result.unchanged += 1;
+                    return Ok(());
+                }
+                _ => {}
+            }
+
+            db.save_imported_draft(Draft {
+                id: local.id,
+                created_at: local.created_at,
+                synced_at: draft.synced_at.or(local.synced_at),
+                published_at: draft.published_at.or(local.published_at),
+                status: if local.status == DraftStatus::Published {
+                    DraftStatus::Published
+                } else {
+                    draft.status.clone()
+                },
+                ..draft
+            })?;
+            result.updated += 1;
+        }
+    }
+
+    Ok(())
+}
+
+/// Build a draft row (without an id) from a repo file.
+/// `load_image` returns an image referenced by the file as a data URL, or
+/// `None` to keep the reference as written.
+pub fn draft_from_file(
+    slug: &str,
+    meta: &ArticleMeta,
+    body: &str,
+    kind: SourceKind,
+    load_image: &dyn Fn(&str) -> Option<String>,
+) -> AppResult<Draft> {
+    let resolve = |src: &str| load_image(src).unwrap_or_else(|| src.to_string());
+    let (content, text_content) = MarkdownImporter::new(&resolve)?.convert(body);
+
+    let updated_at = meta
+        .last_updated
+        .clone()
+        .filter(|t| parse_timestamp(t).is_some())
+        .or_else(|| Some(meta.date.clone()).filter(|d| parse_timestamp(d).is_some()))
+        .unwrap_or_default();
+
+    let cover = if meta.cover.is_empty() {
+        String::new()
+    } else {
+        resolve(&meta.cover)
+    };
+
+    Ok(Draft {
+        id: String::new(),
+        slug: slug.to_string(),
+        title: meta.title.clone(),
+        date: meta.date.clone(),
+        tags: meta.tags.clone(),
+        description: meta.description.clone(),
+        cover,
+        cover_position: meta.cover_position,
+        cover_alt: meta.cover_alt.clone(),
+        content,
+        text_content,
+        created_at: updated_at.clone(),
+        updated_at: updated_at.clone(),
+        synced_at: match kind {
+            SourceKind::Synced => Some(updated_at.clone()).filter(|t| !t.is_empty()),
+            SourceKind::Published => None,
+        },
+        published_at: match kind {
+            SourceKind::Published => Some(updated_at).filter(|t| !t.is_empty()),
+            SourceKind::Synced => None,
+        },
+        status: match kind {
+            SourceKind::Published => DraftStatus::Published,
+            SourceKind::Synced => DraftStatus::Synced,
+        },
+        extra_fields: meta.extra.clone(),
+        retracted_at: None,
+        retraction_reason: None,
+    })
+}
+
+/// Markdown files directly inside `dir`, sorted by name
+fn markdown_files(dir: &Path) -> Vec<PathBuf> {
+    let mut files: Vec<PathBuf> = match fs::read_dir(dir) {
+        Ok(entries) => entries
+            .filter_map(|entry| entry.ok().map(|e| e.path()))
+            .filter(|path| {
+                path.is_file() && path.extension().and_then(|e| e.to_str()) == Some("md")
+            })
+            .collect(),
+        Err(_) => Vec::new(),
+    };
+    files.sort();
+    files
+}
+
+/// Load an image referenced by a published or synced file as a data URL,
+/// which is how the editor holds pasted images. Relative references are
+/// resolved from `article_dir`, the directory holding the file.
+fn resolve_repo_image(
+    repo_path: &Path,
+    layout: &RepoLayout,
+    article_dir: &Path,
+    src: &str,
+) -> Option<String> {
+    if src.starts_with("data:") || src.starts_with("http://") || src.starts_with("https://") {
+        return None;
+    }
+
+    let candidates: Vec<PathBuf> = if let Some(name) = layout.drafts_image_name(src) {
+        vec![RepoLayout::dir(repo_path, &layout.drafts_image_path(name))]
+    } else if let Some(name) = layout.image_name(src) {
+        let mut candidates = vec![RepoLayout::dir(repo_path, &layout.image_path(name))];
+        candidates.extend(
+            layout
+                .public_image_path(name)
+                .map(|path| RepoLayout::dir(repo_path, &path)),
+        );
+        candidates
+    } else if !src.starts_with('/') {
+        // Relative to the article, e.g. `../images/{name}` or a bundle's `cover.webp`
+        vec![article_dir.join(src)]
+    } else {
+        return None;
+    };
+
+    let path = candidates.into_iter().find(|p| p.is_file())?;
+    let bytes = fs::read(&path).ok()?;
+    image_data_url(&path.to_string_lossy(), &bytes)
+}
+
+/// Encode image bytes as a data URL, picking the MIME type from the file name
+pub fn image_data_url(name: &str, bytes: &[u8]) -> Option<String> {
+    let extension = Path::new(name).extension()?.to_str()?.to_lowercase();
+    let mime = match extension.as_str() {
+        "png" => "image/png",
+        "gif" => "image/gif",
+        "webp" => "image/webp",
+        "avif" => "image/avif",
+        "svg" => "image/svg+xml",
+        "jpg" | "jpeg" => "image/jpeg",
+        _ => return None,
+    };
+
+    Some(format!("data:{};base64,{}", mime, STANDARD.encode(bytes)))
+}
@@ -0,0 +1,204 @@
+use serde::{Deserialize, Serialize};
+use std::path::{Component, Path, PathBuf};
+
+/// Where content lives in the blog repo and which remote and branches to use.
+/// Directories are relative to the repo root and use `/` as separator.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase", default)]
+pub struct RepoLayout {
+    /// Published Markdown files, `{slug}.md`
+    pub articles_dir: String,
+    /// Images referenced by published articles
+    pub images_dir: String,
+    /// Where the site serves images from; empty to skip the copy
+    pub public_images_dir: String,
+    /// Synced drafts, `{slug}.md`
+    pub drafts_dir: String,
+    pub drafts_images_dir: String,
+    /// URL prefix published articles use for images (e.g. `/images`)
+    pub images_url: String,
+    /// URL prefix synced drafts use for images (e.g. `/drafts/images`)
+    pub drafts_images_url: String,
+    /// URL prefix the site serves articles under (e.g. `/articles`), used to
+    /// recognize links to other articles
+    pub articles_url: String,
+    pub remote: String,
+    /// The site's default branch
+    pub main_branch: String,
+    /// Drafts are synced to `{drafts_branch_prefix}{slug}`
+    pub drafts_branch_prefix: String,
+    /// Start image file names with the article slug (`{slug}-{hash}.webp`)
+    pub slug_image_names: bool,
+}
+
+impl Default for RepoLayout {
+    fn default() -> Self {
+        RepoLayout {
+            articles_dir: "content/articles".to_string(),
+            images_dir: "content/images".to_string(),
+            public_images_dir: "public/images".to_string(),
+            drafts_dir: "drafts".to_string(),
+            drafts_images_dir: "drafts/images".to_string(),
+            images_url: "/images".to_string(),
+            drafts_images_url: "/drafts/images".to_string(),
+            articles_url: "/articles".to_string(),
+            remote: "origin".to_string(),
+            main_branch: "main".to_string(),
+            drafts_branch_prefix: "drafts/".to_string(),
+            slug_image_names: true,
+        }
+    }
+}
+
+impl RepoLayout {
+    /// `rel` (one of the directories above) resolved against `root`
+    pub fn dir(root: &Path, rel: &str) -> PathBuf {
+        rel.split('/')
+            .filter(|part| !part.is_empty())
+            .fold(root.to_path_buf(), |path, part| path.join(part))
+    }
+
+    pub fn article_path(&self, slug: &str) -> String {
+        self.article_file(&format!("{}.md", slug))
+    }
+
+    /// `name` in the articles directory, e.g. `2026-01-15-slug.md`
+    pub fn article_file(&self, name: &str) -> String {
+        join(&self.articles_dir, name)
+    }
+
+    /// Page bundle directory for `slug`, holding `index.md` and its images
+    pub fn bundle_dir(&self, slug: &str) -> String {
+        join(&self.articles_dir, slug)
+    }
+
+    pub fn draft_path(&self, slug: &str) -> String {
+        join(&self.drafts_dir, &format!("{}.md", slug))
+    }
+
+    pub fn image_path(&self, filename: &str) -> String {
+        join(&self.images_dir, filename)
+    }
+
+    pub fn public_image_path(&self, filename: &str) -> Option<String> {
+        (!self.public_images_dir.is_empty()).then(|| join(&self.public_images_dir, filename))
+    }
+
+    pub fn drafts_image_path(&self, filename: &str) -> String {
+        join(&self.drafts_images_dir, filename)
+    }
+
+    pub fn image_url(&self, filename: &str) -> String {
+        format!("{}/{}", self.images_url.trim_end_matches('/'), filename)
+    }
+
+    pub fn drafts_image_url(&self, filename: &str) -> String {
+        format!(
+            "{}/{}",
+            self.drafts_images_url.trim_end_matches('/'),
+            filename
+        )
+    }
+
+    /// The file name behind a drafts image URL, if `url` is one
+    pub fn drafts_image_name<'a>(&self, url: &'a str) -> Option<&'a str> {
+        url.strip_prefix(self.drafts_images_url.trim_end_matches('/'))?
+            .strip_prefix('/')
+    }
+
+    /// The file name behind a published image URL, if `url` is one
+    pub fn image_name<'a>(&self, url: &'a str) -> Option<&'a str> {
+        url.strip_prefix(self.images_url.trim_end_matches('/'))?
+            .strip_prefix('/')
+    }
+
+    /// What image file names for `slug` start with, if anything
+    pub fn image_name_prefix<'a>(&self, slug: &'a str) -> &'a str {
+        if self.slug_image_names {
+            slug
+        } else {
+            ""
+        }
+    }
+
+    pub fn drafts_branch(&self, slug: &str) -> String {
+        format!("{}{}", self.drafts_branch_prefix, slug)
+    }
+
+    /// `branch` as a remote-tracking name, e.g. `origin/main`
+    pub fn remote_branch(&self, branch: &str) -> String {
+        format!("{}/{}", self.remote, branch)
+    }
+
+    /// Directories to stage after changing published articles in the checkout
+    /// at `root`. Ones that aren't there (`public/images` on a Hugo site) are
+    /// left out, since `git add` fails on them.
+    pub fn publish_paths(&self, root: &Path) -> Vec<&str> {
+        let mut paths = vec![self.articles_dir.as_str(), self.images_dir.as_str()];
+        if !self.public_images_dir.is_empty() {
+            paths.push(self.public_images_dir.as_str());
+        }
+        paths.retain(|path| RepoLayout::dir(root, path).exists());
+        paths
+    }
+
+    /// Settings that can't work, described for the user
+    pub fn problems(&self) -> Vec<String> {
+        let mut problems = Vec::new();
+        let dirs = [
+            ("Articles directory", &self.articles_dir, false),
+            ("Images directory", &self.images_dir, false),
+            ("Public images directory", &self.public_images_dir, true),
+            ("Drafts directory", &self.drafts_dir, false),
+            ("Drafts images directory", &self.drafts_images_dir, false),
+        ];
+        for (label, dir, optional) in dirs {
+            if dir.is_empty() {
+                if !optional {
+                    problems.push(format!("{} is empty", label));
+                }
+            } else if !is_relative_inside(dir) {
+                problems.push(format!(
+                    "{} must be a path inside the repository: {}",
+                    label, dir
+                ));
+            }
+        }
+
+        for (label, url) in [
+            ("Images URL", &self.images_url),
+            ("Drafts images URL", &self.drafts_images_url),
+        ] {
+            if url.is_empty() {
+                problems.push(format!("{} is empty", label));
+            }
+        }
+
+        for (label, name) in [
+            ("Remote", &self.remote),
+            ("Main branch", &self.main_branch),
+            ("Drafts branch prefix", &self.drafts_branch_prefix),
+        ] {
+            if name.trim().is_empty() || name.contains(char::is_whitespace) || name.contains("..") {
+                problems.push(format!("{} is not a valid git name: \"{}\"", label, name));
+            }
+        }
+
+        problems
+    }
+}
+
+fn join(dir: &str, name: &str) -> String {
+    let dir = dir.trim_end_matches('/');
+    if dir.is_empty() {
+        name.to_string()
+    } else {
+        format!("{}/{}", dir, name)
+    }
+}
+
+fn is_relative_inside(dir: &str) -> bool {
+    Path::new(dir)
+        .components()
+        .all(|component| matches!(component, Component::Normal(_) | Component::CurDir))
+}
@@ -1,16 +1,48 @@
-use base64::{engine::general_purpose::STANDARD, Engine};
-use image::{codecs::webp::WebPEncoder, ColorType, DynamicImage};
-use regex::Regex;
-use reqwest::header::CONTENT_TYPE;
-use reqwest::Client;
 use serde::{Deserialize, Serialize};
+use std::collections::HashSet;
 use std::fs;
 use std::path::Path;
-use std::process::Command;
 use tauri::State;
 
+mod audit;
 mod database;
-use database::{Database, Draft, DraftStatus, DraftSummary};
+mod downloads;
+mod error;
+mod frontmatter;
+mod git;
+mod images;
+mod import;
+mod layout;
+mod markdown;
+mod orphans;
+mod publish;
+mod pull;
+mod redirects;
+mod slug;
+mod ssg;
+mod tags;
+mod unpublish;
+mod validation;
+use database::{
+    Database, Draft, DraftSearchResult, DraftStatus, DraftSummary, ExtraFields, TrashedDraft, Version, VersionDiff,
+    VersionPolicy, VersionSummary,
+};
+use downloads::ImageDownloader;
+use error::{AppError, AppResult, ErrorKind};
+use frontmatter::DraftFrontmatter;
+use git::{first_existing_rev, tracking_refspec, GitBackend, GitBackendKind, Worktree};
+use images::{ImageOptions, ImageReport};
+use import::ImportResult;
+use layout::RepoLayout;
+use orphans::{OrphanCleanup, OrphanScan};
+use publish::{PublishDraftArgs, PublishMode, PublishPlan, PublishStrategy};
+use pull::PullResult;
+use redirects::RedirectOptions;
+use slug::{check_slug, SlugRename};
+use ssg::SiteGenerator;
+use tags::{TagSuggestion, TagUpdateResult};
+use unpublish::Unpublished;
+use validation::{RuleMode, ValidationReport, ValidationRules};
 
 // ============================================================================
 // Settings Types and Commands
@@ -23,38 +55,90 @@ pub struct Settings {
     pub theme: String,
     #[serde(default = "default_editor_width")]
     pub editor_width: String,
+    #[serde(default = "default_max_versions")]
+    pub max_versions: usize,
+    /// Days a deleted draft stays in the trash before it is purged (0 = forever)
+    #[serde(default = "default_trash_retention_days")]
+    pub trash_retention_days: u32,
+    /// Run git through the `git` executable or in-process with libgit2
+    #[serde(default)]
+    pub git_backend: GitBackendKind,
+    /// Push directly or through a pull request, and how that PR looks
+    #[serde(default)]
+    pub publish: PublishStrategy,
+    /// Where articles, drafts and images live in the repo, and which remote
+    /// and branches to use
+    #[serde(default)]
+    pub layout: RepoLayout,
+    /// Frontmatter, file naming and image conventions for published articles
+    #[serde(default)]
+    pub site_generator: SiteGenerator,
+    /// Maximum dimensions and `srcset` breakpoints for published images
+    #[serde(default)]
+    pub images: ImageOptions,
+    /// Checks run before publishing and whether each one blocks
+    #[serde(default)]
+    pub validation: ValidationRules,
+    /// How old URLs are redirected when a published article's slug changes
+    #[serde(default)]
+    pub redirects: RedirectOptions,
 }
 
 fn default_editor_width() -> String {
     "medium".to_string()
 }
 
+fn default_max_versions() -> usize {
+    VersionPolicy::default().max_versions
+}
+
+fn default_trash_retention_days() -> u32 {
+    30
+}
+
+impl From<&Settings> for VersionPolicy {
+    fn from(settings: &Settings) -> Self {
+        VersionPolicy {
+            max_versions: settings.max_versions,
+        }
+    }
+}
+
 impl Default for Settings {
     fn default() -> Self {
         Settings {
             repo_path: String::new(),
             theme: "dark".to_string(),
             editor_width: default_editor_width(),
+            max_versions: default_max_versions(),
+            trash_retention_days: default_trash_retention_days(),
+            git_backend: GitBackendKind::default(),
+            publish: PublishStrategy::default(),
+            layout: RepoLayout::default(),
+            site_generator: SiteGenerator::default(),
+            images: ImageOptions::default(),
+            validation: ValidationRules::default(),
+            redirects: RedirectOptions::default(),
         }
     }
 }
 
 /// Get the app data directory for storing settings
-fn get_settings_path() -> Result<std::path::PathBuf, String> {
+fn get_settings_path() -> AppResult<std::path::PathBuf> {
     let data_dir = dirs::data_dir()
-        .ok_or("Could not determine app data directory")?
+        .ok_or_else(|| AppError::new(ErrorKind::Settings, "Could not determine app data directory"))?
         .join("com.pranavhari.nibandh");
 
     // Create directory if it doesn't exist
     fs::create_dir_all(&data_dir)
-        .map_err(|e| format!("Failed to create settings directory: {}", e))?;
+        .map_err(|e| AppError::settings("Failed to create settings directory", e))?;
 
     Ok(data_dir.join("settings.json"))
 }
 
 /// Get settings from disk
 #[tauri::command]
-fn get_settings() -> Result<Settings, String> {
+fn get_settings() -> AppResult<Settings> {
     let settings_path = get_settings_path()?;
 
     if !settings_path.exists() {
@@ -62,22 +146,46 @@ fn get_settings() -> Result<Settings, String> {
     }
 
     let content = fs::read_to_string(&settings_path)
-        .map_err(|e| format!("Failed to read settings: {}", e))?;
+        .map_err(|e| AppError::settings("Failed to read settings", e))?;
 
     serde_json::from_str(&content)
-        .map_err(|e| format!("Failed to parse settings: {}", e))
+        .map_err(|e| AppError::settings("Failed to parse settings", e))
+}
+
+/// The git backend chosen in settings. Settings that fail to parse are an
+/// error rather than defaults, so a command never pushes with a strategy,
+/// layout or branch the user didn't choose.
+fn git_backend() -> AppResult<Box<dyn GitBackend>> {
+    get_settings().map(|s| git::backend(s.git_backend))
+}
+
+/// The repo layout from settings
+fn repo_layout() -> AppResult<RepoLayout> {
+    get_settings().map(|s| s.layout)
+}
+
+/// Image size limits from settings
+fn image_options() -> AppResult<ImageOptions> {
+    get_settings().map(|s| s.images)
 }
 
 /// Save settings to disk
 #[tauri::command]
-fn save_settings(settings: Settings) -> Result<(), String> {
+fn save_settings(db: State<Database>, settings: Settings) -> AppResult<()> {
+    let problems = settings.layout.problems();
+    if !problems.is_empty() {
+        return Err(AppError::invalid(problems.join("; ")));
+    }
+
     let settings_path = get_settings_path()?;
 
     let content = serde_json::to_string_pretty(&settings)
-        .map_err(|e| format!("Failed to serialize settings: {}", e))?;
+        .map_err(|e| AppError::settings("Failed to serialize settings", e))?;
 
     fs::write(&settings_path, content)
-        .map_err(|e| format!("Failed to write settings: {}", e))?;
+        .map_err(|e| AppError::settings("Failed to write settings", e))?;
+
+    db.set_version_policy(VersionPolicy::from(&settings))?;
 
     Ok(())
 }
@@ -89,6 +197,8 @@ pub struct RepoValidation {
     pub is_git_repo: bool,
     pub has_content_dir: bool,
     pub error: Option<String>,
+    /// Things that won't stop publishing but are worth knowing about
+    pub warnings: Vec<String>,
 }
 
 /// Validate a repository path
@@ -103,6 +213,7 @@ fn validate_repo_path(path: String) -> RepoValidation {
             is_git_repo: false,
             has_content_dir: false,
             error: Some("Path does not exist".to_string()),
+            warnings: Vec::new(),
         };
     }
 
@@ -110,11 +221,45 @@ fn validate_repo_path(path: String) -> RepoValidation {
     let git_dir = repo_path.join(".git");
     let is_git_repo = git_dir.exists();
 
-    // Check if content directory exists or can be created
-    let content_dir = repo_path.join("content");
-    let has_content_dir = content_dir.exists();
+    // Check the layout's directories exist or can be created
+    let settings = match get_settings() {
+        Ok(settings) => settings,
+        Err(e) => {
+            return RepoValidation {
+                is_valid: false,
+                is_git_repo,
+                has_content_dir: false,
+                error: Some(e.to_string()),
+                warnings: Vec::new(),
+            }
+        }
+    };
+    let layout = &settings.layout;
+    let problems = layout.problems();
+    let has_content_dir = RepoLayout::dir(repo_path, &layout.articles_dir).exists();
+
+    let mut warnings = Vec::new();
+    if problems.is_empty() {
+        for dir in [&layout.articles_dir, &layout.images_dir, &layout.public_images_dir] {
+            if !dir.is_empty() && !RepoLayout::dir(repo_path, dir).exists() {
+                warnings.push(format!("{}/ will be created on first publish", dir));
+            }
+        }
+        if is_git_repo {
+            let main = layout.remote_branch(&layout.main_branch);
+            if git::backend(settings.git_backend)
+                .resolve(repo_path, &main)
+                .is_none()
+            {
+                warnings.push(format!(
+                    "{} not found; fetch or check the remote and branch names",
+                    main
+                ));
+            }
+        }
+    }
 
-    let is_valid = is_git_repo; // At minimum, must be a git repo
+    let is_valid = is_git_repo && problems.is_empty(); // At minimum, must be a git repo
 
     RepoValidation {
         is_valid,
@@ -122,784 +267,405 @@ fn validate_repo_path(path: String) -> RepoValidation {
         has_content_dir,
         error: if !is_git_repo {
             Some("Not a git repository (no .git directory)".to_string())
+        } else if !problems.is_empty() {
+            Some(format!("Invalid layout: {}", problems.join("; ")))
         } else {
             None
         },
+        warnings,
     }
 }
 
-#[derive(Debug, Deserialize)]
-#[serde(rename_all = "camelCase")]
-struct PublishDraftArgs {
-    slug: String,
-    title: String,
-    date: String,
-    tags: Vec<String>,
-    description: String,
-    cover: String,         // base64 data URL or empty
-    cover_position: Option<f64>,
-    updated_at: Option<String>,
-    content: String,       // markdown content
-    commit_message: String,
-    repo_path: String,
-}
-
 #[derive(Debug, Serialize)]
 struct PublishResult {
     success: bool,
     message: String,
     file_path: Option<String>,
+    /// Bytes saved by re-encoding each image
+    images: Vec<ImageReport>,
+    /// Images that were dropped or left as remote links
+    warnings: Vec<String>,
 }
 
+/// Dry run of `publish_draft`: report the files, branch and pull request
+/// changes publishing would make, without writing anything
 #[tauri::command]
-async fn publish_draft(args: PublishDraftArgs) -> Result<PublishResult, String> {
-    let repo_path = Path::new(&args.repo_path);
-
-    // Verify repo exists
-    if !repo_path.exists() {
-        return Err(format!("Repository path does not exist: {}", args.repo_path));
-    }
-
-    let mut stashed = false;
-
-    let status_result = Command::new("git")
-        .current_dir(&repo_path)
-        .args(["status", "--porcelain"])
-        .output()
-        .map_err(|e| format!("Failed to get git status: {}", e))?;
+async fn plan_publish(args: PublishDraftArgs) -> AppResult<PublishPlan> {
+    check_slug(&args.slug)?;
+    let settings = get_settings()?;
+    let downloader = ImageDownloader::new(&settings.images)?;
+    let git = git::backend(settings.git_backend);
+    publish::plan_publish(
+        &args,
+        &downloader,
+        git.as_ref(),
+        &settings.publish,
+        &settings.layout,
+        &settings.site_generator.profile(),
+        &settings.images,
+    )
+    .await
+}
 
-    if !status_result.status.success() {
-        return Err(format!(
-            "git status failed: {}",
-            String::from_utf8_lossy(&status_result.stderr)
-        ));
-    }
-
-    if !String::from_utf8_lossy(&status_result.stdout).trim().is_empty() {
-        let stash_result = Command::new("git")
-            .current_dir(&repo_path)
-            .args(["stash", "push", "-u", "-m", "Nibandh auto-stash"])
-            .output()
-            .map_err(|e| format!("Failed to stash changes: {}", e))?;
-
-        if !stash_result.status.success() {
-            return Err(format!(
-                "git stash failed: {}",
-                String::from_utf8_lossy(&stash_result.stderr)
-            ));
-        }
-        stashed = true;
+/// Slugs on the publish base branch for the internal links rule, unless the
+/// rule is off or there is no repo to read them from
+fn link_targets(settings: &Settings, repo_path: &str) -> AppResult<Option<HashSet<String>>> {
+    let repo_path = Path::new(repo_path);
+    if settings.validation.internal_links == RuleMode::Off || !repo_path.exists() {
+        return Ok(None);
     }
+    let git = git::backend(settings.git_backend);
+    slug::published_slugs(git.as_ref(), repo_path, &settings.layout, &settings.publish).map(Some)
+}
 
-    let branch_name = format!("drafts/{}", args.slug);
-
-    let current_branch = Command::new("git")
-        .current_dir(&repo_path)
-        .args(["rev-parse", "--abbrev-ref", "HEAD"])
-        .output()
-        .map_err(|e| format!("Failed to get current branch: {}", e))?;
-
-    let original_branch = String::from_utf8_lossy(&current_branch.stdout).trim().to_string();
-
-    let _ = Command::new("git")
-        .current_dir(&repo_path)
-        .args(["fetch", "origin", "main"])
-        .output();
-
-    let branch_check = Command::new("git")
-        .current_dir(&repo_path)
-        .args(["show-ref", "--verify", "--quiet", &format!("refs/heads/{}", branch_name)])
-        .output()
-        .map_err(|e| format!("Failed to check branch: {}", e))?;
-
-    if branch_check.status.success() {
-        let checkout_result = Command::new("git")
-            .current_dir(&repo_path)
-            .args(["checkout", &branch_name])
-            .output()
-            .map_err(|e| format!("Failed to checkout publish branch: {}", e))?;
-
-        if !checkout_result.status.success() {
-            if stashed {
-                let _ = Command::new("git")
-                    .current_dir(&repo_path)
-                    .args(["stash", "pop"])
-                    .output();
-            }
-            return Err(format!(
-                "Failed to checkout publish branch: {}",
-                String::from_utf8_lossy(&checkout_result.stderr)
-            ));
-        }
-    } else {
-        let create_result = Command::new("git")
-            .current_dir(&repo_path)
-            .args(["checkout", "-b", &branch_name, "origin/main"])
-            .output()
-            .map_err(|e| format!("Failed to create publish branch: {}", e))?;
-
-        if !create_result.status.success() {
-            let fallback_result = Command::new("git")
-                .current_dir(&repo_path)
-                .args(["checkout", "-b", &branch_name, "main"])
-                .output()
-                .map_err(|e| format!("Failed to create publish branch from main: {}", e))?;
-
-            if !fallback_result.status.success() {
-                if stashed {
-                    let _ = Command::new("git")
-                        .current_dir(&repo_path)
-                        .args(["stash", "pop"])
-                        .output();
-                }
-                return Err(format!(
-                    "Failed to create publish branch: {}",
-                    String::from_utf8_lossy(&fallback_result.stderr)
-                ));
-            }
-        }
+/// The freshly fetched drafts branch of `slug` for the image size rule,
+/// unless the rule is off or the branch doesn't exist. Drafts images are
+/// synced there, not to the user's checkout.
+fn drafts_images_rev(
+    settings: &Settings,
+    git: &dyn GitBackend,
+    repo_path: &str,
+    slug: &str,
+) -> Option<String> {
+    let repo_path = Path::new(repo_path);
+    if settings.validation.image_size == RuleMode::Off || !repo_path.exists() {
+        return None;
     }
+    let layout = &settings.layout;
+    let branch = layout.drafts_branch(slug);
+    // Not pushed yet is fine: a local branch may still be there
+    let _ = git.fetch(
+        repo_path,
+        &layout.remote,
+        &[&tracking_refspec(&layout.remote, &branch)],
+    );
+    first_existing_rev(git, repo_path, &[&layout.remote_branch(&branch), &branch])
+}
 
-    let content_dir = repo_path.join("content");
-    let articles_dir = content_dir.join("articles");
-    let images_dir = content_dir.join("images");
-    let public_images_dir = repo_path.join("public").join("images");
+/// Check a draft against the pre-publish rules from settings
+#[tauri::command]
+async fn validate_draft(args: PublishDraftArgs) -> AppResult<ValidationReport> {
+    let settings = get_settings()?;
+    let published = link_targets(&settings, &args.repo_path)?;
+    let git = git::backend(settings.git_backend);
+    let drafts = drafts_images_rev(&settings, git.as_ref(), &args.repo_path, &args.slug);
+    let read_synced_image = |name: &str| {
+        let path = settings.layout.drafts_image_path(name);
+        git.read_file(Path::new(&args.repo_path), drafts.as_ref()?, &path)
+    };
+    validation::validate_draft(
+        &args,
+        &settings.validation,
+        &settings.layout,
+        published.as_ref(),
+        &read_synced_image,
+    )
+}
 
-    // Create directories if they don't exist
-    fs::create_dir_all(&articles_dir).map_err(|e| format!("Failed to create articles dir: {}", e))?;
-    fs::create_dir_all(&images_dir).map_err(|e| format!("Failed to create images dir: {}", e))?;
-    fs::create_dir_all(&public_images_dir)
-        .map_err(|e| format!("Failed to create public images dir: {}", e))?;
+#[tauri::command]
+async fn publish_draft(args: PublishDraftArgs) -> AppResult<PublishResult> {
+    check_slug(&args.slug)?;
+    let repo_path = Path::new(&args.repo_path);
 
-    // Handle cover image if present (base64 data URL)
-    let client = Client::builder()
-        .timeout(std::time::Duration::from_secs(20))
-        .build()
-        .map_err(|e| format!("Failed to create HTTP client: {}", e))?;
+    // Verify repo exists
+    if !repo_path.exists() {
+        return Err(AppError::repo_not_found(args.repo_path));
+    }
 
-    let cover_path = if !args.cover.is_empty() && args.cover.starts_with("data:image") {
-        match save_base64_image(&args.cover, &images_dir, &args.slug) {
-            Ok(filename) => {
-                let _ = copy_to_public_images(&public_images_dir, &images_dir, &filename);
-                format!("/images/{}", filename)
-            }
-            Err(e) => {
-                eprintln!("Failed to save cover image: {}", e);
-                String::new()
-            }
-        }
-    } else if !args.cover.is_empty() && args.cover.starts_with("http") {
-        match save_remote_image(
-            &client,
-            &args.cover,
-            &images_dir,
-            &args.slug,
-            None,
-            "cover",
-        )
-        .await
-        {
-            Ok(filename) => {
-                let _ = copy_to_public_images(&public_images_dir, &images_dir, &filename);
-                format!("/images/{}", filename)
-            }
-            Err(e) => {
-                eprintln!("Failed to download cover image: {}", e);
-                args.cover.clone()
-            }
-        }
-    } else if !args.cover.is_empty() && args.cover.starts_with("/drafts/images/") {
-        let filename = args.cover.trim_start_matches("/drafts/images/").to_string();
-        let drafts_images_dir = repo_path.join("drafts").join("images");
-        let source_path = drafts_images_dir.join(&filename);
-        let target_path = images_dir.join(&filename);
-        if source_path.exists() {
-            let _ = fs::copy(&source_path, &target_path);
-            let _ = copy_to_public_images(&public_images_dir, &images_dir, &filename);
-            format!("/images/{}", filename)
-        } else {
-            args.cover.clone()
-        }
-    } else if !args.cover.is_empty() {
-        // Already a path, keep it
-        args.cover.clone()
-    } else {
-        String::new()
+    let settings = get_settings()?;
+    let strategy = &settings.publish;
+    let layout = &settings.layout;
+
+    // Check before opening the publish worktree, so a blocked publish leaves
+    // no branch behind
+    let git = git::backend(settings.git_backend);
+    let published = link_targets(&settings, &args.repo_path)?;
+    let drafts = drafts_images_rev(&settings, git.as_ref(), &args.repo_path, &args.slug);
+    let read_synced_image = |name: &str| {
+        git.read_file(repo_path, drafts.as_ref()?, &layout.drafts_image_path(name))
     };
-
-    let content = replace_inline_images(
-        &args.content,
-        &images_dir,
-        Some(&public_images_dir),
-        Some(&client),
-        &args.slug,
-        "/images",
+    validation::validate_draft(
+        &args,
+        &settings.validation,
+        layout,
+        published.as_ref(),
+        &read_synced_image,
+    )?
+    .check()?;
+    let findings = audit::audit_article(&args.content, &args.cover, &args.cover_alt)?;
+    strategy.audit.check(&findings)?;
+
+    let branch_name = strategy.branch(layout, &args.slug);
+    let start_points = strategy.start_points(layout, &args.slug);
+    let start_points: Vec<&str> = start_points.iter().map(String::as_str).collect();
+
+    // Work in a temporary worktree so the user's checkout is never touched
+    let worktree = Worktree::open(
+        git.as_ref(),
+        repo_path,
+        &layout.remote,
+        &branch_name,
+        &start_points,
+    )?;
+
+    let downloader = ImageDownloader::new(&settings.images)?;
+    let drafts_images_dir = RepoLayout::dir(worktree.path(), &layout.drafts_images_dir);
+    let profile = settings.site_generator.profile();
+    let read_drafts_image = |name: &str| fs::read(drafts_images_dir.join(name)).ok();
+    let article = publish::prepare_article(
+        &args,
+        &downloader,
+        layout,
+        &profile,
+        &settings.images,
+        &read_drafts_image,
     )
     .await?;
-
-    // Generate YAML frontmatter
-    let tags_yaml = args
-        .tags
-        .iter()
-        .map(|t| format!("\"{}\"", t.replace('"', "\\\"")))
-        .collect::<Vec<_>>()
-        .join(", ");
-
-    let cover_position = args.cover_position.unwrap_or(50.0);
-    let updated_at = args.updated_at.unwrap_or_else(|| args.date.clone());
-    let frontmatter = format!(
-        r#"---
-title: "{}"
-date: "{}"
-tags: [{}]
-description: "{}"
-cover: "{}"
-cover_position: {}
-last_updated: "{}"
----
-
-"#,
-        args.title.replace('"', "\\\""),
-        args.date,
-        tags_yaml,
-        args.description.replace('"', "\\\""),
-        cover_path,
-        cover_position,
-        updated_at
-    );
-
-    // Write markdown file
-    let article_path = articles_dir.join(format!("{}.md", args.slug));
-    let full_content = format!("{}{}", frontmatter, content);
-
-    fs::write(&article_path, &full_content)
-        .map_err(|e| format!("Failed to write article: {}", e))?;
+    let written = publish::write_article(worktree.path(), layout, &article)?;
+    let article_path = article.path.clone();
 
     // Git operations
     // 1. git add
-    let add_result = Command::new("git")
-        .current_dir(&repo_path)
-        .args(["add", "content/", "public/images/"])
-        .output()
-        .map_err(|e| format!("Failed to run git add: {}", e))?;
-
-    if !add_result.status.success() {
-        let _ = Command::new("git")
-            .current_dir(&repo_path)
-            .args(["checkout", &original_branch])
-            .output();
-        if stashed {
-            let _ = Command::new("git")
-                .current_dir(&repo_path)
-                .args(["stash", "pop"])
-                .output();
-        }
-        return Err(format!(
-            "git add failed: {}",
-            String::from_utf8_lossy(&add_result.stderr)
-        ));
-    }
+    let written: Vec<&str> = written.iter().map(String::as_str).collect();
+    worktree.add(&written)?;
 
-    // 2. git commit
-    let commit_result = Command::new("git")
-        .current_dir(&repo_path)
-        .args(["commit", "-m", &args.commit_message])
-        .output()
-        .map_err(|e| format!("Failed to run git commit: {}", e))?;
-
-    if !commit_result.status.success() {
-        let stderr = String::from_utf8_lossy(&commit_result.stderr);
-        // "nothing to commit" is not really an error
-        if !stderr.contains("nothing to commit") {
-            let _ = Command::new("git")
-                .current_dir(&repo_path)
-                .args(["checkout", &original_branch])
-                .output();
-            if stashed {
-                let _ = Command::new("git")
-                    .current_dir(&repo_path)
-                    .args(["stash", "pop"])
-                    .output();
-            }
-            return Err(format!("git commit failed: {}", stderr));
-        }
-    }
+    // 2. git commit ("nothing to commit" is not really an error)
+    worktree.commit(&args.commit_message)?;
 
     // 3. git push branch
-    let push_result = Command::new("git")
-        .current_dir(&repo_path)
-        .args(["push", "-u", "origin", &branch_name])
-        .output()
-        .map_err(|e| format!("Failed to run git push: {}", e))?;
-
-    if !push_result.status.success() {
-        let _ = Command::new("git")
-            .current_dir(&repo_path)
-            .args(["checkout", &original_branch])
-            .output();
-        if stashed {
-            let _ = Command::new("git")
-                .current_dir(&repo_path)
-                .args(["stash", "pop"])
-                .output();
-        }
-        return Err(format!(
-            "git push failed: {}",
-            String::from_utf8_lossy(&push_result.stderr)
-        ));
+    worktree.push()?;
+
+    if strategy.mode == PublishMode::DirectPush {
+        return Ok(PublishResult {
+            success: true,
+            message: format!("Published directly to {}.", branch_name),
+            file_path: Some(article_path),
+            images: article.image_reports(),
+            warnings: article.warnings,
+        });
     }
 
     // 4. Create PR (if needed)
-    let pr_number_output = Command::new("gh")
-        .current_dir(&repo_path)
-        .args([
-            "pr",
-            "list",
-            "--head",
-            &branch_name,
-            "--json",
-            "number",
-            "--jq",
-            ".[0].number",
-        ])
-        .output()
-        .map_err(|e| format!("Failed to list PRs: {}", e))?;
-
-    let mut pr_number = String::from_utf8_lossy(&pr_number_output.stdout).trim().to_string();
-
-    if pr_number.is_empty() {
-        let pr_create = Command::new("gh")
-            .current_dir(&repo_path)
-            .args([
-                "pr",
-                "create",
-                "--title",
-                &format!("Publish: {}", args.title),
-                "--body",
-                "Published via Nibandh",
-                "--head",
-                &branch_name,
-                "--base",
-                "main",
-            ])
-            .output()
-            .map_err(|e| format!("Failed to create PR: {}", e))?;
-
-        if !pr_create.status.success() {
-            return Err(format!(
-                "Failed to create PR: {}",
-                String::from_utf8_lossy(&pr_create.stderr)
-            ));
+    let (pr_url, created) = publish::open_pull_request(
+        worktree.path(),
+        &branch_name,
+        strategy.base_branch(layout),
+        strategy,
+        &args,
+    )?;
+    let opened = if created { "created" } else { "updated" };
+
+    // 5. Merge PR if the strategy asks for it
+    let message = if strategy.mode == PublishMode::PullRequest {
+        format!("PR {} for review: {}", opened, pr_url)
+    } else {
+        match publish::merge_pull_request(worktree.path(), &pr_url, strategy.merge_method) {
+            Ok(()) => format!("PR {} and merged successfully.", opened),
+            Err(e) => format!(
+                "PR {} for '{}' but could not be merged automatically: {}",
+                opened, args.title, e
+            ),
         }
-
-        let pr_number_output = Command::new("gh")
-            .current_dir(&repo_path)
-            .args([
-                "pr",
-                "list",
-                "--head",
-                &branch_name,
-                "--json",
-                "number",
-                "--jq",
-                ".[0].number",
-            ])
-            .output()
-            .map_err(|e| format!("Failed to list PRs: {}", e))?;
-
-        pr_number = String::from_utf8_lossy(&pr_number_output.stdout).trim().to_string();
-    }
-
-    if pr_number.is_empty() {
-        return Err("Failed to resolve PR number".to_string());
-    }
-
-    // 5. Merge PR if possible
-    let merge_result = Command::new("gh")
-        .current_dir(&repo_path)
-        .args(["pr", "merge", &pr_number, "--merge", "--delete-branch"])
-        .output()
-        .map_err(|e| format!("Failed to merge PR: {}", e))?;
-
-    let mut message = "PR created and merged successfully.".to_string();
-    if !merge_result.status.success() {
-        let stderr = String::from_utf8_lossy(&merge_result.stderr);
-        message = format!(
-            "PR created for '{}' but could not be merged automatically: {}",
-            args.title, stderr
-        );
-    }
-
-    // Switch back to original branch
-    let _ = Command::new("git")
-        .current_dir(&repo_path)
-        .args(["checkout", &original_branch])
-        .output();
-
-    if stashed {
-        let _ = Command::new("git")
-            .current_dir(&repo_path)
-            .args(["stash", "pop"])
-            .output();
-    }
+    };
 
     Ok(PublishResult {
         success: true,
         message,
-        file_path: Some(article_path.to_string_lossy().to_string()),
+        file_path: Some(article_path),
+        images: article.image_reports(),
+        warnings: article.warnings,
     })
 }
 
-/// Save a base64 data URL image to the filesystem
-fn save_base64_image(data_url: &str, images_dir: &Path, slug: &str) -> Result<String, String> {
-    // Parse data URL: data:image/jpeg;base64,/9j/4AAQ...
-    let parts: Vec<&str> = data_url.splitn(2, ',').collect();
-    if parts.len() != 2 {
-        return Err("Invalid data URL format".to_string());
-    }
-
-    let header = parts[0];
-    let base64_data = parts[1];
-
-    // Decode base64 and write to file
-    let image_data = STANDARD
-        .decode(base64_data)
-        .map_err(|e| format!("Failed to decode base64: {}", e))?;
-
-    if let Ok(image) = image::load_from_memory(&image_data) {
-        let webp_data = encode_webp(&image)?;
-        let filename = format!("cover_{}.webp", slug);
-        let file_path = images_dir.join(&filename);
-        fs::write(&file_path, webp_data).map_err(|e| format!("Failed to write image: {}", e))?;
-        return Ok(filename);
-    }
-
-    // Fallback to original format if decoding fails
-    let extension = if header.contains("image/png") {
-        "png"
-    } else if header.contains("image/gif") {
-        "gif"
-    } else if header.contains("image/webp") {
-        "webp"
-    } else if header.contains("image/heic") || header.contains("image/heif") {
-        "heic"
-    } else {
-        "jpg"
+/// A slug for `title`, made unique among the articles on the publish base
+/// branch if a repo is given. `current` is the draft's own slug.
+#[tauri::command]
+async fn generate_slug(
+    title: String,
+    repo_path: Option<String>,
+    current: Option<String>,
+) -> AppResult<String> {
+    let settings = get_settings()?;
+    let repo_path = repo_path.filter(|path| !path.is_empty());
+    let taken = match repo_path.as_deref().map(Path::new) {
+        Some(repo_path) if repo_path.exists() => {
+            let git = git::backend(settings.git_backend);
+            slug::published_slugs(git.as_ref(), repo_path, &settings.layout, &settings.publish)?
+        }
+        _ => HashSet::new(),
     };
-
-    let filename = format!("cover_{}.{}", slug, extension);
-    let file_path = images_dir.join(&filename);
-    fs::write(&file_path, image_data).map_err(|e| format!("Failed to write image: {}", e))?;
-
-    Ok(filename)
+    Ok(slug::unique_slug(&taken, &title, current.as_deref()))
 }
 
-fn encode_webp(image: &DynamicImage) -> Result<Vec<u8>, String> {
-    let rgba = image.to_rgba8();
-    let (width, height) = rgba.dimensions();
-    let mut buffer = Vec::new();
-    let encoder = WebPEncoder::new_lossless(&mut buffer);
-    encoder
-        .encode(&rgba, width, height, ColorType::Rgba8.into())
-        .map_err(|e| format!("Failed to encode webp: {}", e))?;
-
-    Ok(buffer)
-}
-
-fn save_inline_base64_image(
-    data_url: &str,
-    images_dir: &Path,
-    slug: &str,
-    index: usize,
-) -> Result<String, String> {
-    let parts: Vec<&str> = data_url.splitn(2, ',').collect();
-    if parts.len() != 2 {
-        return Err("Invalid data URL format".to_string());
-    }
-
-    let header = parts[0];
-    let base64_data = parts[1];
-
-    let image_data = STANDARD
-        .decode(base64_data)
-        .map_err(|e| format!("Failed to decode base64: {}", e))?;
-
-    if let Ok(image) = image::load_from_memory(&image_data) {
-        let webp_data = encode_webp(&image)?;
-        let filename = format!("img_{}_{}.webp", slug, index);
-        let file_path = images_dir.join(&filename);
-        fs::write(&file_path, webp_data).map_err(|e| format!("Failed to write image: {}", e))?;
-        return Ok(filename);
+/// Move a published article to a new slug and redirect its old URL
+#[tauri::command]
+async fn rename_slug(
+    repo_path: String,
+    old_slug: String,
+    new_slug: String,
+) -> AppResult<SlugRename> {
+    let repo_path = Path::new(&repo_path);
+    if !repo_path.exists() {
+        return Err(AppError::repo_not_found(repo_path.display()));
     }
 
-    let extension = if header.contains("image/png") {
-        "png"
-    } else if header.contains("image/gif") {
-        "gif"
-    } else if header.contains("image/webp") {
-        "webp"
-    } else if header.contains("image/heic") || header.contains("image/heif") {
-        "heic"
-    } else {
-        "jpg"
-    };
-
-    let filename = format!("img_{}_{}.{}", slug, index, extension);
-    let file_path = images_dir.join(&filename);
-    fs::write(&file_path, image_data).map_err(|e| format!("Failed to write image: {}", e))?;
-
-    Ok(filename)
+    let settings = get_settings()?;
+    let git = git::backend(settings.git_backend);
+    slug::rename_article(
+        git.as_ref(),
+        repo_path,
+        &settings.layout,
+        &settings.site_generator.profile(),
+        &settings.publish,
+        &settings.redirects,
+        &old_slug,
+        &new_slug,
+    )
 }
 
-fn copy_to_public_images(
-    public_images_dir: &Path,
-    images_dir: &Path,
-    filename: &str,
-) -> Result<(), String> {
-    let source_path = images_dir.join(filename);
-    let target_path = public_images_dir.join(filename);
-    if let Some(parent) = target_path.parent() {
-        fs::create_dir_all(parent)
-            .map_err(|e| format!("Failed to create public images dir: {}", e))?;
-    }
-    fs::copy(&source_path, &target_path)
-        .map_err(|e| format!("Failed to copy image to public: {}", e))?;
-    Ok(())
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct UnpublishArgs {
+    /// The local draft to set back to `Draft`; empty to look it up by slug
+    #[serde(default)]
+    draft_id: String,
+    slug: String,
+    repo_path: String,
+    #[serde(default)]
+    reason: String,
 }
 
-fn guess_extension_from_url(url: &str) -> Option<String> {
-    let trimmed = url.split('?').next().unwrap_or(url);
-    let filename = trimmed.rsplit('/').next().unwrap_or(trimmed);
-    let extension = filename.rsplit('.').next().unwrap_or("");
-    match extension.to_lowercase().as_str() {
-        "png" | "jpg" | "jpeg" | "webp" | "gif" | "bmp" | "tiff" | "avif" => {
-            Some(extension.to_lowercase())
-        }
-        _ => None,
-    }
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+struct UnpublishResult {
+    #[serde(flatten)]
+    unpublished: Unpublished,
+    /// The retracted draft, if the takedown landed and there is one in the
+    /// local database
+    draft: Option<Draft>,
 }
 
-async fn save_remote_image(
-    client: &Client,
-    url: &str,
-    images_dir: &Path,
-    slug: &str,
-    index: Option<usize>,
-    prefix: &str,
-) -> Result<String, String> {
-    let response = client
-        .get(url)
-        .send()
-        .await
-        .map_err(|e| format!("Failed to download image: {}", e))?;
-
-    if !response.status().is_success() {
-        return Err(format!(
-            "Failed to download image: HTTP {}",
-            response.status()
-        ));
+/// Take a published article off the site (deleted or flagged as a draft, as
+/// the publish strategy says). Once the change is on the base branch its
+/// draft is set back to `Draft`, recording when and why; while it waits in a
+/// pull request the draft stays `Published`.
+#[tauri::command]
+async fn unpublish_draft(
+    db: State<'_, Database>,
+    args: UnpublishArgs,
+) -> AppResult<UnpublishResult> {
+    let repo_path = Path::new(&args.repo_path);
+    if !repo_path.exists() {
+        return Err(AppError::repo_not_found(repo_path.display()));
     }
 
-    let content_type = response
-        .headers()
-        .get(CONTENT_TYPE)
-        .and_then(|v| v.to_str().ok())
-        .unwrap_or("")
-        .to_string();
-
-    let bytes = response
-        .bytes()
-        .await
-        .map_err(|e| format!("Failed to read image bytes: {}", e))?;
-
-    let filename = if let Ok(image) = image::load_from_memory(&bytes) {
-        let webp_data = encode_webp(&image)?;
-        let name = if let Some(index) = index {
-            format!("img_{}_{}.webp", slug, index)
-        } else {
-            format!("{}_{}.webp", prefix, slug)
-        };
-        let file_path = images_dir.join(&name);
-        fs::write(&file_path, webp_data).map_err(|e| format!("Failed to write image: {}", e))?;
-        name
+    let settings = get_settings()?;
+    let git = git::backend(settings.git_backend);
+    let unpublished = unpublish::unpublish_article(
+        git.as_ref(),
+        repo_path,
+        &settings.layout,
+        &settings.site_generator.profile(),
+        &settings.publish,
+        &args.slug,
+        &args.reason,
+    )?;
+
+    if !unpublished.landed {
+        return Ok(UnpublishResult {
+            unpublished,
+            draft: None,
+        });
+    }
+    let draft_id = if args.draft_id.is_empty() {
+        db.find_draft_by_slug(&args.slug)?.map(|draft| draft.id)
     } else {
-        let extension = if content_type.contains("image/png") {
-            "png".to_string()
-        } else if content_type.contains("image/webp") {
-            "webp".to_string()
-        } else if content_type.contains("image/gif") {
-            "gif".to_string()
-        } else if content_type.contains("image/jpeg") {
-            "jpg".to_string()
-        } else {
-            guess_extension_from_url(url).unwrap_or_else(|| "jpg".to_string())
-        };
-        let name = if let Some(index) = index {
-            format!("img_{}_{}.{}", slug, index, extension)
-        } else {
-            format!("{}_{}.{}", prefix, slug, extension)
-        };
-        let file_path = images_dir.join(&name);
-        fs::write(&file_path, bytes).map_err(|e| format!("Failed to write image: {}", e))?;
-        name
+        Some(args.draft_id)
     };
-
-    Ok(filename)
-}
-
-async fn replace_inline_images(
-    content: &str,
-    images_dir: &Path,
-    public_images_dir: Option<&Path>,
-    client: Option<&Client>,
-    slug: &str,
-    path_prefix: &str,
-) -> Result<String, String> {
-    let re = Regex::new(
-        r#"!\[([^\]]*)\]\((data:image[^)]+|https?://[^)]+)\)|<img([^>]*?)src="(data:image[^"]+|https?://[^"]+)"([^>]*)>"#,
-    )
-    .map_err(|e| format!("Failed to build image regex: {}", e))?;
-    let mut output = String::with_capacity(content.len());
-    let mut last_index = 0;
-    let mut image_index = 1;
-    let prefix = path_prefix.trim_end_matches('/');
-
-    for caps in re.captures_iter(content) {
-        let full = caps.get(0).unwrap();
-        let alt = caps.get(1).map(|m| m.as_str()).unwrap_or("");
-        let data_url = caps.get(2).map(|m| m.as_str());
-        let img_before = caps.get(3).map(|m| m.as_str());
-        let img_data_url = caps.get(4).map(|m| m.as_str());
-        let img_after = caps.get(5).map(|m| m.as_str());
-
-        output.push_str(&content[last_index..full.start()]);
-
-        if let Some(data_url) = data_url {
-            let save_result = if data_url.starts_with("http") {
-                if let Some(client) = client {
-                    save_remote_image(client, data_url, images_dir, slug, Some(image_index), "img")
-                        .await
-                } else {
-                    Err("No HTTP client available for remote image".to_string())
-                }
-            } else {
-                save_inline_base64_image(data_url, images_dir, slug, image_index)
-            };
-
-            match save_result {
-                Ok(filename) => {
-                    if let Some(public_dir) = public_images_dir {
-                        let _ = copy_to_public_images(public_dir, images_dir, &filename);
-                    }
-                    let image_path = format!("{}/{}", prefix, filename);
-                    output.push_str(&format!("![{}]({})", alt, image_path));
-                    image_index += 1;
-                }
-                Err(err) => {
-                    eprintln!("Failed to save inline image: {}", err);
-                    output.push_str(full.as_str());
-                }
-            }
-        } else if let (Some(data_url), Some(before), Some(after)) =
-            (img_data_url, img_before, img_after)
-        {
-            let save_result = if data_url.starts_with("http") {
-                if let Some(client) = client {
-                    save_remote_image(client, data_url, images_dir, slug, Some(image_index), "img")
-                        .await
-                } else {
-                    Err("No HTTP client available for remote image".to_string())
-                }
-            } else {
-                save_inline_base64_image(data_url, images_dir, slug, image_index)
-            };
-
-            match save_result {
-                Ok(filename) => {
-                    if let Some(public_dir) = public_images_dir {
-                        let _ = copy_to_public_images(public_dir, images_dir, &filename);
-                    }
-                    let image_path = format!("{}/{}", prefix, filename);
-                    output.push_str(&format!(
-                        "<img{}src=\"{}\"{}>",
-                        before, image_path, after
-                    ));
-                    image_index += 1;
-                }
-                Err(err) => {
-                    eprintln!("Failed to save inline image: {}", err);
-                    output.push_str(full.as_str());
-                }
-            }
-        } else {
-            output.push_str(full.as_str());
-        }
-
-        last_index = full.end();
-    }
-
-    output.push_str(&content[last_index..]);
-
-    Ok(output)
+    let draft = match draft_id {
+        Some(id) => db.retract_draft(&id, &args.reason)?,
+        None => None,
+    };
+    Ok(UnpublishResult { unpublished, draft })
 }
 
 /// Get the current git status of the repository
 #[tauri::command]
-async fn get_repo_status(repo_path: String) -> Result<String, String> {
+async fn get_repo_status(repo_path: String) -> AppResult<String> {
     let path = Path::new(&repo_path);
     if !path.exists() {
-        return Err("Repository path does not exist".to_string());
+        return Err(AppError::repo_not_found(&repo_path));
     }
 
-    let output = Command::new("git")
-        .current_dir(&path)
-        .args(["status", "--porcelain"])
-        .output()
-        .map_err(|e| format!("Failed to run git status: {}", e))?;
-
-    Ok(String::from_utf8_lossy(&output.stdout).to_string())
+    let status = git_backend()?.status(path)?;
+    Ok(status.iter().map(|line| format!("{}\n", line)).collect())
 }
 
-/// One-time sync: copy content/images -> public/images
+/// One-time sync: copy the images directory into the public images directory
 #[tauri::command]
-async fn sync_public_images(repo_path: String) -> Result<String, String> {
+async fn sync_public_images(repo_path: String) -> AppResult<String> {
     let repo_path = Path::new(&repo_path);
     if !repo_path.exists() {
-        return Err(format!("Repository path does not exist: {}", repo_path.display()));
+        return Err(AppError::repo_not_found(repo_path.display()));
     }
 
-    let source_dir = repo_path.join("content").join("images");
-    let target_dir = repo_path.join("public").join("images");
+    let layout = repo_layout()?;
+    if layout.public_images_dir.is_empty() {
+        return Ok("No public images directory configured; nothing to sync".to_string());
+    }
+    let source_dir = RepoLayout::dir(repo_path, &layout.images_dir);
+    let target_dir = RepoLayout::dir(repo_path, &layout.public_images_dir);
 
     if !source_dir.exists() {
-        return Err("content/images does not exist".to_string());
+        return Err(AppError::not_found(format!(
+            "{} does not exist",
+            layout.images_dir
+        )));
     }
 
-    fs::create_dir_all(&target_dir)
-        .map_err(|e| format!("Failed to create public/images: {}", e))?;
+    fs::create_dir_all(&target_dir).map_err(|e| {
+        AppError::io(format!("Failed to create {}", layout.public_images_dir), e)
+    })?;
 
     let mut copied = 0usize;
     for entry in fs::read_dir(&source_dir)
-        .map_err(|e| format!("Failed to read content/images: {}", e))?
+        .map_err(|e| AppError::io(format!("Failed to read {}", layout.images_dir), e))?
     {
-        let entry = entry.map_err(|e| format!("Failed to read entry: {}", e))?;
+        let entry = entry.map_err(|e| AppError::io("Failed to read entry", e))?;
         let path = entry.path();
         if path.is_file() {
-            if let Some(filename) = path.file_name() {
-                let target_path = target_dir.join(filename);
-                fs::copy(&path, &target_path)
-                    .map_err(|e| format!("Failed to copy {:?}: {}", path, e))?;
-                copied += 1;
+            if let Some(filename) = path.file_name().and_then(|name| name.to_str()) {
+                if images::copy_image(&source_dir, &target_dir, filename)? {
+                    copied += 1;
+                }
             }
         }
     }
 
     Ok(format!(
-        "Synced {} images from content/images to public/images",
-        copied
+        "Copied {} new or changed images from {} to {}",
+        copied, layout.images_dir, layout.public_images_dir
     ))
 }
 
+/// Import published articles and synced drafts from the repo into the local
+/// database. Local drafts with newer changes are left alone and reported as skipped.
+#[tauri::command]
+async fn import_articles(db: State<'_, Database>, repo_path: String) -> AppResult<ImportResult> {
+    let repo_path = Path::new(&repo_path);
+    if !repo_path.exists() {
+        return Err(AppError::repo_not_found(repo_path.display()));
+    }
+
+    let settings = get_settings()?;
+    import::import_repo(
+        &db,
+        repo_path,
+        &settings.layout,
+        &settings.site_generator.profile(),
+    )
+}
+
 #[derive(Debug, Deserialize)]
 #[serde(rename_all = "camelCase")]
 struct SyncDraftArgs {
@@ -910,10 +676,14 @@ struct SyncDraftArgs {
     description: String,
     cover: String,
     cover_position: Option<f64>,
+    #[serde(default)]
+    cover_alt: String,
     updated_at: Option<String>,
     content: String,
     repo_path: String,
     draft_id: String,
+    #[serde(default)]
+    extra_fields: ExtraFields,
 }
 
 #[derive(Debug, Serialize)]
@@ -921,149 +691,54 @@ struct SyncResult {
     success: bool,
     message: String,
     branch: String,
+    /// Images that were dropped or left as remote links, and why
+    warnings: Vec<String>,
 }
 
 /// Sync a draft to the drafts branch (creates branch if it doesn't exist)
 #[tauri::command]
-async fn sync_to_drafts(args: SyncDraftArgs) -> Result<SyncResult, String> {
+async fn sync_to_drafts(args: SyncDraftArgs) -> AppResult<SyncResult> {
+    check_slug(&args.slug)?;
     let repo_path = Path::new(&args.repo_path);
 
     // Verify repo exists
     if !repo_path.exists() {
-        return Err(format!("Repository path does not exist: {}", args.repo_path));
-    }
-
-    let drafts_branch = format!("drafts/{}", args.slug);
-    let mut stashed = false;
-
-    let status_result = Command::new("git")
-        .current_dir(&repo_path)
-        .args(["status", "--porcelain"])
-        .output()
-        .map_err(|e| format!("Failed to get git status: {}", e))?;
-
-    if !status_result.status.success() {
-        return Err(format!(
-            "git status failed: {}",
-            String::from_utf8_lossy(&status_result.stderr)
-        ));
+        return Err(AppError::repo_not_found(args.repo_path));
     }
 
-    if !String::from_utf8_lossy(&status_result.stdout).trim().is_empty() {
-        let stash_result = Command::new("git")
-            .current_dir(&repo_path)
-            .args(["stash", "push", "-u", "-m", "Nibandh auto-stash"])
-            .output()
-            .map_err(|e| format!("Failed to stash changes: {}", e))?;
-
-        if !stash_result.status.success() {
-            return Err(format!(
-                "git stash failed: {}",
-                String::from_utf8_lossy(&stash_result.stderr)
-            ));
-        }
-        stashed = true;
-    }
-
-    // Get current branch name
-    let current_branch = Command::new("git")
-        .current_dir(&repo_path)
-        .args(["rev-parse", "--abbrev-ref", "HEAD"])
-        .output()
-        .map_err(|e| format!("Failed to get current branch: {}", e))?;
-
-    let original_branch = String::from_utf8_lossy(&current_branch.stdout).trim().to_string();
-
-    // Check if drafts branch exists locally
-    let branch_check = Command::new("git")
-        .current_dir(&repo_path)
-        .args(["show-ref", "--verify", "--quiet", &format!("refs/heads/{}", drafts_branch)])
-        .output()
-        .map_err(|e| format!("Failed to check branch: {}", e))?;
-
-    let drafts_branch_exists = branch_check.status.success();
-
-    // If drafts branch doesn't exist, create it
-    if !drafts_branch_exists {
-        // Try to create from origin/drafts first, otherwise from main
-        let fetch_result = Command::new("git")
-            .current_dir(&repo_path)
-            .args(["fetch", "origin", &drafts_branch])
-            .output();
-
-        let create_from = if fetch_result.is_ok() && fetch_result.unwrap().status.success() {
-            format!("origin/{}", drafts_branch)
-        } else {
-            "main".to_string()
-        };
-
-        let create_result = Command::new("git")
-            .current_dir(&repo_path)
-            .args(["checkout", "-b", &drafts_branch, &create_from])
-            .output()
-            .map_err(|e| format!("Failed to create drafts branch: {}", e))?;
-
-        if !create_result.status.success() {
-            // If that fails, try creating from HEAD
-            let create_from_head = Command::new("git")
-                .current_dir(&repo_path)
-                .args(["checkout", "-b", &drafts_branch])
-                .output()
-                .map_err(|e| format!("Failed to create drafts branch: {}", e))?;
-
-            if !create_from_head.status.success() {
-                if stashed {
-                    let _ = Command::new("git")
-                        .current_dir(&repo_path)
-                        .args(["stash", "pop"])
-                        .output();
-                }
-                return Err(format!(
-                    "Failed to create drafts branch: {}",
-                    String::from_utf8_lossy(&create_from_head.stderr)
-                ));
-            }
-        }
-    } else {
-        // Switch to drafts branch
-        let checkout_result = Command::new("git")
-            .current_dir(&repo_path)
-            .args(["checkout", &drafts_branch])
-            .output()
-            .map_err(|e| format!("Failed to checkout drafts branch: {}", e))?;
-
-        if !checkout_result.status.success() {
-            if stashed {
-                let _ = Command::new("git")
-                    .current_dir(&repo_path)
-                    .args(["stash", "pop"])
-                    .output();
-            }
-            return Err(format!(
-                "Failed to checkout drafts branch: {}",
-                String::from_utf8_lossy(&checkout_result.stderr)
-            ));
-        }
-
-        // Pull latest changes
-        let _ = Command::new("git")
-            .current_dir(&repo_path)
-            .args(["pull", "origin", &drafts_branch])
-            .output();
-    }
+    let layout = repo_layout()?;
+    // Drafts keep plain Markdown image links, so no responsive variants
+    let image_options = image_options()?.without_variants();
+    let drafts_branch = layout.drafts_branch(&args.slug);
+
+    // Work in a temporary worktree so the user's checkout is never touched
+    let remote_branch = layout.remote_branch(&drafts_branch);
+    let git = git_backend()?;
+    let worktree = Worktree::open(
+        git.as_ref(),
+        repo_path,
+        &layout.remote,
+        &drafts_branch,
+        &[&drafts_branch, &remote_branch, &layout.main_branch],
+    )?;
 
     // Create drafts directory structure
-    let drafts_dir = repo_path.join("drafts");
-    let images_dir = repo_path.join("drafts").join("images");
-    fs::create_dir_all(&drafts_dir).map_err(|e| format!("Failed to create drafts dir: {}", e))?;
-    fs::create_dir_all(&images_dir).map_err(|e| format!("Failed to create images dir: {}", e))?;
+    let drafts_dir = RepoLayout::dir(worktree.path(), &layout.drafts_dir);
+    let images_dir = RepoLayout::dir(worktree.path(), &layout.drafts_images_dir);
+    fs::create_dir_all(&drafts_dir).map_err(|e| AppError::io("Failed to create drafts dir", e))?;
+    fs::create_dir_all(&images_dir).map_err(|e| AppError::io("Failed to create images dir", e))?;
 
     // Handle cover image if present
+    let mut warnings = Vec::new();
     let cover_path = if !args.cover.is_empty() && args.cover.starts_with("data:image") {
-        match save_base64_image(&args.cover, &images_dir, &args.slug) {
-            Ok(filename) => format!("/drafts/images/{}", filename),
+        let name_prefix = layout.image_name_prefix(&args.slug);
+        match images::prepare_data_url_image(&args.cover, name_prefix, &image_options)
+            .and_then(|image| images::write_image(&images_dir, &image).map(|_| image.filename))
+        {
+            Ok(filename) => layout.drafts_image_url(&filename),
             Err(e) => {
                 eprintln!("Failed to save cover image: {}", e);
+                warnings.push(format!("Cover image was dropped: {}", e));
                 String::new()
             }
         }
@@ -1073,207 +748,314 @@ async fn sync_to_drafts(args: SyncDraftArgs) -> Result<SyncResult, String> {
         String::new()
     };
 
-    let client = Client::builder()
-        .timeout(std::time::Duration::from_secs(20))
-        .build()
-        .map_err(|e| format!("Failed to create HTTP client: {}", e))?;
-
-    let content = replace_inline_images(
+    let downloader = ImageDownloader::new(&image_options)?;
+    let inline = images::prepare_inline_images(
         &args.content,
-        &images_dir,
-        None,
-        Some(&client),
-        &args.slug,
-        "/drafts/images",
+        Some(&downloader),
+        layout.image_name_prefix(&args.slug),
+        &layout.drafts_images_url,
+        &image_options,
+        false,
     )
     .await?;
+    warnings.extend(inline.failures);
+    for image in &inline.images {
+        if let Err(e) = images::write_image(&images_dir, &image.image) {
+            eprintln!("Failed to save inline image: {}", e);
+            warnings.push(format!("Image {} was not saved: {}", image.image.filename, e));
+        }
+    }
+    let content = inline.content;
 
     // Generate frontmatter
-    let tags_yaml = args
-        .tags
-        .iter()
-        .map(|t| format!("\"{}\"", t.replace('"', "\\\"")))
-        .collect::<Vec<_>>()
-        .join(", ");
-
-    let cover_position = args.cover_position.unwrap_or(50.0);
     let updated_at = args.updated_at.unwrap_or_else(|| args.date.clone());
-    let frontmatter = format!(
-        r#"---
-title: "{}"
-date: "{}"
-tags: [{}]
-description: "{}"
-cover: "{}"
-cover_position: {}
-last_updated: "{}"
-draft_id: "{}"
----
-
-"#,
-        args.title.replace('"', "\\\""),
-        args.date,
-        tags_yaml,
-        args.description.replace('"', "\\\""),
-        cover_path,
-        cover_position,
-        updated_at,
-        args.draft_id
-    );
+    let frontmatter = DraftFrontmatter {
+        title: &args.title,
+        date: &args.date,
+        tags: &args.tags,
+        description: &args.description,
+        cover: &cover_path,
+        cover_position: args.cover_position.unwrap_or(50.0),
+        cover_alt: &args.cover_alt,
+        last_updated: &updated_at,
+        draft_id: &args.draft_id,
+        extra: &args.extra_fields,
+    }
+    .render()?;
 
     // Write draft file
-    let draft_path = drafts_dir.join(format!("{}.md", args.slug));
+    let draft_path = RepoLayout::dir(worktree.path(), &layout.draft_path(&args.slug));
     let full_content = format!("{}{}", frontmatter, content);
 
     fs::write(&draft_path, &full_content)
-        .map_err(|e| format!("Failed to write draft: {}", e))?;
+        .map_err(|e| AppError::io("Failed to write draft", e))?;
 
     // Git add
-    let add_result = Command::new("git")
-        .current_dir(&repo_path)
-        .args(["add", "drafts/"])
-        .output()
-        .map_err(|e| format!("Failed to run git add: {}", e))?;
-
-    if !add_result.status.success() {
-        // Switch back to original branch before returning error
-        let _ = Command::new("git")
-            .current_dir(&repo_path)
-            .args(["checkout", &original_branch])
-            .output();
-        if stashed {
-            let _ = Command::new("git")
-                .current_dir(&repo_path)
-                .args(["stash", "pop"])
-                .output();
-        }
-        return Err(format!(
-            "git add failed: {}",
-            String::from_utf8_lossy(&add_result.stderr)
-        ));
-    }
+    worktree.add(&[&layout.drafts_dir, &layout.drafts_images_dir])?;
 
     // Git commit
     let commit_msg = format!("Sync draft: {}", args.title);
-    let commit_result = Command::new("git")
-        .current_dir(&repo_path)
-        .args(["commit", "-m", &commit_msg])
-        .output()
-        .map_err(|e| format!("Failed to run git commit: {}", e))?;
-
-    if !commit_result.status.success() {
-        let stderr = String::from_utf8_lossy(&commit_result.stderr);
-        if !stderr.contains("nothing to commit") {
-            let _ = Command::new("git")
-                .current_dir(&repo_path)
-                .args(["checkout", &original_branch])
-                .output();
-            if stashed {
-                let _ = Command::new("git")
-                    .current_dir(&repo_path)
-                    .args(["stash", "pop"])
-                    .output();
-            }
-            return Err(format!("git commit failed: {}", stderr));
-        }
-    }
-
-    // Git push (with -u to set upstream if needed)
-    let push_result = Command::new("git")
-        .current_dir(&repo_path)
-        .args(["push", "-u", "origin", &drafts_branch])
-        .output()
-        .map_err(|e| format!("Failed to run git push: {}", e))?;
-
-    if !push_result.status.success() {
-        let _ = Command::new("git")
-            .current_dir(&repo_path)
-            .args(["checkout", &original_branch])
-            .output();
-        if stashed {
-            let _ = Command::new("git")
-                .current_dir(&repo_path)
-                .args(["stash", "pop"])
-                .output();
-        }
-        return Err(format!(
-            "git push failed: {}",
-            String::from_utf8_lossy(&push_result.stderr)
-        ));
-    }
+    worktree.commit(&commit_msg)?;
 
-    // Switch back to original branch
-    let _ = Command::new("git")
-        .current_dir(&repo_path)
-        .args(["checkout", &original_branch])
-        .output();
-
-    if stashed {
-        let pop_result = Command::new("git")
-            .current_dir(&repo_path)
-            .args(["stash", "pop"])
-            .output()
-            .map_err(|e| format!("Failed to pop stash: {}", e))?;
-        if !pop_result.status.success() {
-            return Err(format!(
-                "git stash pop failed: {}",
-                String::from_utf8_lossy(&pop_result.stderr)
-            ));
-        }
-    }
+    // Git push
+    worktree.push()?;
 
     Ok(SyncResult {
         success: true,
         message: format!("Draft '{}' synced to drafts branch", args.title),
         branch: drafts_branch.to_string(),
+        warnings,
     })
 }
 
+/// Fetch every drafts branch and bring remote edits into the local database.
+/// When both sides changed, local wins and the remote copy is saved as a new draft.
+#[tauri::command]
+async fn pull_drafts(db: State<'_, Database>, repo_path: String) -> AppResult<PullResult> {
+    let repo_path = Path::new(&repo_path);
+    if !repo_path.exists() {
+        return Err(AppError::repo_not_found(repo_path.display()));
+    }
+
+    pull::pull_drafts(&db, repo_path, git_backend()?.as_ref(), &repo_layout()?)
+}
+
+/// List images in the repo's image directories that nothing refers to
+#[tauri::command]
+async fn scan_orphan_images(repo_path: String) -> AppResult<OrphanScan> {
+    let repo_path = Path::new(&repo_path);
+    if !repo_path.exists() {
+        return Err(AppError::repo_not_found(repo_path.display()));
+    }
+
+    let settings = get_settings()?;
+    let git = git::backend(settings.git_backend);
+    orphans::scan_orphan_images(git.as_ref(), repo_path, &settings.layout, &settings.publish)
+}
+
+/// Delete images from a previous scan in one commit, if they are still unused
+#[tauri::command]
+async fn delete_orphan_images(repo_path: String, paths: Vec<String>) -> AppResult<OrphanCleanup> {
+    let repo_path = Path::new(&repo_path);
+    if !repo_path.exists() {
+        return Err(AppError::repo_not_found(repo_path.display()));
+    }
+
+    let settings = get_settings()?;
+    let git = git::backend(settings.git_backend);
+    orphans::remove_orphan_images(
+        git.as_ref(),
+        repo_path,
+        &settings.layout,
+        &settings.publish,
+        &paths,
+    )
+}
+
 // ============================================================================
 // Draft CRUD Commands
 // ============================================================================
 
 /// Save a draft to SQLite (create or update)
 #[tauri::command]
-fn save_draft_to_db(db: State<Database>, draft: Draft) -> Result<Draft, String> {
+fn save_draft_to_db(db: State<Database>, draft: Draft) -> AppResult<Draft> {
     db.save_draft(draft)
 }
 
 /// Get a single draft by ID
 #[tauri::command]
-fn get_draft_from_db(db: State<Database>, id: String) -> Result<Option<Draft>, String> {
+fn get_draft_from_db(db: State<Database>, id: String) -> AppResult<Option<Draft>> {
     db.get_draft(&id)
 }
 
 /// List all drafts (summary only)
 #[tauri::command]
-fn list_drafts(db: State<Database>) -> Result<Vec<DraftSummary>, String> {
+fn list_drafts(db: State<Database>) -> AppResult<Vec<DraftSummary>> {
     db.list_drafts()
 }
 
-/// Delete a draft by ID
+/// Full-text search over drafts, optionally filtered by tag and status
 #[tauri::command]
-fn delete_draft(db: State<Database>, id: String) -> Result<bool, String> {
+fn search_drafts(
+    db: State<Database>,
+    query: String,
+    tag: Option<String>,
+    status: Option<String>,
+) -> AppResult<Vec<DraftSearchResult>> {
+    db.search_drafts(&query, tag.as_deref(), status.map(DraftStatus::from))
+}
+
+/// Move a draft to the trash
+#[tauri::command]
+fn delete_draft(db: State<Database>, id: String) -> AppResult<bool> {
     db.delete_draft(&id)
 }
 
+/// List drafts in the trash, purging any past the retention period first
+#[tauri::command]
+fn list_trash(db: State<Database>) -> AppResult<Vec<TrashedDraft>> {
+    let settings = get_settings()?;
+    db.purge_expired_trash(settings.trash_retention_days)?;
+    db.list_trash()
+}
+
+/// Restore a draft from the trash
+#[tauri::command]
+fn restore_draft(db: State<Database>, id: String) -> AppResult<bool> {
+    db.restore_draft(&id)
+}
+
+/// Permanently delete a draft from the trash
+#[tauri::command]
+fn purge_draft(db: State<Database>, id: String) -> AppResult<bool> {
+    db.purge_draft(&id)
+}
+
 /// Get the most recently updated draft
 #[tauri::command]
-fn get_latest_draft(db: State<Database>) -> Result<Option<Draft>, String> {
+fn get_latest_draft(db: State<Database>) -> AppResult<Option<Draft>> {
     db.get_latest_draft()
 }
 
 /// Update draft status (for sync/publish tracking)
 #[tauri::command]
-fn update_draft_status(db: State<Database>, id: String, status: String) -> Result<(), String> {
+fn update_draft_status(db: State<Database>, id: String, status: String) -> AppResult<()> {
     let draft_status = DraftStatus::from(status);
     db.update_status(&id, draft_status)
 }
 
+// ============================================================================
+// Tag Commands
+// ============================================================================
+
+/// Tag autocomplete ranked by usage in local drafts and published articles
+#[tauri::command]
+fn suggest_tags(
+    db: State<Database>,
+    prefix: String,
+    repo_path: Option<String>,
+    limit: Option<usize>,
+) -> AppResult<Vec<TagSuggestion>> {
+    // Without readable settings the articles can't be found, but the drafts
+    // still give suggestions
+    let settings = get_settings();
+    let repo_path = repo_path
+        .as_deref()
+        .filter(|p| !p.is_empty() && settings.is_ok())
+        .map(Path::new);
+    let settings = settings.unwrap_or_default();
+    Ok(tags::suggest_tags(
+        db.tag_usage()?,
+        repo_path,
+        &settings.layout,
+        &settings.site_generator.profile(),
+        &prefix,
+        limit.unwrap_or(10),
+    ))
+}
+
+/// Rewrite the published articles before the drafts, so a failed commit or
+/// push leaves the local tags as they were
+fn update_tags(
+    repo_path: Option<&str>,
+    sources: &[String],
+    target: &str,
+    update_drafts: impl FnOnce() -> AppResult<usize>,
+) -> AppResult<TagUpdateResult> {
+    let (articles_updated, message) = match repo_path.filter(|p| !p.is_empty()) {
+        Some(repo_path) => {
+            let repo_path = Path::new(repo_path);
+            if !repo_path.exists() {
+                return Err(AppError::repo_not_found(repo_path.display()));
+            }
+            let settings = get_settings()?;
+            let git = git::backend(settings.git_backend);
+            match tags::rewrite_article_tags(
+                git.as_ref(),
+                repo_path,
+                &settings.layout,
+                &settings.site_generator.profile(),
+                &settings.publish,
+                sources,
+                target,
+            )? {
+                Some((articles, message)) => (articles, Some(message)),
+                None => (Vec::new(), None),
+            }
+        }
+        None => (Vec::new(), None),
+    };
+    Ok(TagUpdateResult {
+        drafts_updated: update_drafts()?,
+        articles_updated,
+        message,
+    })
+}
+
+/// Rename a tag across local drafts and published articles
+#[tauri::command]
+async fn rename_tag(
+    db: State<'_, Database>,
+    from: String,
+    to: String,
+    repo_path: Option<String>,
+) -> AppResult<TagUpdateResult> {
+    update_tags(repo_path.as_deref(), std::slice::from_ref(&from), &to, || {
+        db.rename_tag(&from, &to)
+    })
+}
+
+/// Merge several tags into one across local drafts and published articles
+#[tauri::command]
+async fn merge_tags(
+    db: State<'_, Database>,
+    sources: Vec<String>,
+    target: String,
+    repo_path: Option<String>,
+) -> AppResult<TagUpdateResult> {
+    update_tags(repo_path.as_deref(), &sources, &target, || {
+        db.merge_tags(&sources, &target)
+    })
+}
+
+// ============================================================================
+// Version History Commands
+// ============================================================================
+
+/// List the saved versions of a draft (newest first)
+#[tauri::command]
+fn list_versions(db: State<Database>, draft_id: String) -> AppResult<Vec<VersionSummary>> {
+    db.list_versions(&draft_id)
+}
+
+/// Get a single version by ID
+#[tauri::command]
+fn get_version(db: State<Database>, id: String) -> AppResult<Option<Version>> {
+    db.get_version(&id)
+}
+
+/// Diff a version against the draft's current content
+#[tauri::command]
+fn diff_version(db: State<Database>, id: String) -> AppResult<VersionDiff> {
+    db.diff_version(&id)
+}
+
+/// Restore a version (current content is kept as a new version)
+#[tauri::command]
+fn restore_version(db: State<Database>, id: String) -> AppResult<Draft> {
+    db.restore_version(&id)
+}
+
 #[cfg_attr(mobile, tauri::mobile_entry_point)]
 pub fn run() {
     // Initialize database
     let db = Database::new().expect("Failed to initialize database");
+    let settings = get_settings().unwrap_or_default();
+    db.set_version_policy(VersionPolicy::from(&settings))
+        .expect("Failed to apply version policy");
+    if let Err(e) = db.purge_expired_trash(settings.trash_retention_days) {
+        eprintln!("Failed to purge expired trash: {}", e);
+    }
 
     tauri::Builder::default()
         .plugin(tauri_plugin_opener::init())
@@ -1281,10 +1063,19 @@ pub fn run() {
         .manage(db)
         .invoke_handler(tauri::generate_handler![
             // Publish commands
+            plan_publish,
+            validate_draft,
+            generate_slug,
+            rename_slug,
             publish_draft,
+            unpublish_draft,
             sync_to_drafts,
+            pull_drafts,
             get_repo_status,
             sync_public_images,
+            scan_orphan_images,
+            delete_orphan_images,
+            import_articles,
             // Settings commands
             get_settings,
             save_settings,
@@ -1293,9 +1084,22 @@ pub fn run() {
             save_draft_to_db,
             get_draft_from_db,
             list_drafts,
+            search_drafts,
             delete_draft,
+            list_trash,
+            restore_draft,
+            purge_draft,
             get_latest_draft,
             update_draft_status,
+            // Tag commands
+            suggest_tags,
+            rename_tag,
+            merge_tags,
+            // Version history commands
+            list_versions,
+            get_version,
+            diff_version,
+            restore_version,
         ])
         .run(tauri::generate_context!())
         .expect("error while running tauri application");
@@ -0,0 +1,444 @@
+use regex::Regex;
+use serde_json::{json, Value};
+
+use crate::error::{AppError, AppResult};
+
+// Lexical text format bits
+const FORMAT_BOLD: u32 = 1;
+const FORMAT_ITALIC: u32 = 2;
+const FORMAT_STRIKETHROUGH: u32 = 4;
+const FORMAT_CODE: u32 = 16;
+
+/// Converts Markdown back into a Lexical editor state.
+///
+/// This is the reverse of the frontend's `lexicalToMarkdown` for the blocks the
+/// editor produces (headings, paragraphs, quotes, lists, code, rules, images,
+/// links and basic inline formatting). Anything else is kept as plain text so no
+/// content is lost. `resolve_image` maps an image `src` to what the editor should
+/// load (e.g. a repo path to a data URL).
+pub struct MarkdownImporter<'a> {
+    inline_re: Regex,
+    /// `src` and `alt` of the `<img>` tags publish writes for HTML profiles
+    attribute_re: Regex,
+    resolve_image: &'a dyn Fn(&str) -> String,
+    text: String,
+}
+
+impl<'a> MarkdownImporter<'a> {
+    pub fn new(resolve_image: &'a dyn Fn(&str) -> String) -> AppResult<Self> {
+        let inline_re = Regex::new(
+            r#"!\[([^\]]*)\]\(([^)\s]+)(?:\s+"[^"]*")?\)|\[([^\]]+)\]\(([^)\s]+)(?:\s+"[^"]*")?\)|`([^`]+)`|\*\*([^*]+)\*\*|__([^_]+)__|~~([^~]+)~~|\*([^*\s][^*]*)\*|\b_([^_\s][^_]*)_\b|(?i)<img\b([^>]*)>"#,
+        )
+        .map_err(|e| AppError::invalid(format!("Failed to build markdown regex: {}", e)))?;
+        let attribute_re = Regex::new(r#"(?i)\b(src|alt)\s*=\s*(?:"([^"]*)"|'([^']*)')"#)
+            .map_err(|e| AppError::invalid(format!("Failed to build markdown regex: {}", e)))?;
+
+        Ok(MarkdownImporter {
+            inline_re,
+            attribute_re,
+            resolve_image,
+            text: String::new(),
+        })
+    }
+
+    /// Convert a Markdown body into `(lexical_json, plain_text)`
+    pub fn convert(mut self, markdown: &str) -> (String, String) {
+        let children = self.blocks(markdown);
+        let state = json!({
+            "root": {
+                "children": children,
+                "direction": null,
+                "format": "",
+                "indent": 0,
+                "type": "root",
+                "version": 1
+            }
+        });
+
+        (state.to_string(), self.text.trim().to_string())
+    }
+
+    fn blocks(&mut self, markdown: &str) -> Vec<Value> {
+        let lines: Vec<&str> = markdown.lines().collect();
+        let mut blocks = Vec::new();
+        let mut i = 0;
+
+        while i < lines.len() {
+            let line = lines[i];
+            let trimmed = line.trim();
+
+            if trimmed.is_empty() {
+                i += 1;
+                continue;
+            }
+
+            // Fenced code block
+            if let Some(language) = trimmed.strip_prefix("```") {
+                let mut code = Vec::new();
+                i += 1;
+                while i < lines.len() && !lines[i].trim_start().starts_with("```") {
+                    code.push(lines[i]);
+                    i += 1;
+                }
+                i += 1;
+                blocks.push(self.code_block(language.trim(), &code));
+                continue;
+            }
+
+            // Heading
+            let hashes = trimmed.chars().take_while(|c| *c == '#').count();
+            if (1..=6).contains(&hashes) && trimmed[hashes..].starts_with(' ') {
+                let children = self.inline(trimmed[hashes..].trim());
+                blocks.push(element("heading", children, &[("tag", json!(format!("h{}", hashes)))]));
+                self.text.push('\n');
+                i += 1;
+                continue;
+            }
+
+            // Horizontal rule
+            if matches!(trimmed, "---" | "***" | "___") {
+                blocks.push(json!({ "type": "horizontalrule", "version": 1 }));
+                i += 1;
+                continue;
+            }
+
+            // Blockquote
+            if trimmed.starts_with('>') {
+                let mut quoted = Vec::new();
+                while i < lines.len() && lines[i].trim_start().starts_with('>') {
+                    let content = lines[i].trim_start().trim_start_matches('>');
+                    quoted.push(content.strip_prefix(' ').unwrap_or(content));
+                    i += 1;
+                }
+                let children = self.inline_lines(&quoted);
+                blocks.push(element("quote", children, &[]));
+                continue;
+            }
+
+            // List
+            if let Some((list_type, _, _)) = list_item(trimmed) {
+                let mut items = Vec::new();
+                while i < lines.len() {
+                    match list_item(lines[i].trim()) {
+                        Some((kind, checked, content)) if kind == list_type => {
+                            let children = self.inline(content);
+                            let mut attrs = vec![("value", json!(items.len() + 1))];
+                            if let Some(checked) = checked {
+                                attrs.push(("checked", json!(checked)));
+                            }
+                            items.push(element("listitem", children, &attrs));
+                            self.text.push('\n');
+                            i += 1;
+                        }
+                        _ => break,
+                    }
+                }
+                let tag = if list_type == "number" { "ol" } else { "ul" };
+                blocks.push(element(
+                    "list",
+                    items,
+                    &[
+                        ("listType", json!(list_type)),
+                        ("start", json!(1)),
+                        ("tag", json!(tag)),
+                    ],
+                ));
+                continue;
+            }
+
+            // Paragraph: consecutive non-blank lines that don't start another block
+            let mut paragraph = Vec::new();
+            while i < lines.len() {
+                let next = lines[i].trim();
+                if next.is_empty()
+                    || (!paragraph.is_empty() && starts_block(next))
+                {
+                    break;
+                }
+                paragraph.push(lines[i]);
+                i += 1;
+            }
+            let children = self.inline_lines(&paragraph);
+            blocks.push(element("paragraph", children, &[]));
+        }
+
+        blocks
+    }
+
+    fn code_block(&mut self, language: &str, lines: &[&str]) -> Value {
+        let mut children = Vec::new();
+        for (index, line) in lines.iter().enumerate() {
+            if index > 0 {
+                children.push(json!({ "type": "linebreak", "version": 1 }));
+            }
+            if !line.is_empty() {
+                children.push(text_node(line, 0));
+            }
+            self.text.push_str(line);
+            self.text.push('\n');
+        }
+
+        let mut attrs = vec![];
+        if !language.is_empty() {
+            attrs.push(("language", json!(language)));
+        }
+        element("code", children, &attrs)
+    }
+
+    /// Inline content spread over several source lines, joined with line breaks
+    fn inline_lines(&mut self, lines: &[&str]) -> Vec<Value> {
+        let mut children = Vec::new();
+        for (index, line) in lines.iter().enumerate() {
+            if index > 0 {
+                children.push(json!({ "type": "linebreak", "version": 1 }));
+                self.text.push(' ');
+            }
+            children.extend(self.inline(line.trim()));
+        }
+        self.text.push('\n');
+        children
+    }
+
+    fn inline(&mut self, source: &str) -> Vec<Value> {
+        let mut nodes = Vec::new();
+        let mut last = 0;
+
+        let captures: Vec<_> = self.inline_re.captures_iter(source).collect();
+        for caps in captures {
+            let full = match caps.get(0) {
+                Some(full) => full,
+                None => continue,
+            };
+            if full.start() > last {
+                nodes.push(self.plain(&source[last..full.start()], 0));
+            }
+
+            if let (Some(alt), Some(src)) = (caps.get(1), caps.get(2)) {
+                nodes.push(image_node(alt.as_str(), &(self.resolve_image)(src.as_str())));
+            } else if let (Some(label), Some(url)) = (caps.get(3), caps.get(4)) {
+                let children = vec![self.plain(label.as_str(), 0)];
+                nodes.push(element(
+                    "link",
+                    children,
+                    &[
+                        ("rel", json!("noreferrer")),
+                        ("target", Value::Null),
+                        ("title", Value::Null),
+                        ("url", json!(url.as_str())),
+                    ],
+                ));
+            } else if let Some(code) = caps.get(5) {
+                nodes.push(self.plain(code.as_str(), FORMAT_CODE));
+            } else if let Some(bold) = caps.get(6).or_else(|| caps.get(7)) {
+                nodes.push(self.plain(bold.as_str(), FORMAT_BOLD));
+            } else if let Some(strike) = caps.get(8) {
+                nodes.push(self.plain(strike.as_str(), FORMAT_STRIKETHROUGH));
+            } else if let Some(italic) = caps.get(9).or_else(|| caps.get(10)) {
+                nodes.push(self.plain(italic.as_str(), FORMAT_ITALIC));
+            } else if let Some(attributes) = caps.get(11) {
+                let mut src = None;
+                let mut alt = String::new();
+                for attr in self.attribute_re.captures_iter(attributes.as_str()) {
+                    let value = attr.get(2).or_else(|| attr.get(3)).map_or("", |m| m.as_str());
+                    let value = unescape_attribute(value);
+                    if attr[1].eq_ignore_ascii_case("src") {
+                        src = Some(value);
+                    } else {
+                        alt = value;
+                    }
+                }
+                match src {
+                    Some(src) => nodes.push(image_node(&alt, &(self.resolve_image)(&src))),
+                    // Not an image we can show; keep the tag as text
+                    None => nodes.push(self.plain(full.as_str(), 0)),
+                }
+            }
+
+            last = full.end();
+        }
+
+        if last < source.len() {
+            nodes.push(self.plain(&source[last..], 0));
+        }
+
+        nodes
+    }
+
+    fn plain(&mut self, text: &str, format: u32) -> Value {
+        self.text.push_str(text);
+        text_node(text, format)
+    }
+}
+
+/// Classify a list line as `(listType, checked, content)`
+fn list_item(line: &str) -> Option<(&'static str, Option<bool>, &str)> {
+    for marker in ["- ", "* ", "+ "] {
+        if let Some(rest) = line.strip_prefix(marker) {
+            if let Some(rest) = rest.strip_prefix("[ ] ") {
+                return Some(("check", Some(false), rest));
+            }
+            if let Some(rest) = rest
+                .strip_prefix("[x] ")
+                .or_else(|| rest.strip_prefix("[X] "))
+            {
+                return Some(("check", Some(true), rest));
+            }
+            return Some(("bullet", None, rest));
+        }
+    }
+
+    let digits = line.chars().take_while(|c| c.is_ascii_digit()).count();
+    if digits > 0 {
+        if let Some(rest) = line[digits..].strip_prefix(". ") {
+            return Some(("number", None, rest));
+        }
+    }
+
+    None
+}
+
+fn starts_block(line: &str) -> bool {
+    line.starts_with('#')
+        || line.starts_with('>')
+        || line.starts_with("```")
+        || matches!(line, "---" | "***" | "___")
+        || list_item(line).is_some()
+}
+
+/// Undo the escaping publish applies to `<img>` attributes
+fn unescape_attribute(value: &str) -> String {
+    value
+        .replace("&quot;", "\"")
+        .replace("&#39;", "'")
+        .replace("&lt;", "<")
+        .replace("&gt;", ">")
+        .replace("&amp;", "&")
+}
+
+fn element(kind: &str, children: Vec<Value>, attrs: &[(&str, Value)]) -> Value {
+    let mut node = json!({
+        "children": children,
+        "direction": null,
+        "format": "",
+        "indent": 0,
+        "type": kind,
+        "version": 1
+    });
+    if let Some(object) = node.as_object_mut() {
+        for (key, value) in attrs {
+            object.insert(key.to_string(), value.clone());
+        }
+    }
+    node
+}
+
+fn text_node(text: &str, format: u32) -> Value {
+    json!({
+        "detail": 0,
+        "format": format,
+        "mode": "normal",
+        "style": "",
+        "text": text,
+        "type": "text",
+        "version": 1
+    })
+}
+
+fn image_node(alt: &str, src: &str) -> Value {
+    json!({
+        "altText": alt,
+        "caption": {
+            "editorState": {
+                "root": {
+                    "children": [],
+                    "direction": null,
+                    "format": "",
+                    "indent": 0,
+                    "type": "root",
+                    "version": 1
+                }
+            }
+        },
+        "height": 0,
+        "maxWidth": 800,
+        "showCaption": false,
+        "src": src,
+        "type": "image",
+        "version": 1,
+        "width": 0
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::images::{prepare_inline_images, ImageOptions};
+    use base64::{engine::general_purpose::STANDARD, Engine};
+
+    fn images(state: &str) -> Vec<(String, String)> {
+        let state: Value = serde_json::from_str(state).unwrap();
+        let mut found = Vec::new();
+        let mut stack = vec![&state["root"]];
+        while let Some(node) = stack.pop() {
+            if node["type"] == "image" {
+                found.push((
+                    node["src"].as_str().unwrap().to_string(),
+                    node["altText"].as_str().unwrap().to_string(),
+                ));
+            }
+            if let Some(children) = node["children"].as_array() {
+                stack.extend(children.iter().rev());
+            }
+        }
+        found
+    }
+
+    #[test]
+    fn html_images_read_back_as_images() {
+        let mut png = Vec::new();
+        image::RgbImage::new(1200, 800)
+            .write_to(&mut std::io::Cursor::new(&mut png), image::ImageFormat::Png)
+            .unwrap();
+        let markdown = format!(
+            "Intro\n\n![A \"quoted\" <cat> & dog](data:image/png;base64,{})\n",
+            STANDARD.encode(&png)
+        );
+        let published = futures::executor::block_on(prepare_inline_images(
+            &markdown,
+            None,
+            "hello",
+            "/images",
+            &ImageOptions::default(),
+            true,
+        ))
+        .unwrap();
+        assert!(published.content.contains("<img src=\"/images/"), "{}", published.content);
+        assert!(published.content.contains("srcset="), "{}", published.content);
+
+        let resolve = |src: &str| format!("resolved:{}", src);
+        let (state, text) = MarkdownImporter::new(&resolve)
+            .unwrap()
+            .convert(&published.content);
+        let filename = &published.images[0].image.filename;
+        assert_eq!(
+            images(&state),
+            [(
+                format!("resolved:/images/{}", filename),
+                "A \"quoted\" <cat> & dog".to_string()
+            )]
+        );
+        assert_eq!(text, "Intro");
+    }
+
+    #[test]
+    fn markdown_and_html_images_match() {
+        let resolve = |src: &str| src.to_string();
+        let markdown = "![Alt](/images/a.webp)\n\n\
+                        <img alt='Alt' src=\"/images/a.webp\" width=\"4\" />\n\n\
+                        <img class=\"x\">";
+        let (state, text) = MarkdownImporter::new(&resolve).unwrap().convert(markdown);
+        let expected = ("/images/a.webp".to_string(), "Alt".to_string());
+        assert_eq!(images(&state), [expected.clone(), expected]);
+        assert_eq!(text, "<img class=\"x\">");
+    }
+}
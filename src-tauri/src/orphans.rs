@@ -0,0 +1,304 @@
+use chrono::Utc;
+use regex::{Regex, RegexBuilder};
+use serde::Serialize;
+use std::collections::HashSet;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use crate::error::{AppError, AppResult};
+use crate::git::{CommitOutcome, GitBackend, Worktree};
+use crate::layout::RepoLayout;
+use crate::publish::{self, PublishMode, PublishStrategy, PullRequest};
+
+const IMAGE_EXTENSIONS: &[&str] = &[
+    "png", "jpg", "jpeg", "gif", "webp", "avif", "svg", "heic", "bmp", "tiff", "ico",
+];
+
+/// Directories that are never searched for references, besides hidden ones
+const SKIPPED_DIRS: &[&str] = &["node_modules", "target"];
+
+/// An image file nothing in the repo refers to
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct OrphanImage {
+    pub path: String, // relative to the repo root
+    pub size_bytes: u64,
+}
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct OrphanScan {
+    /// The branch that was scanned
+    pub branch: String,
+    pub images: Vec<OrphanImage>,
+    pub total_bytes: u64,
+    /// Files searched for references
+    pub files_scanned: usize,
+}
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct OrphanCleanup {
+    pub deleted: Vec<String>,
+    pub freed_bytes: u64,
+    pub message: String,
+}
+
+/// Find unreferenced files in the image directories on the publish base branch.
+/// An image counts as referenced when its file name appears in any text file
+/// outside the image directories: Markdown bodies, frontmatter such as
+/// `cover`, and the site's own templates and components.
+pub fn scan_orphan_images(
+    git: &dyn GitBackend,
+    repo_path: &Path,
+    layout: &RepoLayout,
+    strategy: &PublishStrategy,
+) -> AppResult<OrphanScan> {
+    let base = strategy.base_branch(layout);
+    let remote_base = layout.remote_branch(base);
+    let worktree = Worktree::open(git, repo_path, &layout.remote, base, &[&remote_base, base])?;
+
+    let (images, files_scanned) = find_orphans(worktree.path(), layout)?;
+    Ok(OrphanScan {
+        branch: base.to_string(),
+        total_bytes: images.iter().map(|image| image.size_bytes).sum(),
+        images,
+        files_scanned,
+    })
+}
+
+/// Delete the images in `paths` that are still unreferenced and commit the
+/// removal on its own, following the publish strategy: pushed straight to
+/// the base branch, or through a pull request.
+pub fn remove_orphan_images(
+    git: &dyn GitBackend,
+    repo_path: &Path,
+    layout: &RepoLayout,
+    strategy: &PublishStrategy,
+    paths: &[String],
+) -> AppResult<OrphanCleanup> {
+    let base = strategy.base_branch(layout);
+    let remote_base = layout.remote_branch(base);
+    let branch = match strategy.mode {
+        PublishMode::DirectPush => base.to_string(),
+        _ => format!(
+            "nibandh/remove-unused-images-{}",
+            Utc::now().format("%Y%m%d%H%M%S")
+        ),
+    };
+    let worktree = Worktree::open(
+        git,
+        repo_path,
+        &layout.remote,
+        &branch,
+        &[&remote_base, base],
+    )?;
+
+    // Re-check on the latest base so a stale scan never deletes a used image
+    let (orphans, _) = find_orphans(worktree.path(), layout)?;
+    let requested: HashSet<&str> = paths.iter().map(String::as_str).collect();
+    let mut deleted = Vec::new();
+    let mut freed_bytes = 0;
+    for orphan in orphans
+        .iter()
+        .filter(|o| requested.contains(o.path.as_str()))
+    {
+        fs::remove_file(RepoLayout::dir(worktree.path(), &orphan.path))
+            .map_err(|e| AppError::io(format!("Failed to delete {}", orphan.path), e))?;
+        deleted.push(orphan.path.clone());
+        freed_bytes += orphan.size_bytes;
+    }
+
+    let skipped = paths.len() - deleted.len();
+    let message = format!(
+        "Remove {} unused image{}",
+        deleted.len(),
+        plural(deleted.len())
+    );
+    let nothing_removed = || OrphanCleanup {
+        deleted: Vec::new(),
+        freed_bytes: 0,
+        message: "No unused images to remove".to_string(),
+    };
+    if deleted.is_empty() {
+        return Ok(nothing_removed());
+    }
+    let deleted_paths: Vec<&str> = deleted.iter().map(String::as_str).collect();
+    worktree.add(&deleted_paths)?;
+    if worktree.commit(&message)? == CommitOutcome::NothingToCommit {
+        return Ok(nothing_removed());
+    }
+    worktree.push()?;
+
+    let mut summary = if strategy.mode == PublishMode::DirectPush {
+        format!(
+            "Removed {} unused image{} from {}",
+            deleted.len(),
+            plural(deleted.len()),
+            base
+        )
+    } else {
+        let request = PullRequest {
+            title: message.clone(),
+            body: deleted
+                .iter()
+                .map(|path| format!("- `{}`", path))
+                .collect::<Vec<_>>()
+                .join("\n"),
+            reviewers: &strategy.reviewers,
+        };
+        let (url, _) = request.open(worktree.path(), &branch, base)?;
+        if strategy.mode == PublishMode::AutoMerge {
+            match publish::merge_pull_request(worktree.path(), &url, strategy.merge_method) {
+                Ok(()) => format!("{} (merged {})", message, url),
+                Err(e) => format!(
+                    "{}: PR {} could not be merged automatically: {}",
+                    message, url, e
+                ),
+            }
+        } else {
+            format!("{}: PR {} opened for review", message, url)
+        }
+    };
+    if skipped > 0 {
+        summary.push_str(&format!(
+            "; skipped {} that {} no longer unused",
+            skipped,
+            if skipped == 1 { "is" } else { "are" }
+        ));
+    }
+
+    Ok(OrphanCleanup {
+        deleted,
+        freed_bytes,
+        message: summary,
+    })
+}
+
+fn plural(count: usize) -> &'static str {
+    if count == 1 {
+        ""
+    } else {
+        "s"
+    }
+}
+
+/// Unreferenced images under `root`, and how many files were searched
+pub fn find_orphans(root: &Path, layout: &RepoLayout) -> AppResult<(Vec<OrphanImage>, usize)> {
+    let mut image_dirs: Vec<&str> = vec![&layout.images_dir, &layout.drafts_images_dir];
+    if !layout.public_images_dir.is_empty() {
+        image_dirs.push(&layout.public_images_dir);
+    }
+    image_dirs.sort();
+    image_dirs.dedup();
+
+    let mut images = Vec::new();
+    for dir in &image_dirs {
+        list_files(&RepoLayout::dir(root, dir), dir, &mut images)?;
+    }
+    images.retain(|image| is_image(file_name(&image.path)));
+    images.sort_by(|a, b| a.path.cmp(&b.path));
+    images.dedup_by(|a, b| a.path == b.path);
+    if images.is_empty() {
+        return Ok((images, 0));
+    }
+
+    // Names as they may be written: as-is and with spaces percent-encoded
+    let mut spellings: Vec<String> = images
+        .iter()
+        .flat_map(|image| {
+            let name = file_name(&image.path);
+            [name.to_string(), name.replace(' ', "%20")]
+        })
+        .collect();
+    // Longest first, so a name is never cut short by one it ends with
+    spellings.sort_by(|a, b| b.len().cmp(&a.len()).then_with(|| a.cmp(b)));
+    spellings.dedup();
+    let pattern = spellings
+        .iter()
+        .map(|name| regex::escape(name))
+        .collect::<Vec<_>>()
+        .join("|");
+    let names_re = RegexBuilder::new(&pattern)
+        .size_limit(64 << 20)
+        .build()
+        .map_err(|e| AppError::invalid("Failed to build image name pattern").with_details(e))?;
+
+    let skip: Vec<PathBuf> = image_dirs
+        .iter()
+        .map(|dir| RepoLayout::dir(root, dir))
+        .collect();
+    let mut referenced = HashSet::new();
+    let mut files_scanned = 0;
+    collect_references(root, &skip, &names_re, &mut referenced, &mut files_scanned)?;
+
+    images.retain(|image| {
+        let name = file_name(&image.path);
+        !referenced.contains(name) && !referenced.contains(&name.replace(' ', "%20"))
+    });
+    Ok((images, files_scanned))
+}
+
+/// Record which image names are mentioned in the text files under `dir`
+fn collect_references(
+    dir: &Path,
+    skip: &[PathBuf],
+    names_re: &Regex,
+    referenced: &mut HashSet<String>,
+    files_scanned: &mut usize,
+) -> AppResult<()> {
+    let entries =
+        fs::read_dir(dir).map_err(|e| AppError::io(format!("Failed to read {:?}", dir), e))?;
+    for entry in entries.flatten() {
+        let path = entry.path();
+        let name = entry.file_name().to_string_lossy().to_string();
+        if path.is_dir() {
+            if !name.starts_with('.')
+                && !SKIPPED_DIRS.contains(&name.as_str())
+                && !skip.contains(&path)
+            {
+                collect_references(&path, skip, names_re, referenced, files_scanned)?;
+            }
+        } else if !is_image(&name) {
+            if let Ok(bytes) = fs::read(&path) {
+                *files_scanned += 1;
+                let text = String::from_utf8_lossy(&bytes);
+                referenced.extend(names_re.find_iter(&text).map(|m| m.as_str().to_string()));
+            }
+        }
+    }
+    Ok(())
+}
+
+/// Files under `dir`, recursively, with paths starting with `rel`
+fn list_files(dir: &Path, rel: &str, files: &mut Vec<OrphanImage>) -> AppResult<()> {
+    if !dir.exists() {
+        return Ok(());
+    }
+    let entries =
+        fs::read_dir(dir).map_err(|e| AppError::io(format!("Failed to read {}", rel), e))?;
+    for entry in entries.flatten() {
+        let name = entry.file_name().to_string_lossy().to_string();
+        let rel_path = format!("{}/{}", rel.trim_end_matches('/'), name);
+        let path = entry.path();
+        if path.is_dir() {
+            list_files(&path, &rel_path, files)?;
+        } else if let Ok(meta) = entry.metadata() {
+            files.push(OrphanImage {
+                path: rel_path,
+                size_bytes: meta.len(),
+            });
+        }
+    }
+    Ok(())
+}
+
+fn file_name(path: &str) -> &str {
+    path.rsplit('/').next().unwrap_or(path)
+}
+
+fn is_image(name: &str) -> bool {
+    name.rsplit_once('.').is_some_and(|(_, extension)| {
+        IMAGE_EXTENSIONS.contains(&extension.to_ascii_lowercase().as_str())
+    })
+}
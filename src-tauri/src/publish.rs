@@ -0,0 +1,736 @@
+use serde::{Deserialize, Serialize};
+use std::fs;
+use std::path::Path;
+use std::process::{Command, Output};
+
+use crate::audit::{audit_article, AuditPolicy, Finding};
+use crate::database::ExtraFields;
+use crate::downloads::ImageDownloader;
+use crate::error::{AppError, AppResult, ErrorKind};
+use crate::frontmatter::parse_article;
+use crate::git::{self, GitBackend};
+use crate::images::{
+    copy_to_public_images, fetch_remote_image, prepare_data_url_image, prepare_image_bytes,
+    prepare_inline_images, write_image, ImageOptions, ImageOrigin, ImageReport, InlineImage,
+    PreparedImage,
+};
+use crate::layout::RepoLayout;
+use crate::ssg::{PostMeta, SiteProfile};
+use crate::unpublish::UnpublishMode;
+
+#[derive(Debug, Clone, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct PublishDraftArgs {
+    pub slug: String,
+    pub title: String,
+    pub date: String,
+    pub tags: Vec<String>,
+    pub description: String,
+    pub cover: String, // base64 data URL or empty
+    pub cover_position: Option<f64>,
+    #[serde(default)]
+    pub cover_alt: String,
+    pub updated_at: Option<String>,
+    pub content: String, // markdown content
+    pub commit_message: String,
+    pub repo_path: String,
+    #[serde(default)]
+    pub extra_fields: ExtraFields,
+}
+
+/// The cover image of an article about to be published
+#[derive(Debug, Clone)]
+pub enum CoverImage {
+    Prepared(PreparedImage, ImageOrigin),
+    /// Synced to the drafts images directory, possibly raw by an older version
+    /// or added by hand, so re-encoded like the others
+    FromDrafts(PreparedImage),
+}
+
+/// An article with its frontmatter rendered and its images prepared in
+/// memory. Building one never touches the repo; `write_article` does.
+#[derive(Debug, Clone)]
+pub struct PreparedArticle {
+    /// Article file, relative to the repo root
+    pub path: String,
+    /// Where its images go, relative to the repo root
+    pub images_dir: String,
+    /// Whether images are also copied to the public images directory
+    pub public_images: bool,
+    pub frontmatter: String,
+    pub markdown: String,
+    pub cover: Option<CoverImage>,
+    pub images: Vec<InlineImage>,
+    pub warnings: Vec<String>,
+}
+
+impl PreparedArticle {
+    /// Where image `filename` is written, relative to the repo root
+    pub fn image_path(&self, filename: &str) -> String {
+        format!("{}/{}", self.images_dir.trim_end_matches('/'), filename)
+    }
+
+    /// Original and written sizes of the cover and inline images
+    pub fn image_reports(&self) -> Vec<ImageReport> {
+        let cover = match &self.cover {
+            Some(CoverImage::Prepared(image, _) | CoverImage::FromDrafts(image)) => {
+                Some(image.report())
+            }
+            None => None,
+        };
+        cover
+            .into_iter()
+            .chain(self.images.iter().map(|inline| inline.image.report()))
+            .collect()
+    }
+}
+
+/// Prepare the cover, inline images and Markdown for an article, following
+/// the site generator's `profile`. Inline images get responsive variants when
+/// the profile links them with HTML. `read_drafts_image` returns a file from
+/// the drafts images directory on the branch being published, if it is there.
+pub async fn prepare_article(
+    args: &PublishDraftArgs,
+    downloader: &ImageDownloader,
+    layout: &RepoLayout,
+    profile: &SiteProfile,
+    options: &ImageOptions,
+    read_drafts_image: &(dyn Fn(&str) -> Option<Vec<u8>> + Sync),
+) -> AppResult<PreparedArticle> {
+    let mut warnings = Vec::new();
+    let name_prefix = layout.image_name_prefix(&args.slug);
+    let cover_options = options.without_variants();
+
+    let (cover_path, cover) = if !args.cover.is_empty() && args.cover.starts_with("data:image") {
+        match prepare_data_url_image(&args.cover, name_prefix, &cover_options) {
+            Ok(image) => (
+                profile.image_url(layout, &image.filename),
+                Some(CoverImage::Prepared(image, ImageOrigin::Pasted)),
+            ),
+            Err(e) => {
+                eprintln!("Failed to save cover image: {}", e);
+                warnings.push(format!("Cover image will be dropped: {}", e));
+                (String::new(), None)
+            }
+        }
+    } else if !args.cover.is_empty() && args.cover.starts_with("http") {
+        match fetch_remote_image(downloader, &args.cover, name_prefix, &cover_options).await {
+            Ok(image) => (
+                profile.image_url(layout, &image.filename),
+                Some(CoverImage::Prepared(image, ImageOrigin::Downloaded)),
+            ),
+            Err(e) => {
+                eprintln!("Failed to download cover image: {}", e);
+                warnings.push(format!("Cover image will stay a remote link: {}", e));
+                (args.cover.clone(), None)
+            }
+        }
+    } else if let Some(filename) = layout.drafts_image_name(&args.cover) {
+        match read_drafts_image(filename)
+            .map(|bytes| prepare_image_bytes(&bytes, name_prefix, &cover_options))
+        {
+            Some(Ok(image)) => (
+                profile.image_url(layout, &image.filename),
+                Some(CoverImage::FromDrafts(image)),
+            ),
+            Some(Err(e)) => {
+                eprintln!("Failed to clean cover image: {}", e);
+                warnings.push(format!("Cover image will be dropped: {}", e));
+                (String::new(), None)
+            }
+            None => {
+                warnings.push(format!("Cover image {} was not found", args.cover));
+                (args.cover.clone(), None)
+            }
+        }
+    } else {
+        // Already a path (or empty), keep it
+        (args.cover.clone(), None)
+    };
+
+    let inline = prepare_inline_images(
+        &args.content,
+        Some(downloader),
+        name_prefix,
+        profile.images_url(layout),
+        if profile.html_images {
+            options
+        } else {
+            &cover_options
+        },
+        profile.html_images,
+    )
+    .await?;
+    warnings.extend(inline.failures);
+
+    let frontmatter = profile.render_frontmatter(&PostMeta {
+        title: &args.title,
+        date: &args.date,
+        updated: Some(args.updated_at.as_deref().unwrap_or(&args.date)),
+        tags: &args.tags,
+        description: &args.description,
+        cover: &cover_path,
+        cover_position: args.cover_position,
+        cover_alt: &args.cover_alt,
+        extra: &args.extra_fields,
+    })?;
+    let markdown = format!("{}{}", frontmatter, inline.content);
+
+    Ok(PreparedArticle {
+        path: profile.article_path(layout, &args.slug, &args.date),
+        images_dir: profile.images_dir(layout, &args.slug),
+        public_images: profile.copies_to_public(),
+        frontmatter,
+        markdown,
+        cover,
+        images: inline.images,
+        warnings,
+    })
+}
+
+/// Write a prepared article and its images into a checkout of the repo.
+/// Returns what to stage, relative to the repo root: the article file, its
+/// images directory and, if images were copied there, the public images
+/// directory. Directories the profile doesn't use are never listed, since
+/// `git add` fails on a path that doesn't exist.
+pub fn write_article(
+    repo_path: &Path,
+    layout: &RepoLayout,
+    article: &PreparedArticle,
+) -> AppResult<Vec<String>> {
+    let article_path = RepoLayout::dir(repo_path, &article.path);
+    let images_dir = RepoLayout::dir(repo_path, &article.images_dir);
+    let public_images_dir = (article.public_images && !layout.public_images_dir.is_empty())
+        .then(|| RepoLayout::dir(repo_path, &layout.public_images_dir));
+
+    // Create directories if they don't exist
+    if let Some(articles_dir) = article_path.parent() {
+        fs::create_dir_all(articles_dir)
+            .map_err(|e| AppError::io("Failed to create articles dir", e))?;
+    }
+    fs::create_dir_all(&images_dir).map_err(|e| AppError::io("Failed to create images dir", e))?;
+    if let Some(dir) = &public_images_dir {
+        fs::create_dir_all(dir)
+            .map_err(|e| AppError::io("Failed to create public images dir", e))?;
+    }
+    // The public copy is the file the site serves, so failing to make it
+    // fails the publish
+    let copy_to_public = |filename: &str| match &public_images_dir {
+        Some(dir) => copy_to_public_images(dir, &images_dir, filename),
+        None => Ok(()),
+    };
+
+    match &article.cover {
+        Some(CoverImage::Prepared(image, _) | CoverImage::FromDrafts(image)) => {
+            write_image(&images_dir, image)?;
+            copy_to_public(&image.filename)?;
+        }
+        None => {}
+    }
+
+    for image in article
+        .images
+        .iter()
+        .flat_map(|inline| inline.image.files())
+    {
+        match write_image(&images_dir, image) {
+            Ok(()) => copy_to_public(&image.filename)?,
+            Err(e) => eprintln!("Failed to save inline image: {}", e),
+        }
+    }
+
+    fs::write(&article_path, &article.markdown)
+        .map_err(|e| AppError::io("Failed to write article", e))?;
+
+    let mut written = vec![article.path.clone(), article.images_dir.clone()];
+    if public_images_dir.is_some() {
+        written.push(layout.public_images_dir.clone());
+    }
+    Ok(written)
+}
+
+// ============================================================================
+// Publish strategy
+// ============================================================================
+
+/// How a published article reaches the site's branch
+#[derive(Debug, Clone, Copy, Default, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub enum PublishMode {
+    /// Commit straight onto the base branch
+    DirectPush,
+    /// Push `drafts/{slug}`, open a pull request and merge it right away
+    #[default]
+    AutoMerge,
+    /// Push `drafts/{slug}` and leave the pull request open for review
+    PullRequest,
+}
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub enum MergeMethod {
+    #[default]
+    Merge,
+    Squash,
+    Rebase,
+}
+
+impl MergeMethod {
+    fn gh_flag(self) -> &'static str {
+        match self {
+            MergeMethod::Merge => "--merge",
+            MergeMethod::Squash => "--squash",
+            MergeMethod::Rebase => "--rebase",
+        }
+    }
+}
+
+/// Per-repo publish settings. Templates may use `{title}`, `{slug}`,
+/// `{description}`, `{date}` and `{tags}`.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase", default)]
+pub struct PublishStrategy {
+    pub mode: PublishMode,
+    pub merge_method: MergeMethod,
+    /// Branch articles are published to (pushed to directly, or the PR base);
+    /// empty for the layout's main branch
+    pub base_branch: String,
+    pub pr_title: String,
+    pub pr_body: String,
+    /// GitHub users or teams asked to review new pull requests
+    pub reviewers: Vec<String>,
+    /// Whether accessibility audit errors stop the publish
+    pub audit: AuditPolicy,
+    /// Whether unpublishing deletes the article or flags it as a draft
+    pub unpublish: UnpublishMode,
+}
+
+impl Default for PublishStrategy {
+    fn default() -> Self {
+        PublishStrategy {
+            mode: PublishMode::default(),
+            merge_method: MergeMethod::default(),
+            base_branch: String::new(),
+            pr_title: "Publish: {title}".to_string(),
+            pr_body: "Published via Nibandh".to_string(),
+            reviewers: Vec::new(),
+            audit: AuditPolicy::default(),
+            unpublish: UnpublishMode::default(),
+        }
+    }
+}
+
+impl PublishStrategy {
+    pub fn base_branch<'a>(&'a self, layout: &'a RepoLayout) -> &'a str {
+        if self.base_branch.is_empty() {
+            &layout.main_branch
+        } else {
+            &self.base_branch
+        }
+    }
+
+    /// The branch the publish commit is pushed to
+    pub fn branch(&self, layout: &RepoLayout, slug: &str) -> String {
+        match self.mode {
+            PublishMode::DirectPush => self.base_branch(layout).to_string(),
+            _ => layout.drafts_branch(slug),
+        }
+    }
+
+    /// Where the publish worktree starts, in order of preference
+    pub fn start_points(&self, layout: &RepoLayout, slug: &str) -> Vec<String> {
+        let base = self.base_branch(layout).to_string();
+        let remote_base = layout.remote_branch(&base);
+        match self.mode {
+            PublishMode::DirectPush => vec![remote_base, base],
+            _ => vec![self.branch(layout, slug), remote_base, base],
+        }
+    }
+
+    pub fn pr_title(&self, args: &PublishDraftArgs) -> String {
+        render_template(&self.pr_title, args)
+    }
+
+    pub fn pr_body(&self, args: &PublishDraftArgs) -> String {
+        render_template(&self.pr_body, args)
+    }
+}
+
+fn render_template(template: &str, args: &PublishDraftArgs) -> String {
+    template
+        .replace("{title}", &args.title)
+        .replace("{slug}", &args.slug)
+        .replace("{description}", &args.description)
+        .replace("{date}", &args.date)
+        .replace("{tags}", &args.tags.join(", "))
+}
+
+/// URL of the open pull request from `branch`, if there is one
+pub fn find_pull_request(dir: &Path, branch: &str, base: &str) -> AppResult<Option<String>> {
+    let output = gh(
+        dir,
+        &[
+            "pr", "list", "--head", branch, "--base", base, "--state", "open", "--json", "url",
+            "--jq", ".[0].url",
+        ],
+        "Failed to list pull requests",
+    )?;
+    let url = String::from_utf8_lossy(&output.stdout).trim().to_string();
+    Ok(Some(url).filter(|url| !url.is_empty()))
+}
+
+/// Reuse the open pull request from `branch` into `base` or create one.
+/// Returns its URL and whether it was created now.
+pub fn open_pull_request(
+    dir: &Path,
+    branch: &str,
+    base: &str,
+    strategy: &PublishStrategy,
+    args: &PublishDraftArgs,
+) -> AppResult<(String, bool)> {
+    let request = PullRequest {
+        title: strategy.pr_title(args),
+        body: strategy.pr_body(args),
+        reviewers: &strategy.reviewers,
+    };
+    request.open(dir, branch, base)
+}
+
+/// A pull request to open from a pushed branch
+pub struct PullRequest<'a> {
+    pub title: String,
+    pub body: String,
+    /// GitHub users or teams asked to review it
+    pub reviewers: &'a [String],
+}
+
+impl PullRequest<'_> {
+    /// Reuse the open pull request from `branch` into `base` or create this
+    /// one. Returns its URL and whether it was created now.
+    pub fn open(&self, dir: &Path, branch: &str, base: &str) -> AppResult<(String, bool)> {
+        if let Some(url) = find_pull_request(dir, branch, base)? {
+            return Ok((url, false));
+        }
+
+        let (title, body) = (self.title.as_str(), self.body.as_str());
+        let reviewers = self.reviewers.join(",");
+        let mut gh_args = vec![
+            "pr", "create", "--title", title, "--body", body, "--head", branch, "--base", base,
+        ];
+        if !reviewers.is_empty() {
+            gh_args.extend(["--reviewer", &reviewers]);
+        }
+
+        let output = gh(dir, &gh_args, "Failed to create PR")?;
+        // `gh pr create` prints the new PR's URL last
+        let url = String::from_utf8_lossy(&output.stdout)
+            .lines()
+            .last()
+            .unwrap_or_default()
+            .trim()
+            .to_string();
+        if url.is_empty() {
+            return Err(AppError::new(
+                ErrorKind::PullRequest,
+                "Failed to resolve PR URL",
+            ));
+        }
+
+        Ok((url, true))
+    }
+}
+
+/// Merge a pull request and delete its branch. Run from the detached
+/// worktree so gh never switches the user's checkout.
+pub fn merge_pull_request(dir: &Path, url: &str, method: MergeMethod) -> AppResult<()> {
+    gh(
+        dir,
+        &["pr", "merge", url, method.gh_flag(), "--delete-branch"],
+        "Failed to merge PR",
+    )
+    .map(|_| ())
+}
+
+/// Run `gh`, failing with `message` if it exits non-zero
+fn gh(dir: &Path, args: &[&str], message: &str) -> AppResult<Output> {
+    let output = Command::new("gh")
+        .current_dir(dir)
+        .args(args)
+        .output()
+        .map_err(|e| AppError::spawn("gh", e))?;
+
+    if !output.status.success() {
+        return Err(AppError::gh(message, &output));
+    }
+
+    Ok(output)
+}
+
+// ============================================================================
+// Publish plan (dry run)
+// ============================================================================
+
+#[derive(Debug, Clone, Copy, PartialEq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub enum FileAction {
+    Create,
+    Overwrite,
+    Unchanged,
+}
+
+/// A repo file publishing would write
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct PlannedFile {
+    pub path: String, // relative to the repo root
+    pub action: FileAction,
+}
+
+#[derive(Debug, Clone, Copy, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub enum ImageSource {
+    Pasted,
+    Downloaded,
+    Drafts,
+}
+
+/// An image publishing would write, plus its copy in the public images
+/// directory when the layout has one
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct PlannedImage {
+    pub path: String,
+    pub action: FileAction,
+    pub source: ImageSource,
+    pub is_cover: bool,
+    pub size_bytes: Option<usize>,
+    pub public_copy: Option<PlannedFile>,
+}
+
+/// Everything `publish_draft` would do for the same arguments
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct PublishPlan {
+    pub article: PlannedFile,
+    pub frontmatter: String,
+    pub images: Vec<PlannedImage>,
+    pub branch: String,
+    pub base: String,
+    /// Git and pull request steps, in order
+    pub steps: Vec<String>,
+    pub warnings: Vec<String>,
+    /// Accessibility audit of the article
+    pub audit: Vec<Finding>,
+    /// Whether the audit policy would refuse to publish
+    pub audit_blocks: bool,
+}
+
+/// Work out what publishing would change, without writing to the repo.
+/// Files are compared against the branch the publish would start from.
+pub async fn plan_publish(
+    args: &PublishDraftArgs,
+    downloader: &ImageDownloader,
+    git: &dyn GitBackend,
+    strategy: &PublishStrategy,
+    layout: &RepoLayout,
+    profile: &SiteProfile,
+    options: &ImageOptions,
+) -> AppResult<PublishPlan> {
+    let repo_path = Path::new(&args.repo_path);
+    if !repo_path.exists() {
+        return Err(AppError::repo_not_found(&args.repo_path));
+    }
+
+    let branch = strategy.branch(layout, &args.slug);
+    let mut steps = Vec::new();
+    let mut warnings = Vec::new();
+
+    let audit = audit_article(&args.content, &args.cover, &args.cover_alt)?;
+    let audit_blocks = strategy.audit.blocks(&audit);
+    if audit_blocks {
+        warnings.push("Publishing is blocked until the accessibility errors are fixed".to_string());
+    }
+
+    if git.current_branch(repo_path)?.as_deref() == Some(branch.as_str()) {
+        warnings.push(format!(
+            "Your checkout is on {}; it stays as is, so pull afterwards to see the commit",
+            branch
+        ));
+    }
+
+    let start_points = strategy.start_points(layout, &args.slug);
+    let start_points: Vec<&str> = start_points.iter().map(String::as_str).collect();
+    let base = git::first_existing_rev(git, repo_path, &start_points)
+        .unwrap_or_else(|| "HEAD".to_string());
+    if base == branch {
+        steps.push(format!("Start a temporary worktree from {}", branch));
+    } else {
+        steps.push(format!(
+            "Start a temporary worktree for {} from {}",
+            branch, base
+        ));
+    }
+
+    let read_drafts_image =
+        |name: &str| git.read_file(repo_path, &base, &layout.drafts_image_path(name));
+    let article = prepare_article(
+        args,
+        downloader,
+        layout,
+        profile,
+        options,
+        &read_drafts_image,
+    )
+    .await?;
+    warnings.extend(article.warnings.iter().cloned());
+
+    let article_path = article.path.clone();
+    let existing = git.read_file(repo_path, &base, &article_path);
+    if let Some(existing) = &existing {
+        let existing_title = parse_article(&String::from_utf8_lossy(existing), profile)
+            .map(|(meta, _)| meta.title)
+            .unwrap_or_default();
+        if existing_title != args.title {
+            warnings.push(format!(
+                "Slug collision: {} already contains \"{}\" and will be overwritten",
+                article_path, existing_title
+            ));
+        }
+    }
+
+    let mut images = Vec::new();
+    let mut plan_image = |image: &PreparedImage, source: ImageSource, is_cover: bool| {
+        let path = article.image_path(&image.filename);
+        let bytes = Some(image.bytes.as_slice());
+        images.push(PlannedImage {
+            action: file_action(git, repo_path, &base, &path, bytes),
+            size_bytes: Some(image.bytes.len()),
+            public_copy: (article.public_images)
+                .then(|| layout.public_image_path(&image.filename))
+                .flatten()
+                .map(|public_path| PlannedFile {
+                    action: file_action(git, repo_path, &base, &public_path, bytes),
+                    path: public_path,
+                }),
+            path,
+            source,
+            is_cover,
+        });
+    };
+
+    match &article.cover {
+        Some(CoverImage::Prepared(image, origin)) => plan_image(image, (*origin).into(), true),
+        Some(CoverImage::FromDrafts(image)) => plan_image(image, ImageSource::Drafts, true),
+        None => {}
+    }
+    for inline in &article.images {
+        for image in inline.image.files() {
+            plan_image(image, inline.origin.into(), false);
+        }
+    }
+
+    steps.push(format!("Commit \"{}\"", args.commit_message));
+    steps.push(format!("Push {} to {}", branch, layout.remote));
+    if strategy.mode != PublishMode::DirectPush {
+        steps.extend(plan_pull_request(
+            repo_path,
+            &branch,
+            strategy.base_branch(layout),
+            strategy,
+            args,
+            &mut warnings,
+        ));
+    }
+    steps.push("Remove the temporary worktree".to_string());
+
+    Ok(PublishPlan {
+        article: PlannedFile {
+            action: file_action(
+                git,
+                repo_path,
+                &base,
+                &article_path,
+                Some(article.markdown.as_bytes()),
+            ),
+            path: article_path,
+        },
+        frontmatter: article.frontmatter,
+        images,
+        branch,
+        base,
+        steps,
+        warnings,
+        audit,
+        audit_blocks,
+    })
+}
+
+impl From<ImageOrigin> for ImageSource {
+    fn from(origin: ImageOrigin) -> Self {
+        match origin {
+            ImageOrigin::Pasted => ImageSource::Pasted,
+            ImageOrigin::Downloaded => ImageSource::Downloaded,
+        }
+    }
+}
+
+fn plan_pull_request(
+    repo_path: &Path,
+    branch: &str,
+    base: &str,
+    strategy: &PublishStrategy,
+    args: &PublishDraftArgs,
+    warnings: &mut Vec<String>,
+) -> Vec<String> {
+    let create_step = format!(
+        "Open pull request \"{}\" into {}",
+        strategy.pr_title(args),
+        base
+    );
+    let mut steps = match find_pull_request(repo_path, branch, base) {
+        Ok(Some(url)) => vec![format!("Reuse open pull request {}", url)],
+        Ok(None) if strategy.reviewers.is_empty() => vec![create_step],
+        Ok(None) => vec![
+            create_step,
+            format!("Request review from {}", strategy.reviewers.join(", ")),
+        ],
+        Err(e) => {
+            warnings.push(format!(
+                "Could not check for an existing pull request: {}",
+                e
+            ));
+            vec![create_step]
+        }
+    };
+
+    if strategy.mode == PublishMode::AutoMerge {
+        let method = match strategy.merge_method {
+            MergeMethod::Merge => "merge commit",
+            MergeMethod::Squash => "squash",
+            MergeMethod::Rebase => "rebase",
+        };
+        steps.push(format!(
+            "Merge the pull request ({}) and delete {}",
+            method, branch
+        ));
+    } else {
+        steps.push("Leave the pull request open for review".to_string());
+    }
+
+    steps
+}
+
+/// Compare what would be written at `path` with the file on `base`
+fn file_action(
+    git: &dyn GitBackend,
+    repo_path: &Path,
+    base: &str,
+    path: &str,
+    bytes: Option<&[u8]>,
+) -> FileAction {
+    match git.read_file(repo_path, base, path) {
+        None => FileAction::Create,
+        Some(existing) if Some(existing.as_slice()) == bytes => FileAction::Unchanged,
+        Some(_) => FileAction::Overwrite,
+    }
+}
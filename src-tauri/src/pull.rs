@@ -0,0 +1,176 @@
+use chrono::{Duration, Utc};
+use serde::Serialize;
+use std::path::Path;
+
+use crate::database::{Database, Draft, DraftStatus};
+use crate::error::{AppError, AppResult};
+use crate::frontmatter::{parse_article, parse_timestamp};
+use crate::git::{tracking_refspec, GitBackend};
+use crate::import::{draft_from_file, image_data_url, ImportSkip, SourceKind};
+use crate::layout::RepoLayout;
+use crate::ssg::SiteProfile;
+
+/// Saving a draft right after syncing it bumps `updated_at` slightly past
+/// `synced_at`; edits within this window don't count as local changes.
+const SYNC_GRACE_SECS: i64 = 10;
+
+/// Outcome of pulling the drafts branches into the local database
+#[derive(Debug, Clone, Default, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct PullResult {
+    pub created: usize,
+    pub updated: usize,
+    pub unchanged: usize,
+    pub conflicts: Vec<PullConflict>,
+    pub skipped: Vec<ImportSkip>,
+}
+
+/// A draft changed both locally and remotely. Local wins; the remote copy is
+/// saved as its own draft so nothing is lost.
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct PullConflict {
+    pub draft_id: String,
+    pub slug: String,
+    pub remote_copy_id: String,
+    pub remote_copy_slug: String,
+}
+
+/// Fetch every drafts branch from the layout's remote and merge the draft
+/// file from each into the local database
+pub fn pull_drafts(
+    db: &Database,
+    repo_path: &Path,
+    git: &dyn GitBackend,
+    layout: &RepoLayout,
+) -> AppResult<PullResult> {
+    let pattern = format!("{}*", layout.drafts_branch_prefix);
+    let refspec = tracking_refspec(&layout.remote, &pattern);
+    git.fetch(repo_path, &layout.remote, &[&refspec])?;
+
+    let prefix = format!(
+        "refs/remotes/{}",
+        layout.remote_branch(&layout.drafts_branch_prefix)
+    );
+    let slugs = git.list_refs(repo_path, &prefix)?;
+
+    let mut result = PullResult::default();
+    for slug in &slugs {
+        let branch = layout.remote_branch(&layout.drafts_branch(slug));
+        let file = layout.draft_path(slug);
+
+        let content = match git.read_file(repo_path, &branch, &file) {
+            Some(content) => String::from_utf8_lossy(&content).to_string(),
+            None => {
+                result.skipped.push(ImportSkip {
+                    path: format!("{}:{}", branch, file),
+                    reason: "Branch has no draft file".to_string(),
+                });
+                continue;
+            }
+        };
+
+        let load_image = |src: &str| {
+            let path = layout.drafts_image_path(layout.drafts_image_name(src)?);
+            let bytes = git.read_file(repo_path, &branch, &path)?;
+            image_data_url(&path, &bytes)
+        };
+        if let Err(e) = pull_draft(db, slug, &content, &load_image, &mut result) {
+            result.skipped.push(ImportSkip {
+                path: format!("{}:{}", branch, file),
+                reason: e.to_string(),
+            });
+        }
+    }
+
+    Ok(result)
+}
+
+fn pull_draft(
+    db: &Database,
+    slug: &str,
+    content: &str,
+    load_image: &dyn Fn(&str) -> Option<String>,
+    result: &mut PullResult,
+) -> AppResult<()> {
+    let (meta, body) = parse_article(content, &SiteProfile::drafts())
+        .ok_or_else(|| AppError::invalid("No frontmatter found"))?;
+
+    let local = match &meta.draft_id {
+        Some(id) => db.get_draft(id)?,
+        None => db.find_draft_by_slug(slug)?,
+    };
+
+    let remote = draft_from_file(slug, &meta, body, SourceKind::Synced, load_image)?;
+    let remote_time = parse_timestamp(&remote.updated_at)
+        .ok_or_else(|| AppError::invalid("Draft file has no last_updated time"))?;
+
+    let local = match local {
+        Some(local) => local,
+        None => {
+            db.save_imported_draft(Draft {
+                id: meta.draft_id.clone().unwrap_or_default(),
+                ..remote
+            })?;
+            result.created += 1;
+            return Ok(());
+        }
+    };
+
+    let synced_time = local.synced_at.as_deref().and_then(parse_timestamp);
+    let local_time = parse_timestamp(&local.updated_at);
+
+    let (remote_changed, local_changed) = match (synced_time, local_time) {
+        (Some(synced), Some(local_time)) => (
+            remote_time > synced,
+            local_time > synced + Duration::seconds(SYNC_GRACE_SECS),
+        ),
+        // Never synced from this machine: the newer side wins
+        (None, Some(local_time)) => (remote_time > local_time, remote_time < local_time),
+        (_, None) => (true, false),
+    };
+
+    if !remote_changed {
+        result.unchanged += 1;
+        return Ok(());
+    }
+
+    if local_changed {
+        let copy_slug = format!("{}_remote_{}", slug, remote_time.format("%Y-%m-%d"));
+        let copy = match db.find_draft_by_slug(&copy_slug)? {
+            Some(existing) if existing.content == remote.content => existing,
+            _ => db.save_imported_draft(Draft {
+                slug: copy_slug,
+                title: format!("{} (remote)", remote.title),
+                synced_at: None,
+                status: DraftStatus::Draft,
+                ..remote
+            })?,
+        };
+
+        db.mark_synced(&local.id, &remote_time.to_rfc3339())?;
+        result.conflicts.push(PullConflict {
+            draft_id: local.id,
+            slug: local.slug,
+            remote_copy_id: copy.id,
+            remote_copy_slug: copy.slug,
+        });
+        return Ok(());
+    }
+
+    db.save_imported_draft(Draft {
+        id: local.id,
+        created_at: local.created_at,
+        synced_at: Some(remote_time.max(Utc::now()).to_rfc3339()),
+        published_at: local.published_at,
+        status: if local.status == DraftStatus::Published {
+            DraftStatus::Published
+        } else {
+            DraftStatus::Synced
+        },
+        ..remote
+    })?;
+    result.updated += 1;
+
+    Ok(())
+}
@@ -0,0 +1,312 @@
+use serde::{Deserialize, Serialize};
+use serde_json::{json, Value};
+use std::fs;
+use std::path::Path;
+
+use crate::error::{AppError, AppResult};
+use crate::layout::RepoLayout;
+use crate::ssg::{FrontmatterFormat, SiteProfile};
+
+/// Where a redirect from an article's old URL is recorded when its slug changes
+#[derive(Debug, Clone, Copy, Default, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub enum RedirectFormat {
+    /// Don't record redirects
+    #[default]
+    None,
+    /// `/old /new 301` lines in a Netlify or Cloudflare Pages `_redirects` file
+    Netlify,
+    /// The `redirects` array of `vercel.json`
+    Vercel,
+    /// The old URL in the article's own frontmatter (Hugo and Zola `aliases`,
+    /// Jekyll `redirect_from`)
+    Aliases,
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase", default)]
+pub struct RedirectOptions {
+    pub format: RedirectFormat,
+    /// Redirects file relative to the repo root; empty for the format's
+    /// usual place (`public/_redirects`, `vercel.json`)
+    pub file: String,
+}
+
+impl RedirectOptions {
+    /// The redirects file, if the format uses one
+    pub fn file(&self) -> Option<&str> {
+        let default = match self.format {
+            RedirectFormat::Netlify => "public/_redirects",
+            RedirectFormat::Vercel => "vercel.json",
+            RedirectFormat::None | RedirectFormat::Aliases => return None,
+        };
+        Some(if self.file.is_empty() {
+            default
+        } else {
+            &self.file
+        })
+    }
+
+    /// Record a permanent redirect from `from` to `to` under `root`. With
+    /// `Aliases` the old URL is added to `article` instead. Redirects that
+    /// pointed at `from` are moved to `to`, so renaming twice never chains.
+    /// Returns a description of the change, if one was made.
+    pub fn record(
+        &self,
+        root: &Path,
+        profile: &SiteProfile,
+        article: &mut String,
+        from: &str,
+        to: &str,
+    ) -> AppResult<Option<String>> {
+        match self.format {
+            RedirectFormat::None => Ok(None),
+            RedirectFormat::Aliases => {
+                let key = profile.aliases_key.ok_or_else(|| {
+                    AppError::invalid(
+                        "This site generator has no frontmatter redirects; use netlify or vercel",
+                    )
+                })?;
+                *article = add_alias(article, profile.format, key, from)?;
+                Ok(Some(format!("Added {} to {}", from, key)))
+            }
+            RedirectFormat::Netlify | RedirectFormat::Vercel => {
+                let file = self.file().unwrap_or_default();
+                let path = RepoLayout::dir(root, file);
+                let existing = match fs::read_to_string(&path) {
+                    Ok(content) => content,
+                    Err(e) if e.kind() == std::io::ErrorKind::NotFound => String::new(),
+                    Err(e) => return Err(AppError::io(format!("Failed to read {}", file), e)),
+                };
+                let updated = if self.format == RedirectFormat::Netlify {
+                    netlify_redirects(&existing, from, to)
+                } else {
+                    vercel_redirects(&existing, from, to)?
+                };
+                if let Some(parent) = path.parent() {
+                    fs::create_dir_all(parent)
+                        .map_err(|e| AppError::io("Failed to create redirects directory", e))?;
+                }
+                fs::write(&path, updated)
+                    .map_err(|e| AppError::io(format!("Failed to write {}", file), e))?;
+                Ok(Some(format!("Redirected {} to {} in {}", from, to, file)))
+            }
+        }
+    }
+}
+
+fn netlify_redirects(existing: &str, from: &str, to: &str) -> String {
+    let mut lines: Vec<String> = Vec::new();
+    for line in existing.lines() {
+        let mut parts = line.split_whitespace();
+        match (parts.next(), parts.next()) {
+            // The old URL now lives at `to`; an earlier rule for it would shadow ours
+            (Some(source), Some(_)) if source == from => continue,
+            (Some(source), Some(target)) if target == from => {
+                let rest: Vec<&str> = parts.collect();
+                lines.push(
+                    format!("{} {} {}", source, to, rest.join(" "))
+                        .trim_end()
+                        .to_string(),
+                );
+            }
+            _ => lines.push(line.to_string()),
+        }
+    }
+    lines.push(format!("{} {} 301", from, to));
+    lines.join("\n") + "\n"
+}
+
+fn vercel_redirects(existing: &str, from: &str, to: &str) -> AppResult<String> {
+    let mut config: Value = if existing.trim().is_empty() {
+        json!({})
+    } else {
+        serde_json::from_str(existing)
+            .map_err(|e| AppError::invalid("vercel.json is not valid JSON").with_details(e))?
+    };
+    let object = config
+        .as_object_mut()
+        .ok_or_else(|| AppError::invalid("vercel.json is not a JSON object"))?;
+    let redirects = object
+        .entry("redirects")
+        .or_insert_with(|| json!([]))
+        .as_array_mut()
+        .ok_or_else(|| AppError::invalid("\"redirects\" in vercel.json is not a list"))?;
+
+    redirects.retain(|redirect| redirect["source"] != from);
+    for redirect in redirects.iter_mut() {
+        if redirect["destination"] == from {
+            redirect["destination"] = json!(to);
+        }
+    }
+    redirects.push(json!({ "source": from, "destination": to, "permanent": true }));
+
+    serde_json::to_string_pretty(&config)
+        .map(|json| json + "\n")
+        .map_err(|e| AppError::invalid("Failed to write vercel.json").with_details(e))
+}
+
+/// Add `url` to the `key` list in the frontmatter of `article`, creating the
+/// list at the top of the frontmatter (where TOML keys are never inside a
+/// table) if it isn't there. Other lines are left untouched.
+fn add_alias(article: &str, format: FrontmatterFormat, key: &str, url: &str) -> AppResult<String> {
+    let separator = format.separator();
+    let mut lines: Vec<String> = article.lines().map(str::to_string).collect();
+    let end = format
+        .end(&lines)
+        .ok_or_else(|| AppError::invalid("The article has no frontmatter to add an alias to"))?;
+    let quoted = serde_json::to_string(url).unwrap_or_default();
+
+    let existing = format.find_key(&lines, end, key);
+    match existing {
+        Some(index) => {
+            let (name, value) = lines[index].split_at(lines[index].find(separator).unwrap());
+            let value = value[separator.len()..].trim();
+            if let Some(items) = value.strip_prefix('[').and_then(|v| v.strip_suffix(']')) {
+                if !items.contains(&quoted) {
+                    let items = items.trim();
+                    let joined = if items.is_empty() {
+                        quoted
+                    } else {
+                        format!("{}, {}", items, quoted)
+                    };
+                    lines[index] = format!("{}{} [{}]", name, separator, joined);
+                }
+            } else if value.is_empty() && format == FrontmatterFormat::Yaml {
+                // Block list: add an item after the last one
+                let last = (index + 1..end)
+                    .take_while(|&i| lines[i].trim_start().starts_with("- "))
+                    .last()
+                    .unwrap_or(index);
+                let indent = lines
+                    .get(index + 1)
+                    .filter(|_| last > index)
+                    .map(|line| &line[..line.len() - line.trim_start().len()])
+                    .unwrap_or("  ")
+                    .to_string();
+                if !lines[index + 1..=last]
+                    .iter()
+                    .any(|line| line.contains(&quoted))
+                {
+                    lines.insert(last + 1, format!("{}- {}", indent, quoted));
+                }
+            } else {
+                lines[index] = format!("{}{} [{}, {}]", name, separator, value, quoted);
+            }
+        }
+        None => lines.insert(1, format.line(key, &format!("[{}]", quoted))),
+    }
+
+    let mut updated = lines.join("\n");
+    if article.ends_with('\n') {
+        updated.push('\n');
+    }
+    Ok(updated)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn netlify_rules_are_appended_and_retargeted() {
+        assert_eq!(
+            netlify_redirects("", "/articles/a", "/articles/b"),
+            "/articles/a /articles/b 301\n"
+        );
+        let existing = "# Redirects\n/old /articles/a 302\n/articles/b /elsewhere 301\n/x /y\n";
+        assert_eq!(
+            netlify_redirects(existing, "/articles/b", "/articles/c"),
+            "# Redirects\n/old /articles/a 302\n/x /y\n/articles/b /articles/c 301\n"
+        );
+        assert_eq!(
+            netlify_redirects("/old /articles/a 302\n", "/articles/a", "/articles/b"),
+            "/old /articles/b 302\n/articles/a /articles/b 301\n"
+        );
+    }
+
+    #[test]
+    fn vercel_redirects_keep_other_settings() {
+        let updated = vercel_redirects("", "/articles/a", "/articles/b").unwrap();
+        let config: Value = serde_json::from_str(&updated).unwrap();
+        assert_eq!(
+            config,
+            json!({ "redirects": [
+                { "source": "/articles/a", "destination": "/articles/b", "permanent": true }
+            ] })
+        );
+
+        let existing = r#"{
+  "cleanUrls": true,
+  "redirects": [
+    { "source": "/old", "destination": "/articles/a" },
+    { "source": "/articles/b", "destination": "/elsewhere" }
+  ]
+}"#;
+        let updated = vercel_redirects(existing, "/articles/a", "/articles/b").unwrap();
+        assert!(updated.ends_with("}\n"));
+        let config: Value = serde_json::from_str(&updated).unwrap();
+        assert_eq!(
+            config,
+            json!({
+                "cleanUrls": true,
+                "redirects": [
+                    { "source": "/old", "destination": "/articles/b" },
+                    { "source": "/articles/b", "destination": "/elsewhere" },
+                    { "source": "/articles/a", "destination": "/articles/b", "permanent": true }
+                ]
+            })
+        );
+    }
+
+    #[test]
+    fn invalid_vercel_config_is_refused() {
+        assert!(vercel_redirects("{", "/a", "/b").is_err());
+        assert!(vercel_redirects("[]", "/a", "/b").is_err());
+        assert!(vercel_redirects(r#"{"redirects": {}}"#, "/a", "/b").is_err());
+    }
+
+    #[test]
+    fn aliases_are_added_to_frontmatter() {
+        let yaml = FrontmatterFormat::Yaml;
+        assert_eq!(
+            add_alias("---\ntitle: Hi\n---\nBody\n", yaml, "aliases", "/a").unwrap(),
+            "---\naliases: [\"/a\"]\ntitle: Hi\n---\nBody\n"
+        );
+        assert_eq!(
+            add_alias("---\naliases: [\"/a\"]\n---\n", yaml, "aliases", "/b").unwrap(),
+            "---\naliases: [\"/a\", \"/b\"]\n---\n"
+        );
+        assert_eq!(
+            add_alias("---\naliases: [\"/a\"]\n---\n", yaml, "aliases", "/a").unwrap(),
+            "---\naliases: [\"/a\"]\n---\n"
+        );
+        assert_eq!(
+            add_alias(
+                "---\nredirect_from:\n    - \"/a\"\ntitle: Hi\n---\n",
+                yaml,
+                "redirect_from",
+                "/b"
+            )
+            .unwrap(),
+            "---\nredirect_from:\n    - \"/a\"\n    - \"/b\"\ntitle: Hi\n---\n"
+        );
+        assert_eq!(
+            add_alias("---\naliases: /a\n---", yaml, "aliases", "/b").unwrap(),
+            "---\naliases: [/a, \"/b\"]\n---"
+        );
+
+        let toml = FrontmatterFormat::Toml;
+        assert_eq!(
+            add_alias(
+                "+++\ntitle = \"Hi\"\n[extra]\nx = 1\n+++\n",
+                toml,
+                "aliases",
+                "/a"
+            )
+            .unwrap(),
+            "+++\naliases = [\"/a\"]\ntitle = \"Hi\"\n[extra]\nx = 1\n+++\n"
+        );
+        assert!(add_alias("No frontmatter\n", toml, "aliases", "/a").is_err());
+    }
+}
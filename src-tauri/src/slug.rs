@@ -0,0 +1,430 @@
+use deunicode::deunicode;
+use regex::Regex;
+use serde::Serialize;
+use std::collections::HashSet;
+use std::fs;
+use std::path::Path;
+
+use crate::error::{AppError, AppResult};
+use crate::frontmatter::parse_timestamp;
+use crate::git::{first_existing_rev, tracking_refspec, CommitOutcome, GitBackend, Worktree};
+use crate::layout::RepoLayout;
+use crate::publish::{self, PublishMode, PublishStrategy, PullRequest};
+use crate::redirects::RedirectOptions;
+use crate::ssg::SiteProfile;
+
+/// Longest slug `generate_slug` produces; longer titles are cut at a word
+const MAX_SLUG_LENGTH: usize = 80;
+
+/// A URL and file name friendly slug for `title`: transliterated to ASCII,
+/// lowercased, with runs of anything else turned into single hyphens
+pub fn generate_slug(title: &str) -> String {
+    let ascii = deunicode(title).to_lowercase();
+    let mut slug = String::with_capacity(ascii.len());
+    for word in ascii
+        .split(|c: char| !c.is_ascii_alphanumeric())
+        .filter(|word| !word.is_empty())
+    {
+        if !slug.is_empty() && slug.len() + 1 + word.len() > MAX_SLUG_LENGTH {
+            break;
+        }
+        if !slug.is_empty() {
+            slug.push('-');
+        }
+        slug.push_str(word);
+    }
+    slug.truncate(MAX_SLUG_LENGTH);
+    if slug.is_empty() {
+        "untitled".to_string()
+    } else {
+        slug
+    }
+}
+
+/// Refuse slugs that aren't safe as a file name and a branch name: anything
+/// but ASCII letters, digits, `-` and `_`, or a leading `-`
+pub fn check_slug(slug: &str) -> AppResult<()> {
+    let safe = !slug.is_empty()
+        && !slug.starts_with('-')
+        && slug
+            .chars()
+            .all(|c| c.is_ascii_alphanumeric() || c == '-' || c == '_');
+    if safe {
+        Ok(())
+    } else {
+        Err(AppError::invalid(format!(
+            "Slug \"{}\" can only contain letters, digits, hyphens and underscores",
+            slug
+        )))
+    }
+}
+
+/// `generate_slug(title)`, with `-2`, `-3`, ... appended while it is in
+/// `taken` (see `published_slugs`). `current` is the draft's own slug, which
+/// never counts as taken.
+pub fn unique_slug(taken: &HashSet<String>, title: &str, current: Option<&str>) -> String {
+    let base = generate_slug(title);
+    let is_free = |slug: &str| Some(slug) == current || !taken.contains(slug);
+    if is_free(&base) {
+        return base;
+    }
+    (2..)
+        .map(|n| format!("{}-{}", base, n))
+        .find(|slug| is_free(slug))
+        .unwrap_or(base)
+}
+
+/// A published article file found in the articles directory
+pub struct ArticleFile {
+    /// The slug it is published under (without a date prefix)
+    pub slug: String,
+    /// File or bundle directory name, which may carry a date prefix
+    pub name: String,
+    /// Whether it is a `{slug}/index.md` page bundle
+    pub bundle: bool,
+}
+
+impl ArticleFile {
+    /// The Markdown file, relative to the repo root
+    pub fn path(&self, layout: &RepoLayout) -> String {
+        if self.bundle {
+            layout.article_file(&format!("{}/index.md", self.name))
+        } else {
+            layout.article_file(&self.name)
+        }
+    }
+}
+
+impl ArticleFile {
+    /// The article named `name` in the articles directory: a `{slug}/index.md`
+    /// bundle directory, or a Markdown file
+    fn from_name(name: String, bundle: bool) -> Option<ArticleFile> {
+        if bundle {
+            return Some(ArticleFile {
+                slug: name.clone(),
+                name,
+                bundle,
+            });
+        }
+        let stem = name
+            .strip_suffix(".md")
+            .or_else(|| name.strip_suffix(".mdx"))?;
+        // Jekyll names posts `YYYY-MM-DD-slug.md` but links them by slug
+        let slug = stem
+            .get(11..)
+            .filter(|_| stem.as_bytes().get(10) == Some(&b'-'))
+            .filter(|_| parse_timestamp(&stem[..10]).is_some())
+            .unwrap_or(stem);
+        Some(ArticleFile {
+            slug: slug.to_string(),
+            name,
+            bundle,
+        })
+    }
+}
+
+/// Articles in the checkout: `{slug}.md`, `{date}-{slug}.md` and `{slug}/index.md`,
+/// sorted by name
+pub fn list_articles(root: &Path, layout: &RepoLayout) -> Vec<ArticleFile> {
+    let Ok(entries) = fs::read_dir(RepoLayout::dir(root, &layout.articles_dir)) else {
+        return Vec::new();
+    };
+    let mut articles: Vec<ArticleFile> = entries
+        .flatten()
+        .filter_map(|entry| {
+            let path = entry.path();
+            let name = entry.file_name().to_string_lossy().into_owned();
+            if !path.is_dir() {
+                ArticleFile::from_name(name, false)
+            } else if path.join("index.md").exists() {
+                ArticleFile::from_name(name, true)
+            } else {
+                None
+            }
+        })
+        .collect();
+    articles.sort_by(|a, b| a.name.cmp(&b.name));
+    articles
+}
+
+/// Slugs of `articles`, with and without date prefixes
+fn slugs_of(articles: impl IntoIterator<Item = ArticleFile>) -> HashSet<String> {
+    let mut slugs = HashSet::new();
+    for article in articles {
+        if !article.bundle {
+            let stem = article.name.rsplit_once('.').map_or("", |(stem, _)| stem);
+            slugs.insert(stem.to_string());
+        }
+        slugs.insert(article.slug);
+    }
+    slugs
+}
+
+/// Slugs of the articles on the publish base branch, with and without date
+/// prefixes, freshly fetched rather than read from the user's checkout, which
+/// may be on another branch or out of date. Only the file names are read, so
+/// nothing is checked out.
+pub fn published_slugs(
+    git: &dyn GitBackend,
+    repo_path: &Path,
+    layout: &RepoLayout,
+    strategy: &PublishStrategy,
+) -> AppResult<HashSet<String>> {
+    let base = strategy.base_branch(layout);
+    let remote_base = layout.remote_branch(base);
+    // A missing remote branch is expected for a new site; the local one is
+    // used instead
+    let _ = git.fetch(
+        repo_path,
+        &layout.remote,
+        &[&tracking_refspec(&layout.remote, base)],
+    );
+    let Some(rev) = first_existing_rev(git, repo_path, &[&remote_base, base]) else {
+        return Ok(HashSet::new());
+    };
+
+    let files = git.list_files(repo_path, &rev, &layout.articles_dir)?;
+    Ok(slugs_of(files.into_iter().filter_map(
+        |file| match file.split_once('/') {
+            None => ArticleFile::from_name(file, false),
+            Some((dir, "index.md")) => ArticleFile::from_name(dir.to_string(), true),
+            Some(_) => None,
+        },
+    )))
+}
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SlugRename {
+    pub old_path: String,
+    pub new_path: String,
+    /// Image files renamed to the new slug, relative to the repo root
+    pub images: Vec<String>,
+    /// How the old URL was redirected, if it was
+    pub redirect: Option<String>,
+    pub message: String,
+}
+
+/// Move the published article `old` to slug `new` in one commit, following
+/// the publish strategy like `remove_orphan_images`. Images named after the
+/// old slug are renamed with it, and a redirect from the old URL is recorded
+/// as `redirects` says.
+#[allow(clippy::too_many_arguments)]
+pub fn rename_article(
+    git: &dyn GitBackend,
+    repo_path: &Path,
+    layout: &RepoLayout,
+    profile: &SiteProfile,
+    strategy: &PublishStrategy,
+    redirects: &RedirectOptions,
+    old: &str,
+    new: &str,
+) -> AppResult<SlugRename> {
+    check_slug(old)?;
+    check_slug(new)?;
+    if old == new {
+        return Err(AppError::invalid("The new slug is the same as the old one"));
+    }
+
+    let base = strategy.base_branch(layout);
+    let remote_base = layout.remote_branch(base);
+    let branch = match strategy.mode {
+        PublishMode::DirectPush => base.to_string(),
+        _ => format!("nibandh/rename-{}-to-{}", old, new),
+    };
+    let worktree = Worktree::open(
+        git,
+        repo_path,
+        &layout.remote,
+        &branch,
+        &[&remote_base, base],
+    )?;
+    let root = worktree.path();
+
+    let articles = list_articles(root, layout);
+    let article = articles
+        .iter()
+        .find(|article| article.slug == old)
+        .ok_or_else(|| {
+            AppError::not_found(format!("No published article uses the slug \"{}\"", old))
+        })?;
+    if articles.iter().any(|article| article.slug == new) {
+        return Err(AppError::invalid(format!(
+            "An article already uses the slug \"{}\"",
+            new
+        )));
+    }
+
+    // Only the slug at the end of the name changes, so a date prefix stays
+    let (stem, extension) = match article.name.rsplit_once('.') {
+        Some((stem, extension)) if !article.bundle => (stem, format!(".{}", extension)),
+        _ => (article.name.as_str(), String::new()),
+    };
+    let new_name = format!("{}{}{}", &stem[..stem.len() - old.len()], new, extension);
+    let articles_dir = RepoLayout::dir(root, &layout.articles_dir);
+    fs::rename(
+        articles_dir.join(&article.name),
+        articles_dir.join(&new_name),
+    )
+    .map_err(|e| AppError::io(format!("Failed to move {}", article.name), e))?;
+    let (old_path, new_path, images_dirs) = if article.bundle {
+        (
+            layout.article_file(&format!("{}/index.md", old)),
+            layout.article_file(&format!("{}/index.md", new)),
+            vec![layout.bundle_dir(new)],
+        )
+    } else {
+        let mut dirs = vec![layout.images_dir.clone()];
+        if !layout.public_images_dir.is_empty() {
+            dirs.push(layout.public_images_dir.clone());
+        }
+        (
+            layout.article_file(&article.name),
+            layout.article_file(&new_name),
+            dirs,
+        )
+    };
+    let article_file = RepoLayout::dir(root, &new_path);
+    let mut text = fs::read_to_string(&article_file)
+        .map_err(|e| AppError::io(format!("Failed to read {}", new_path), e))?;
+
+    // Images are named `{slug}-{hash}.{ext}` and `{slug}-{hash}-{width}w.{ext}`
+    let mut images = Vec::new();
+    if layout.slug_image_names {
+        let names_re = Regex::new(&format!(
+            r#"(^|[/("'\s=])({}-[0-9a-f]{{16}}(?:-\d+w)?\.[A-Za-z0-9]+)"#,
+            regex::escape(old)
+        ))
+        .map_err(|e| AppError::invalid("Failed to build image name pattern").with_details(e))?;
+        let names: HashSet<String> = names_re
+            .captures_iter(&text)
+            .map(|caps| caps[2].to_string())
+            .collect();
+        for name in &names {
+            let renamed = format!("{}{}", new, &name[old.len()..]);
+            for dir in &images_dirs {
+                let from = RepoLayout::dir(root, dir).join(name);
+                if from.exists() {
+                    fs::rename(&from, RepoLayout::dir(root, dir).join(&renamed))
+                        .map_err(|e| AppError::io(format!("Failed to rename {}", name), e))?;
+                    images.push(format!("{}/{}", dir.trim_end_matches('/'), renamed));
+                }
+            }
+        }
+        text = names_re
+            .replace_all(&text, |caps: &regex::Captures| {
+                format!("{}{}{}", &caps[1], new, &caps[2][old.len()..])
+            })
+            .into_owned();
+        images.sort();
+    }
+
+    let articles_url = layout.articles_url.trim_end_matches('/');
+    let redirect = redirects.record(
+        root,
+        profile,
+        &mut text,
+        &format!("{}/{}", articles_url, old),
+        &format!("{}/{}", articles_url, new),
+    )?;
+    fs::write(&article_file, &text)
+        .map_err(|e| AppError::io(format!("Failed to write {}", new_path), e))?;
+
+    let mut paths = layout.publish_paths(root);
+    paths.extend(redirects.file());
+    worktree.add(&paths)?;
+    let message = format!("Rename {} to {}", old, new);
+    if worktree.commit(&message)? == CommitOutcome::NothingToCommit {
+        return Err(AppError::invalid("Renaming the article changed nothing"));
+    }
+    worktree.push()?;
+
+    let summary = if strategy.mode == PublishMode::DirectPush {
+        format!("Renamed {} to {} on {}", old, new, base)
+    } else {
+        let mut body = format!("- `{}` → `{}`", old_path, new_path);
+        for image in &images {
+            body.push_str(&format!("\n- `{}`", image));
+        }
+        if let Some(redirect) = &redirect {
+            body.push_str(&format!("\n\n{}", redirect));
+        }
+        let request = PullRequest {
+            title: message.clone(),
+            body,
+            reviewers: &strategy.reviewers,
+        };
+        let (url, _) = request.open(root, &branch, base)?;
+        if strategy.mode == PublishMode::AutoMerge {
+            match publish::merge_pull_request(root, &url, strategy.merge_method) {
+                Ok(()) => format!("{} (merged {})", message, url),
+                Err(e) => format!(
+                    "{}: PR {} could not be merged automatically: {}",
+                    message, url, e
+                ),
+            }
+        } else {
+            format!("{}: PR {} opened for review", message, url)
+        }
+    };
+
+    Ok(SlugRename {
+        old_path,
+        new_path,
+        images,
+        redirect,
+        message: summary,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn generated_slugs_are_safe() {
+        assert_eq!(generate_slug("../../etc/passwd"), "etc-passwd");
+        assert_eq!(generate_slug("a/b\\c"), "a-b-c");
+        assert_eq!(generate_slug("  Hello   World  "), "hello-world");
+        assert_eq!(generate_slug(".."), "untitled");
+        assert_eq!(generate_slug(""), "untitled");
+        for title in ["../x", "a/b", "with spaces", "Über", "日本語"] {
+            assert!(check_slug(&generate_slug(title)).is_ok(), "{}", title);
+        }
+    }
+
+    #[test]
+    fn titles_are_transliterated() {
+        assert_eq!(generate_slug("Über Café"), "uber-cafe");
+        assert_eq!(generate_slug("Héllo, Wörld!"), "hello-world");
+        assert_eq!(generate_slug("日本"), "ri-ben");
+    }
+
+    #[test]
+    fn long_titles_are_cut_at_a_word() {
+        let slug = generate_slug(&"word ".repeat(40));
+        assert!(slug.len() <= MAX_SLUG_LENGTH);
+        assert!(slug.ends_with("word"));
+    }
+
+    #[test]
+    fn unsafe_slugs_are_refused() {
+        for slug in ["", "..", "../x", "a/b", "a\\b", "a b", "é", "-x", "a.md"] {
+            assert!(check_slug(slug).is_err(), "{:?}", slug);
+        }
+        assert!(check_slug("good-slug_2").is_ok());
+    }
+
+    #[test]
+    fn taken_slugs_get_a_suffix() {
+        let taken: HashSet<String> = ["hello", "hello-2", "other"]
+            .into_iter()
+            .map(String::from)
+            .collect();
+        assert_eq!(unique_slug(&taken, "Hello", None), "hello-3");
+        assert_eq!(unique_slug(&taken, "Hello", Some("hello")), "hello");
+        assert_eq!(unique_slug(&taken, "Hello", Some("hello-2")), "hello-2");
+        assert_eq!(unique_slug(&taken, "Fresh", None), "fresh");
+        assert_eq!(unique_slug(&HashSet::new(), "Hello", None), "hello");
+    }
+}
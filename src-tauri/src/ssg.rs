@@ -0,0 +1,886 @@
+use chrono::SecondsFormat;
+use serde::{Deserialize, Serialize};
+use serde_yaml::{Mapping, Value};
+
+use crate::database::ExtraFields;
+use crate::error::{AppError, AppResult};
+use crate::frontmatter::{parse_timestamp, write_yaml};
+use crate::layout::RepoLayout;
+
+/// Static site generator whose conventions published articles follow
+#[derive(Debug, Clone, Copy, Default, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub enum SiteGenerator {
+    /// The original Next.js site: YAML, `{slug}.md`, images under `/images`
+    #[default]
+    Nextjs,
+    Hugo,
+    Jekyll,
+    Astro,
+    Zola,
+    Eleventy,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum FrontmatterFormat {
+    /// `---` delimited YAML
+    Yaml,
+    /// `+++` delimited TOML
+    Toml,
+}
+
+impl FrontmatterFormat {
+    fn delimiter(self) -> &'static str {
+        match self {
+            FrontmatterFormat::Yaml => "---",
+            FrontmatterFormat::Toml => "+++",
+        }
+    }
+
+    /// What separates a key from its value
+    pub fn separator(self) -> &'static str {
+        match self {
+            FrontmatterFormat::Yaml => ":",
+            FrontmatterFormat::Toml => "=",
+        }
+    }
+
+    /// Index of the closing delimiter, if `lines` start with frontmatter
+    pub fn end(self, lines: &[String]) -> Option<usize> {
+        if lines.first()?.trim_end() != self.delimiter() {
+            return None;
+        }
+        lines
+            .iter()
+            .skip(1)
+            .position(|line| line.trim_end() == self.delimiter())
+            .map(|index| index + 1)
+    }
+
+    /// Index of the top-level `key` line in the frontmatter ending at `end`
+    pub fn find_key(self, lines: &[String], end: usize, key: &str) -> Option<usize> {
+        (1..end).find(|&index| {
+            lines[index]
+                .strip_prefix(key)
+                .is_some_and(|rest| rest.trim_start().starts_with(self.separator()))
+        })
+    }
+
+    /// A top-level `key` line, e.g. `draft: true` or `draft = true`
+    pub fn line(self, key: &str, value: &str) -> String {
+        match self {
+            FrontmatterFormat::Yaml => format!("{}: {}", key, value),
+            FrontmatterFormat::Toml => format!("{} = {}", key, value),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum DateStyle {
+    /// `2026-01-15`
+    Date,
+    /// RFC 3339, `2026-01-15T09:30:00Z`
+    DateTime,
+    /// `2026-01-15 09:30:00 +0000`
+    Jekyll,
+}
+
+/// How the article file is named inside the articles directory
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum ArticleNaming {
+    /// `{slug}.md`
+    Slug,
+    /// `{YYYY-MM-DD}-{slug}.md`
+    DatedSlug,
+    /// `{slug}/index.md`, with the article's images next to it
+    Bundle,
+}
+
+/// Frontmatter keys for each field. Dotted keys nest (`extra.cover`);
+/// `None` leaves the field out.
+#[derive(Debug, Clone)]
+pub struct FieldNames {
+    pub title: &'static str,
+    pub date: &'static str,
+    pub updated: Option<&'static str>,
+    pub tags: &'static str,
+    pub description: &'static str,
+    pub cover: &'static str,
+    /// Write the cover as a one-item list (Hugo's `images`)
+    pub cover_as_list: bool,
+    pub cover_position: Option<&'static str>,
+    /// Written with the cover when the draft has alt text for it
+    pub cover_alt: &'static str,
+}
+
+/// Conventions for writing a published article
+#[derive(Debug, Clone)]
+pub struct SiteProfile {
+    pub format: FrontmatterFormat,
+    pub fields: FieldNames,
+    pub date_style: DateStyle,
+    pub naming: ArticleNaming,
+    /// Fields with fixed values written first, e.g. Jekyll's `layout: post`
+    pub fixed: &'static [(&'static str, &'static str)],
+    /// Table custom fields are written under (Zola's `extra`); `None` writes
+    /// them at the top level
+    pub custom_table: Option<&'static str>,
+    /// Keys the generator reads at the top level, kept out of `custom_table`
+    pub top_level_keys: &'static [&'static str],
+    /// Whether raw HTML in Markdown reaches the page, so inline images can be
+    /// written as `<img>` tags with `srcset` (Hugo drops it by default)
+    pub html_images: bool,
+    /// Frontmatter list of old URLs that redirect to the article, if the
+    /// generator (or its usual plugin) reads one
+    pub aliases_key: Option<&'static str>,
+    /// Frontmatter key and value that keep an article off the site
+    pub draft_flag: (&'static str, &'static str),
+}
+
+impl SiteGenerator {
+    pub fn profile(self) -> SiteProfile {
+        match self {
+            SiteGenerator::Nextjs => SiteProfile {
+                format: FrontmatterFormat::Yaml,
+                fields: FieldNames {
+                    title: "title",
+                    date: "date",
+                    updated: Some("last_updated"),
+                    tags: "tags",
+                    description: "description",
+                    cover: "cover",
+                    cover_as_list: false,
+                    cover_position: Some("cover_position"),
+                    cover_alt: "cover_alt",
+                },
+                date_style: DateStyle::Date,
+                naming: ArticleNaming::Slug,
+                fixed: &[],
+                custom_table: None,
+                top_level_keys: &[],
+                html_images: false,
+                aliases_key: None,
+                draft_flag: ("draft", "true"),
+            },
+            SiteGenerator::Hugo => SiteProfile {
+                format: FrontmatterFormat::Toml,
+                fields: FieldNames {
+                    title: "title",
+                    date: "date",
+                    updated: Some("lastmod"),
+                    tags: "tags",
+                    description: "description",
+                    cover: "images",
+                    cover_as_list: true,
+                    cover_position: None,
+                    cover_alt: "cover_alt",
+                },
+                date_style: DateStyle::DateTime,
+                naming: ArticleNaming::Bundle,
+                fixed: &[],
+                custom_table: None,
+                top_level_keys: &[],
+                html_images: false,
+                aliases_key: Some("aliases"),
+                draft_flag: ("draft", "true"),
+            },
+            SiteGenerator::Jekyll => SiteProfile {
+                format: FrontmatterFormat::Yaml,
+                fields: FieldNames {
+                    title: "title",
+                    date: "date",
+                    updated: Some("last_modified_at"),
+                    tags: "tags",
+                    description: "description",
+                    cover: "image",
+                    cover_as_list: false,
+                    cover_position: None,
+                    cover_alt: "image_alt",
+                },
+                date_style: DateStyle::Jekyll,
+                naming: ArticleNaming::DatedSlug,
+                fixed: &[("layout", "post")],
+                custom_table: None,
+                top_level_keys: &[],
+                html_images: true,
+                aliases_key: Some("redirect_from"),
+                draft_flag: ("published", "false"),
+            },
+            SiteGenerator::Astro => SiteProfile {
+                format: FrontmatterFormat::Yaml,
+                fields: FieldNames {
+                    title: "title",
+                    date: "pubDate",
+                    updated: Some("updatedDate"),
+                    tags: "tags",
+                    description: "description",
+                    cover: "heroImage",
+                    cover_as_list: false,
+                    cover_position: None,
+                    cover_alt: "heroImageAlt",
+                },
+                date_style: DateStyle::Date,
+                naming: ArticleNaming::Slug,
+                fixed: &[],
+                custom_table: None,
+                top_level_keys: &[],
+                html_images: true,
+                aliases_key: None,
+                draft_flag: ("draft", "true"),
+            },
+            SiteGenerator::Zola => SiteProfile {
+                format: FrontmatterFormat::Toml,
+                fields: FieldNames {
+                    title: "title",
+                    date: "date",
+                    updated: Some("updated"),
+                    tags: "taxonomies.tags",
+                    description: "description",
+                    cover: "extra.cover",
+                    cover_as_list: false,
+                    cover_position: Some("extra.cover_position"),
+                    cover_alt: "extra.cover_alt",
+                },
+                date_style: DateStyle::Date,
+                naming: ArticleNaming::Slug,
+                fixed: &[],
+                custom_table: Some("extra"),
+                top_level_keys: ZOLA_PAGE_KEYS,
+                html_images: true,
+                aliases_key: Some("aliases"),
+                draft_flag: ("draft", "true"),
+            },
+            SiteGenerator::Eleventy => SiteProfile {
+                format: FrontmatterFormat::Yaml,
+                fields: FieldNames {
+                    title: "title",
+                    date: "date",
+                    updated: None,
+                    tags: "tags",
+                    description: "description",
+                    cover: "image",
+                    cover_as_list: false,
+                    cover_position: None,
+                    cover_alt: "image_alt",
+                },
+                date_style: DateStyle::Date,
+                naming: ArticleNaming::Slug,
+                fixed: &[],
+                custom_table: None,
+                top_level_keys: &[],
+                html_images: true,
+                aliases_key: None,
+                draft_flag: ("draft", "true"),
+            },
+        }
+    }
+}
+
+/// Front matter keys Zola accepts outside `[extra]`
+const ZOLA_PAGE_KEYS: &[&str] = &[
+    "title",
+    "description",
+    "date",
+    "updated",
+    "weight",
+    "draft",
+    "slug",
+    "path",
+    "aliases",
+    "authors",
+    "in_search_index",
+    "template",
+    "render",
+    "taxonomies",
+    "extra",
+];
+
+/// The metadata written to a published article's frontmatter
+#[derive(Debug, Clone)]
+pub struct PostMeta<'a> {
+    pub title: &'a str,
+    pub date: &'a str,
+    pub updated: Option<&'a str>,
+    pub tags: &'a [String],
+    pub description: &'a str,
+    /// Already rewritten to the published image URL; empty for none
+    pub cover: &'a str,
+    pub cover_position: Option<f64>,
+    pub cover_alt: &'a str,
+    /// Custom fields, written after the fields above unless they clash
+    pub extra: &'a ExtraFields,
+}
+
+/// A frontmatter value before it is written as YAML or TOML
+enum Field {
+    Text(String),
+    Date(DateStyle, String),
+    Number(f64),
+    List(Vec<String>),
+    /// A custom field; skipped if the key is already taken
+    Custom(serde_json::Value),
+}
+
+impl SiteProfile {
+    /// How drafts synced to the drafts branch are written, whatever the site
+    /// uses: Nibandh's own field names in YAML
+    pub fn drafts() -> SiteProfile {
+        SiteGenerator::Nextjs.profile()
+    }
+
+    /// Where the article for `slug` goes, relative to the repo root
+    pub fn article_path(&self, layout: &RepoLayout, slug: &str, date: &str) -> String {
+        match self.naming {
+            ArticleNaming::Slug => layout.article_path(slug),
+            ArticleNaming::DatedSlug => {
+                layout.article_path(&format!("{}-{}", format_date(DateStyle::Date, date), slug))
+            }
+            ArticleNaming::Bundle => format!("{}/index.md", layout.bundle_dir(slug)),
+        }
+    }
+
+    /// Where the article's images go, relative to the repo root
+    pub fn images_dir(&self, layout: &RepoLayout, slug: &str) -> String {
+        match self.naming {
+            ArticleNaming::Bundle => layout.bundle_dir(slug),
+            _ => layout.images_dir.clone(),
+        }
+    }
+
+    /// URL prefix the article uses for its images; empty for bare file names
+    pub fn images_url<'a>(&self, layout: &'a RepoLayout) -> &'a str {
+        match self.naming {
+            ArticleNaming::Bundle => "",
+            _ => &layout.images_url,
+        }
+    }
+
+    pub fn image_url(&self, layout: &RepoLayout, filename: &str) -> String {
+        match self.naming {
+            ArticleNaming::Bundle => filename.to_string(),
+            _ => layout.image_url(filename),
+        }
+    }
+
+    /// Whether images are also copied to the layout's public images directory
+    pub fn copies_to_public(&self) -> bool {
+        self.naming != ArticleNaming::Bundle
+    }
+
+    /// Render the frontmatter block, including its closing delimiter and a
+    /// blank line
+    pub fn render_frontmatter(&self, post: &PostMeta) -> AppResult<String> {
+        let fields = self.fields(post);
+        match self.format {
+            FrontmatterFormat::Yaml => Ok(render_yaml(fields)),
+            FrontmatterFormat::Toml => render_toml(fields),
+        }
+    }
+
+    fn fields(&self, post: &PostMeta) -> Vec<(String, Field)> {
+        let names = &self.fields;
+        let mut fields: Vec<(String, Field)> = self
+            .fixed
+            .iter()
+            .map(|(key, value)| (key.to_string(), Field::Text(value.to_string())))
+            .collect();
+
+        fields.push((names.title.to_string(), Field::Text(post.title.to_string())));
+        fields.push((
+            names.date.to_string(),
+            Field::Date(self.date_style, post.date.to_string()),
+        ));
+        if let (Some(key), Some(updated)) = (names.updated, post.updated) {
+            fields.push((
+                key.to_string(),
+                Field::Date(DateStyle::DateTime, updated.to_string()),
+            ));
+        }
+        fields.push((names.tags.to_string(), Field::List(post.tags.to_vec())));
+        fields.push((
+            names.description.to_string(),
+            Field::Text(post.description.to_string()),
+        ));
+        if !post.cover.is_empty() {
+            let cover = post.cover.to_string();
+            fields.push((
+                names.cover.to_string(),
+                if names.cover_as_list {
+                    Field::List(vec![cover])
+                } else {
+                    Field::Text(cover)
+                },
+            ));
+            if let Some(key) = names.cover_position {
+                fields.push((
+                    key.to_string(),
+                    Field::Number(post.cover_position.unwrap_or(50.0)),
+                ));
+            }
+            if !post.cover_alt.is_empty() {
+                fields.push((
+                    names.cover_alt.to_string(),
+                    Field::Text(post.cover_alt.to_string()),
+                ));
+            }
+        }
+
+        for (key, value) in post.extra {
+            fields.push((self.custom_key(key), Field::Custom(value.clone())));
+        }
+
+        fields
+    }
+
+    fn custom_key(&self, key: &str) -> String {
+        let top = key.split('.').next().unwrap_or(key);
+        match self.custom_table {
+            Some(table) if top != table && !self.top_level_keys.contains(&top) => {
+                format!("{}.{}", table, key)
+            }
+            _ => key.to_string(),
+        }
+    }
+}
+
+/// `value` (RFC 3339 or `YYYY-MM-DD`) in `style`, or as given if unparseable
+fn format_date(style: DateStyle, value: &str) -> String {
+    let Some(time) = parse_timestamp(value) else {
+        return value.to_string();
+    };
+    match style {
+        DateStyle::Date => time.format("%Y-%m-%d").to_string(),
+        DateStyle::DateTime => time.to_rfc3339_opts(SecondsFormat::AutoSi, true),
+        DateStyle::Jekyll => time.format("%Y-%m-%d %H:%M:%S %z").to_string(),
+    }
+}
+
+fn render_yaml(fields: Vec<(String, Field)>) -> String {
+    let mut root = Mapping::new();
+    for (key, field) in fields {
+        let value = match field {
+            Field::Text(text) => Value::String(text),
+            Field::Date(style, date) => Value::String(format_date(style, &date)),
+            Field::Number(n) if n.fract() == 0.0 => Value::from(n as i64),
+            Field::Number(n) => Value::from(n),
+            Field::List(items) => Value::Sequence(items.into_iter().map(Value::String).collect()),
+            Field::Custom(value) => {
+                if let Ok(value) = serde_yaml::to_value(value) {
+                    insert_yaml(&mut root, &key, value, false);
+                }
+                continue;
+            }
+        };
+        insert_yaml(&mut root, &key, value, true);
+    }
+
+    format!("---\n{}---\n\n", write_yaml(&root))
+}
+
+/// Insert `value` at the dotted `key`. Without `replace`, a key that is
+/// already set is left alone.
+fn insert_yaml(mapping: &mut Mapping, key: &str, value: Value, replace: bool) {
+    match key.split_once('.') {
+        None => {
+            if replace || !mapping.contains_key(key) {
+                mapping.insert(Value::from(key), value);
+            }
+        }
+        Some((parent, rest)) => {
+            let child = mapping
+                .entry(Value::from(parent))
+                .or_insert_with(|| Value::Mapping(Mapping::new()));
+            if let Value::Mapping(child) = child {
+                insert_yaml(child, rest, value, replace);
+            }
+        }
+    }
+}
+
+fn render_toml(fields: Vec<(String, Field)>) -> AppResult<String> {
+    let mut root = toml::Table::new();
+    for (key, field) in fields {
+        let value = match field {
+            Field::Text(text) => toml::Value::String(text),
+            // Native TOML dates where the value parses as one
+            Field::Date(style, date) => {
+                let formatted = format_date(style, &date);
+                match formatted.parse::<toml::value::Datetime>() {
+                    Ok(datetime) => toml::Value::Datetime(datetime),
+                    Err(_) => toml::Value::String(formatted),
+                }
+            }
+            Field::Number(n) if n.fract() == 0.0 => toml::Value::Integer(n as i64),
+            Field::Number(n) => toml::Value::Float(n),
+            Field::List(items) => {
+                toml::Value::Array(items.into_iter().map(toml::Value::String).collect())
+            }
+            // TOML has no null, so null custom fields are dropped
+            Field::Custom(value) => {
+                if let Ok(value) = toml::Value::try_from(value) {
+                    insert_toml(&mut root, &key, value, false);
+                }
+                continue;
+            }
+        };
+        insert_toml(&mut root, &key, value, true);
+    }
+
+    let toml = toml::to_string(&root)
+        .map_err(|e| AppError::invalid("Failed to write frontmatter").with_details(e))?;
+    Ok(format!("+++\n{}+++\n\n", toml))
+}
+
+fn insert_toml(table: &mut toml::Table, key: &str, value: toml::Value, replace: bool) {
+    match key.split_once('.') {
+        None => {
+            if replace || !table.contains_key(key) {
+                table.insert(key.to_string(), value);
+            }
+        }
+        Some((parent, rest)) => {
+            let child = table
+                .entry(parent)
+                .or_insert_with(|| toml::Value::Table(toml::Table::new()));
+            if let toml::Value::Table(child) = child {
+                insert_toml(child, rest, value, replace);
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::frontmatter::{parse_article, read_tags, rewrite_tags};
+
+    fn sample(generator: SiteGenerator) -> (String, String) {
+        let tags = vec!["Rust".to_string(), "Static sites".to_string()];
+        let extra = ExtraFields::new();
+        let post = PostMeta {
+            title: "Hello: \"World\"",
+            date: "2026-01-15",
+            updated: Some("2026-01-16T09:30:00+00:00"),
+            tags: &tags,
+            description: "A first post",
+            cover: "/images/cover_hello.webp",
+            cover_position: Some(40.0),
+            cover_alt: "A sunrise",
+            extra: &extra,
+        };
+        let profile = generator.profile();
+        let frontmatter = profile.render_frontmatter(&post).unwrap();
+        let path = profile.article_path(&RepoLayout::default(), "hello", post.date);
+        (path, frontmatter)
+    }
+
+    #[test]
+    fn nextjs_sample_post() {
+        let (path, frontmatter) = sample(SiteGenerator::Nextjs);
+        assert_eq!(path, "content/articles/hello.md");
+        assert_eq!(
+            frontmatter,
+            "---\n\
+             title: \"Hello: \\\"World\\\"\"\n\
+             date: \"2026-01-15\"\n\
+             last_updated: \"2026-01-16T09:30:00Z\"\n\
+             tags: [\"Rust\", \"Static sites\"]\n\
+             description: \"A first post\"\n\
+             cover: \"/images/cover_hello.webp\"\n\
+             cover_position: 40\n\
+             cover_alt: \"A sunrise\"\n\
+             ---\n\n"
+        );
+    }
+
+    #[test]
+    fn hugo_sample_post() {
+        let (path, frontmatter) = sample(SiteGenerator::Hugo);
+        assert_eq!(path, "content/articles/hello/index.md");
+        assert_eq!(
+            frontmatter,
+            "+++\n\
+             title = 'Hello: \"World\"'\n\
+             date = 2026-01-15T00:00:00Z\n\
+             lastmod = 2026-01-16T09:30:00Z\n\
+             tags = [\"Rust\", \"Static sites\"]\n\
+             description = \"A first post\"\n\
+             images = [\"/images/cover_hello.webp\"]\n\
+             cover_alt = \"A sunrise\"\n\
+             +++\n\n"
+        );
+    }
+
+    #[test]
+    fn jekyll_sample_post() {
+        let (path, frontmatter) = sample(SiteGenerator::Jekyll);
+        assert_eq!(path, "content/articles/2026-01-15-hello.md");
+        assert_eq!(
+            frontmatter,
+            "---\n\
+             layout: \"post\"\n\
+             title: \"Hello: \\\"World\\\"\"\n\
+             date: \"2026-01-15 00:00:00 +0000\"\n\
+             last_modified_at: \"2026-01-16T09:30:00Z\"\n\
+             tags: [\"Rust\", \"Static sites\"]\n\
+             description: \"A first post\"\n\
+             image: \"/images/cover_hello.webp\"\n\
+             image_alt: \"A sunrise\"\n\
+             ---\n\n"
+        );
+    }
+
+    #[test]
+    fn astro_sample_post() {
+        let (path, frontmatter) = sample(SiteGenerator::Astro);
+        assert_eq!(path, "content/articles/hello.md");
+        assert_eq!(
+            frontmatter,
+            "---\n\
+             title: \"Hello: \\\"World\\\"\"\n\
+             pubDate: \"2026-01-15\"\n\
+             updatedDate: \"2026-01-16T09:30:00Z\"\n\
+             tags: [\"Rust\", \"Static sites\"]\n\
+             description: \"A first post\"\n\
+             heroImage: \"/images/cover_hello.webp\"\n\
+             heroImageAlt: \"A sunrise\"\n\
+             ---\n\n"
+        );
+    }
+
+    #[test]
+    fn zola_sample_post() {
+        let (path, frontmatter) = sample(SiteGenerator::Zola);
+        assert_eq!(path, "content/articles/hello.md");
+        assert_eq!(
+            frontmatter,
+            "+++\n\
+             title = 'Hello: \"World\"'\n\
+             date = 2026-01-15\n\
+             updated = 2026-01-16T09:30:00Z\n\
+             description = \"A first post\"\n\
+             \n\
+             [taxonomies]\n\
+             tags = [\"Rust\", \"Static sites\"]\n\
+             \n\
+             [extra]\n\
+             cover = \"/images/cover_hello.webp\"\n\
+             cover_position = 40\n\
+             cover_alt = \"A sunrise\"\n\
+             +++\n\n"
+        );
+    }
+
+    #[test]
+    fn eleventy_sample_post() {
+        let (path, frontmatter) = sample(SiteGenerator::Eleventy);
+        assert_eq!(path, "content/articles/hello.md");
+        assert_eq!(
+            frontmatter,
+            "---\n\
+             title: \"Hello: \\\"World\\\"\"\n\
+             date: \"2026-01-15\"\n\
+             tags: [\"Rust\", \"Static sites\"]\n\
+             description: \"A first post\"\n\
+             image: \"/images/cover_hello.webp\"\n\
+             image_alt: \"A sunrise\"\n\
+             ---\n\n"
+        );
+    }
+
+    const GENERATORS: [SiteGenerator; 6] = [
+        SiteGenerator::Nextjs,
+        SiteGenerator::Hugo,
+        SiteGenerator::Jekyll,
+        SiteGenerator::Astro,
+        SiteGenerator::Zola,
+        SiteGenerator::Eleventy,
+    ];
+
+    #[test]
+    fn frontmatter_reads_back() {
+        let tags = vec!["a\\b".to_string()];
+        let extra = ExtraFields::new();
+        let post = PostMeta {
+            title: "Line\nbreak: \"quoted\" \u{2028} end",
+            date: "2026-01-15",
+            updated: None,
+            tags: &tags,
+            description: "",
+            cover: "",
+            cover_position: None,
+            cover_alt: "",
+            extra: &extra,
+        };
+        for generator in GENERATORS {
+            let profile = generator.profile();
+            let frontmatter = profile.render_frontmatter(&post).unwrap();
+            let article = format!("{}Body", frontmatter);
+            let (meta, body) = parse_article(&article, &profile).unwrap();
+            assert_eq!(meta.title, post.title, "{:?}", generator);
+            assert_eq!(meta.date, "2026-01-15", "{:?}", generator);
+            assert_eq!(meta.tags, tags, "{:?}", generator);
+            assert_eq!(body, "Body");
+        }
+    }
+
+    #[test]
+    fn every_profile_reads_back_its_fields() {
+        let tags = vec!["Rust".to_string(), "Static sites".to_string()];
+        let extra: ExtraFields = serde_json::from_str(r#"{"series": "Intro"}"#).unwrap();
+        let post = PostMeta {
+            title: "Hello",
+            date: "2026-01-15",
+            updated: Some("2026-01-16T09:30:00Z"),
+            tags: &tags,
+            description: "A first post",
+            cover: "/images/cover_hello.webp",
+            cover_position: Some(40.0),
+            cover_alt: "A sunrise",
+            extra: &extra,
+        };
+        for generator in GENERATORS {
+            let profile = generator.profile();
+            let article = format!("{}Body", profile.render_frontmatter(&post).unwrap());
+            let (meta, _) = parse_article(&article, &profile).unwrap();
+            assert_eq!(meta.title, "Hello", "{:?}", generator);
+            assert_eq!(meta.date, "2026-01-15", "{:?}", generator);
+            assert_eq!(meta.tags, tags, "{:?}", generator);
+            assert_eq!(meta.description, "A first post", "{:?}", generator);
+            assert_eq!(meta.cover, post.cover, "{:?}", generator);
+            assert_eq!(meta.cover_alt, "A sunrise", "{:?}", generator);
+            let position = profile.fields.cover_position.map(|_| 40.0);
+            assert_eq!(meta.cover_position, position, "{:?}", generator);
+            let updated = meta.last_updated.as_deref().and_then(parse_timestamp);
+            let expected = profile
+                .fields
+                .updated
+                .and(parse_timestamp("2026-01-16T09:30:00Z"));
+            assert_eq!(updated, expected, "{:?}", generator);
+            assert_eq!(meta.extra, extra, "{:?}", generator);
+        }
+    }
+
+    #[test]
+    fn every_profile_rewrites_its_tags() {
+        let tags = vec!["rsut".to_string(), "Web".to_string()];
+        let extra = ExtraFields::new();
+        let post = PostMeta {
+            title: "Hello",
+            date: "2026-01-15",
+            updated: None,
+            tags: &tags,
+            description: "A first post",
+            cover: "",
+            cover_position: None,
+            cover_alt: "",
+            extra: &extra,
+        };
+        for generator in GENERATORS {
+            let profile = generator.profile();
+            let article = format!("{}Body\n", profile.render_frontmatter(&post).unwrap());
+            let rewritten = rewrite_tags(&article, &profile, |tags| {
+                tags.iter()
+                    .map(|tag| {
+                        if tag == "rsut" {
+                            "Rust".to_string()
+                        } else {
+                            tag.clone()
+                        }
+                    })
+                    .collect()
+            })
+            .unwrap();
+            assert_eq!(
+                read_tags(&rewritten, &profile),
+                ["Rust", "Web"],
+                "{:?}",
+                generator
+            );
+            let (meta, body) = parse_article(&rewritten, &profile).unwrap();
+            assert_eq!(meta.description, "A first post", "{:?}", generator);
+            assert_eq!(body, "Body\n");
+        }
+    }
+
+    #[test]
+    fn multi_line_toml_tags_are_replaced() {
+        let profile = SiteGenerator::Hugo.profile();
+        let article =
+            "+++\ntitle = \"Hello\"\ntags = [\n  \"a\",\n  \"b\",\n]\ndraft = false\n+++\nBody\n";
+        let rewritten = rewrite_tags(article, &profile, |_| vec!["c".to_string()]).unwrap();
+        assert_eq!(
+            rewritten,
+            "+++\ntitle = \"Hello\"\ntags = [\"c\"]\ndraft = false\n+++\nBody\n"
+        );
+    }
+
+    #[test]
+    fn custom_fields_follow_managed_fields() {
+        let tags = vec!["Rust".to_string()];
+        let extra: ExtraFields = serde_json::from_str(
+            r#"{"title": "Ignored", "series": "Intro", "draft": true, "author": null}"#,
+        )
+        .unwrap();
+        let post = PostMeta {
+            title: "Hello",
+            date: "2026-01-15",
+            updated: None,
+            tags: &tags,
+            description: "A first post",
+            cover: "",
+            cover_position: None,
+            cover_alt: "",
+            extra: &extra,
+        };
+
+        let frontmatter = SiteGenerator::Nextjs
+            .profile()
+            .render_frontmatter(&post)
+            .unwrap();
+        assert_eq!(
+            frontmatter,
+            "---\n\
+             title: \"Hello\"\n\
+             date: \"2026-01-15\"\n\
+             tags: [\"Rust\"]\n\
+             description: \"A first post\"\n\
+             author: null\n\
+             draft: true\n\
+             series: \"Intro\"\n\
+             ---\n\n"
+        );
+
+        let frontmatter = SiteGenerator::Zola
+            .profile()
+            .render_frontmatter(&post)
+            .unwrap();
+        assert_eq!(
+            frontmatter,
+            "+++\n\
+             title = \"Hello\"\n\
+             date = 2026-01-15\n\
+             description = \"A first post\"\n\
+             draft = true\n\
+             \n\
+             [taxonomies]\n\
+             tags = [\"Rust\"]\n\
+             \n\
+             [extra]\n\
+             series = \"Intro\"\n\
+             +++\n\n"
+        );
+    }
+
+    #[test]
+    fn bundle_images_sit_next_to_the_article() {
+        let profile = SiteGenerator::Hugo.profile();
+        let layout = RepoLayout::default();
+        assert_eq!(
+            profile.images_dir(&layout, "hello"),
+            "content/articles/hello"
+        );
+        assert_eq!(profile.image_url(&layout, "cover.webp"), "cover.webp");
+        assert!(!profile.copies_to_public());
+    }
+}
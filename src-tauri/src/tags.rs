@@ -0,0 +1,216 @@
+use chrono::Utc;
+use serde::Serialize;
+use std::collections::HashMap;
+use std::fs;
+use std::path::Path;
+
+use crate::database::TagUsage;
+use crate::error::{AppError, AppResult};
+use crate::frontmatter::{read_tags, rewrite_tags};
+use crate::git::{CommitOutcome, GitBackend, Worktree};
+use crate::layout::RepoLayout;
+use crate::publish::{self, PublishMode, PublishStrategy, PullRequest};
+use crate::slug::list_articles;
+use crate::ssg::SiteProfile;
+
+/// A tag autocomplete entry, ranked by how often the tag is used
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct TagSuggestion {
+    pub name: String,
+    pub draft_count: i64,
+    pub article_count: i64,
+}
+
+/// Outcome of a tag rename or merge
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct TagUpdateResult {
+    pub drafts_updated: usize,
+    /// Changed articles, relative to the repo root
+    pub articles_updated: Vec<String>,
+    /// How the article changes were delivered, if any article changed
+    pub message: Option<String>,
+}
+
+/// Count how many published articles in the checkout at `root` use each tag
+/// (keyed case-insensitively)
+fn article_tag_counts(
+    root: &Path,
+    layout: &RepoLayout,
+    profile: &SiteProfile,
+) -> HashMap<String, (String, i64)> {
+    let mut counts: HashMap<String, (String, i64)> = HashMap::new();
+
+    for article in list_articles(root, layout) {
+        let content = match fs::read_to_string(RepoLayout::dir(root, &article.path(layout))) {
+            Ok(content) => content,
+            Err(_) => continue,
+        };
+        for tag in read_tags(&content, profile) {
+            counts
+                .entry(tag.to_lowercase())
+                .or_insert_with(|| (tag.clone(), 0))
+                .1 += 1;
+        }
+    }
+
+    counts
+}
+
+/// Merge local and published tag usage into suggestions matching `prefix`.
+/// Published articles are read from the checkout at `repo_path`, if given.
+pub fn suggest_tags(
+    usage: Vec<TagUsage>,
+    repo_path: Option<&Path>,
+    layout: &RepoLayout,
+    profile: &SiteProfile,
+    prefix: &str,
+    limit: usize,
+) -> Vec<TagSuggestion> {
+    let mut by_key: HashMap<String, TagSuggestion> = HashMap::new();
+
+    for tag in usage {
+        by_key.insert(
+            tag.name.to_lowercase(),
+            TagSuggestion {
+                name: tag.name,
+                draft_count: tag.count,
+                article_count: 0,
+            },
+        );
+    }
+
+    if let Some(root) = repo_path {
+        for (key, (name, count)) in article_tag_counts(root, layout, profile) {
+            by_key
+                .entry(key)
+                .or_insert(TagSuggestion {
+                    name,
+                    draft_count: 0,
+                    article_count: 0,
+                })
+                .article_count = count;
+        }
+    }
+
+    let prefix = prefix.trim().to_lowercase();
+    let mut suggestions: Vec<TagSuggestion> = by_key
+        .into_iter()
+        .filter(|(key, _)| key.starts_with(&prefix))
+        .map(|(_, suggestion)| suggestion)
+        .collect();
+
+    suggestions.sort_by(|a, b| {
+        (b.draft_count + b.article_count)
+            .cmp(&(a.draft_count + a.article_count))
+            .then_with(|| a.name.to_lowercase().cmp(&b.name.to_lowercase()))
+    });
+    suggestions.truncate(limit);
+    suggestions
+}
+
+/// Replace each of `sources` with `target` in the frontmatter of every
+/// published article, in one commit on the base branch delivered like
+/// `remove_orphan_images`. The user's checkout is left alone. Returns the
+/// changed articles (relative to the repo root) and a summary, or `None`
+/// when no article used the tags.
+pub fn rewrite_article_tags(
+    git: &dyn GitBackend,
+    repo_path: &Path,
+    layout: &RepoLayout,
+    profile: &SiteProfile,
+    strategy: &PublishStrategy,
+    sources: &[String],
+    target: &str,
+) -> AppResult<Option<(Vec<String>, String)>> {
+    let sources: Vec<String> = sources.iter().map(|s| s.trim().to_lowercase()).collect();
+    let target = target.trim();
+
+    let base = strategy.base_branch(layout);
+    let remote_base = layout.remote_branch(base);
+    let branch = match strategy.mode {
+        PublishMode::DirectPush => base.to_string(),
+        _ => format!("nibandh/update-tags-{}", Utc::now().format("%Y%m%d%H%M%S")),
+    };
+    let worktree = Worktree::open(
+        git,
+        repo_path,
+        &layout.remote,
+        &branch,
+        &[&remote_base, base],
+    )?;
+    let root = worktree.path();
+
+    let mut updated = Vec::new();
+    for article in list_articles(root, layout) {
+        let path = article.path(layout);
+        let file = RepoLayout::dir(root, &path);
+        let content = fs::read_to_string(&file)
+            .map_err(|e| AppError::io(format!("Failed to read {}", path), e))?;
+
+        let rewritten = rewrite_tags(&content, profile, |tags| {
+            let mut next: Vec<String> = Vec::new();
+            for tag in tags {
+                let tag = if sources.contains(&tag.to_lowercase()) {
+                    target.to_string()
+                } else {
+                    tag.clone()
+                };
+                if !next.iter().any(|t| t.eq_ignore_ascii_case(&tag)) {
+                    next.push(tag);
+                }
+            }
+            next
+        });
+
+        if let Some(rewritten) = rewritten {
+            fs::write(&file, rewritten)
+                .map_err(|e| AppError::io(format!("Failed to write {}", path), e))?;
+            updated.push(path);
+        }
+    }
+    if updated.is_empty() {
+        return Ok(None);
+    }
+
+    let message = format!(
+        "Change tags to \"{}\" in {} article{}",
+        target,
+        updated.len(),
+        if updated.len() == 1 { "" } else { "s" }
+    );
+    let updated_paths: Vec<&str> = updated.iter().map(String::as_str).collect();
+    worktree.add(&updated_paths)?;
+    if worktree.commit(&message)? == CommitOutcome::NothingToCommit {
+        return Ok(None);
+    }
+    worktree.push()?;
+
+    let summary = if strategy.mode == PublishMode::DirectPush {
+        format!("{} on {}", message, base)
+    } else {
+        let request = PullRequest {
+            title: message.clone(),
+            body: updated
+                .iter()
+                .map(|path| format!("- `{}`", path))
+                .collect::<Vec<_>>()
+                .join("\n"),
+            reviewers: &strategy.reviewers,
+        };
+        let (url, _) = request.open(root, &branch, base)?;
+        if strategy.mode == PublishMode::AutoMerge {
+            match publish::merge_pull_request(root, &url, strategy.merge_method) {
+                Ok(()) => format!("{} (merged {})", message, url),
+                Err(e) => format!(
+                    "{}: PR {} could not be merged automatically: {}",
+                    message, url, e
+                ),
+            }
+        } else {
+            format!("{}: PR {} opened for review", message, url)
+        }
+    };
+    Ok(Some((updated, summary)))
+}
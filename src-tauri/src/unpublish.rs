@@ -0,0 +1,194 @@
+use serde::{Deserialize, Serialize};
+use std::fs;
+use std::path::Path;
+
+use crate::error::{AppError, AppResult};
+use crate::git::{CommitOutcome, GitBackend, Worktree};
+use crate::layout::RepoLayout;
+use crate::orphans::find_orphans;
+use crate::publish::{self, PublishMode, PublishStrategy, PullRequest};
+use crate::slug::{check_slug, list_articles};
+use crate::ssg::{FrontmatterFormat, SiteProfile};
+
+/// How `unpublish_draft` takes an article off the site
+#[derive(Debug, Clone, Copy, Default, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub enum UnpublishMode {
+    /// Delete the article and the images no other file uses
+    #[default]
+    Delete,
+    /// Keep the file but set the generator's draft flag (`draft: true`)
+    MarkDraft,
+}
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Unpublished {
+    /// The article file, relative to the repo root
+    pub article: String,
+    /// Images deleted with it, relative to the repo root
+    pub deleted_images: Vec<String>,
+    /// Whether the takedown is on the base branch: pushed there directly, or
+    /// its pull request merged. Until then the article is still live.
+    pub landed: bool,
+    /// The pull request carrying the takedown, if one was opened
+    pub pull_request: Option<String>,
+    pub message: String,
+}
+
+/// Take the published article `slug` down in one commit, following the
+/// publish strategy like `remove_orphan_images`: delete it together with the
+/// images only it used, or flag it as a draft, as `strategy.unpublish` says.
+/// `reason` goes into the commit message and pull request.
+pub fn unpublish_article(
+    git: &dyn GitBackend,
+    repo_path: &Path,
+    layout: &RepoLayout,
+    profile: &SiteProfile,
+    strategy: &PublishStrategy,
+    slug: &str,
+    reason: &str,
+) -> AppResult<Unpublished> {
+    check_slug(slug)?;
+
+    let base = strategy.base_branch(layout);
+    let remote_base = layout.remote_branch(base);
+    let branch = match strategy.mode {
+        PublishMode::DirectPush => base.to_string(),
+        _ => format!("nibandh/unpublish-{}", slug),
+    };
+    let worktree = Worktree::open(
+        git,
+        repo_path,
+        &layout.remote,
+        &branch,
+        &[&remote_base, base],
+    )?;
+    let root = worktree.path();
+
+    let article = list_articles(root, layout)
+        .into_iter()
+        .find(|article| article.slug == slug)
+        .ok_or_else(|| {
+            AppError::not_found(format!("No published article uses the slug \"{}\"", slug))
+        })?;
+    let path = article.path(layout);
+    let file = RepoLayout::dir(root, &path);
+    let text = fs::read_to_string(&file)
+        .map_err(|e| AppError::io(format!("Failed to read {}", path), e))?;
+
+    let mut deleted_images = Vec::new();
+    match strategy.unpublish {
+        UnpublishMode::Delete if article.bundle => {
+            let dir = layout.bundle_dir(&article.name);
+            let entries = fs::read_dir(RepoLayout::dir(root, &dir))
+                .map_err(|e| AppError::io(format!("Failed to read {}", dir), e))?;
+            for entry in entries.flatten() {
+                let name = entry.file_name().to_string_lossy().into_owned();
+                if name != "index.md" {
+                    deleted_images.push(format!("{}/{}", dir, name));
+                }
+            }
+            fs::remove_dir_all(RepoLayout::dir(root, &dir))
+                .map_err(|e| AppError::io(format!("Failed to delete {}", dir), e))?;
+        }
+        UnpublishMode::Delete => {
+            fs::remove_file(&file)
+                .map_err(|e| AppError::io(format!("Failed to delete {}", path), e))?;
+            // With the article gone, its images are the ones nothing else mentions
+            let (orphans, _) = find_orphans(root, layout)?;
+            for orphan in orphans {
+                let name = orphan.path.rsplit('/').next().unwrap_or(&orphan.path);
+                if text.contains(name) || text.contains(&name.replace(' ', "%20")) {
+                    fs::remove_file(RepoLayout::dir(root, &orphan.path)).map_err(|e| {
+                        AppError::io(format!("Failed to delete {}", orphan.path), e)
+                    })?;
+                    deleted_images.push(orphan.path);
+                }
+            }
+        }
+        UnpublishMode::MarkDraft => {
+            fs::write(&file, set_draft_flag(&text, profile)?)
+                .map_err(|e| AppError::io(format!("Failed to write {}", path), e))?;
+        }
+    }
+    deleted_images.sort();
+
+    let title = format!("Unpublish {}", slug);
+    let message = if reason.trim().is_empty() {
+        title.clone()
+    } else {
+        format!("{}\n\n{}", title, reason.trim())
+    };
+    worktree.add(&layout.publish_paths(root))?;
+    if worktree.commit(&message)? == CommitOutcome::NothingToCommit {
+        return Err(AppError::invalid(format!(
+            "{} is already unpublished",
+            path
+        )));
+    }
+    worktree.push()?;
+
+    let (summary, landed, pull_request) = if strategy.mode == PublishMode::DirectPush {
+        (format!("Unpublished {} from {}", slug, base), true, None)
+    } else {
+        let mut body = format!("- `{}`", path);
+        for image in &deleted_images {
+            body.push_str(&format!("\n- `{}`", image));
+        }
+        if !reason.trim().is_empty() {
+            body.push_str(&format!("\n\n{}", reason.trim()));
+        }
+        let request = PullRequest {
+            title: title.clone(),
+            body,
+            reviewers: &strategy.reviewers,
+        };
+        let (url, _) = request.open(root, &branch, base)?;
+        let (summary, landed) = if strategy.mode == PublishMode::AutoMerge {
+            match publish::merge_pull_request(root, &url, strategy.merge_method) {
+                Ok(()) => (format!("{} (merged {})", title, url), true),
+                Err(e) => (
+                    format!(
+                        "{}: PR {} could not be merged automatically: {}",
+                        title, url, e
+                    ),
+                    false,
+                ),
+            }
+        } else {
+            (format!("{}: PR {} opened for review", title, url), false)
+        };
+        (summary, landed, Some(url))
+    };
+
+    Ok(Unpublished {
+        article: path,
+        deleted_images,
+        landed,
+        pull_request,
+        message: summary,
+    })
+}
+
+/// Set the profile's draft flag in the frontmatter of `article`, replacing
+/// the key if it is there and adding it at the top otherwise. Articles not
+/// written by Nibandh may use YAML on a TOML site (Hugo reads both).
+fn set_draft_flag(article: &str, profile: &SiteProfile) -> AppResult<String> {
+    let (key, value) = profile.draft_flag;
+    let mut lines: Vec<String> = article.lines().map(str::to_string).collect();
+    let (format, end) = [profile.format, FrontmatterFormat::Yaml]
+        .into_iter()
+        .find_map(|format| Some((format, format.end(&lines)?)))
+        .ok_or_else(|| AppError::invalid("The article has no frontmatter to flag as a draft"))?;
+    match format.find_key(&lines, end, key) {
+        Some(index) => lines[index] = format.line(key, value),
+        None => lines.insert(1, format.line(key, value)),
+    }
+
+    let mut updated = lines.join("\n");
+    if article.ends_with('\n') {
+        updated.push('\n');
+    }
+    Ok(updated)
+}
@@ -0,0 +1,349 @@
+use regex::Regex;
+use serde::{Deserialize, Serialize};
+use std::collections::HashSet;
+
+use crate::audit::{Finding, Severity};
+use crate::error::{AppError, AppResult, ErrorKind};
+use crate::frontmatter::parse_timestamp;
+use crate::layout::RepoLayout;
+use crate::publish::PublishDraftArgs;
+
+/// What a failing rule does
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub enum RuleMode {
+    /// Don't run the rule
+    Off,
+    /// Report a warning and publish anyway
+    Warn,
+    /// Refuse to publish until it passes
+    Block,
+}
+
+impl RuleMode {
+    fn severity(self) -> Option<Severity> {
+        match self {
+            RuleMode::Off => None,
+            RuleMode::Warn => Some(Severity::Warning),
+            RuleMode::Block => Some(Severity::Error),
+        }
+    }
+}
+
+/// The checks `validate_draft` runs before publishing, with their limits
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase", default)]
+pub struct ValidationRules {
+    /// Title, slug, date, description and at least one tag
+    pub required_fields: RuleMode,
+    pub description_length: RuleMode,
+    pub min_description_length: usize,
+    pub max_description_length: usize,
+    /// Lowercase letters, digits and single hyphens
+    pub slug_format: RuleMode,
+    /// `YYYY-MM-DD` or RFC 3339
+    pub date_format: RuleMode,
+    pub tag_count: RuleMode,
+    pub min_tags: usize,
+    pub max_tags: usize,
+    /// Pasted images and images from the drafts branch, before re-encoding
+    pub image_size: RuleMode,
+    pub max_image_mb: u64,
+    /// Links to other articles that aren't on the publish base branch
+    pub internal_links: RuleMode,
+    /// `data:` URLs that publishing would not turn into image files
+    pub data_urls: RuleMode,
+}
+
+impl Default for ValidationRules {
+    fn default() -> Self {
+        ValidationRules {
+            required_fields: RuleMode::Block,
+            description_length: RuleMode::Warn,
+            min_description_length: 50,
+            max_description_length: 160,
+            slug_format: RuleMode::Block,
+            date_format: RuleMode::Block,
+            tag_count: RuleMode::Warn,
+            min_tags: 1,
+            max_tags: 8,
+            image_size: RuleMode::Warn,
+            max_image_mb: 10,
+            internal_links: RuleMode::Warn,
+            data_urls: RuleMode::Block,
+        }
+    }
+}
+
+/// Result of `validate_draft`
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ValidationReport {
+    pub findings: Vec<Finding>,
+    /// Whether a blocking rule failed, so publishing will be refused
+    pub blocked: bool,
+}
+
+impl ValidationReport {
+    /// An error listing the failed blocking rules, if there are any
+    pub fn check(&self) -> AppResult<()> {
+        if !self.blocked {
+            return Ok(());
+        }
+        let errors: Vec<&str> = self
+            .findings
+            .iter()
+            .filter(|f| f.severity == Severity::Error)
+            .map(|f| f.message.as_str())
+            .collect();
+        Err(AppError::new(
+            ErrorKind::ValidationFailed,
+            format!(
+                "Publishing is blocked by {} failed check{}",
+                errors.len(),
+                if errors.len() == 1 { "" } else { "s" }
+            ),
+        )
+        .with_details(errors.join("\n")))
+    }
+}
+
+/// Run the enabled `rules` against a draft about to be published. Internal
+/// links are checked against `published`, the slugs on the publish base
+/// branch (`None` skips them); drafts images are read by name with
+/// `read_drafts_image`, and ones it can't find are skipped.
+pub fn validate_draft(
+    args: &PublishDraftArgs,
+    rules: &ValidationRules,
+    layout: &RepoLayout,
+    published: Option<&HashSet<String>>,
+    read_drafts_image: &dyn Fn(&str) -> Option<Vec<u8>>,
+) -> AppResult<ValidationReport> {
+    let mut findings = Vec::new();
+    let mut report = |rule: &'static str, mode: RuleMode, message: String, line: Option<usize>| {
+        if let Some(severity) = mode.severity() {
+            findings.push(Finding {
+                rule,
+                severity,
+                message,
+                line,
+            });
+        }
+    };
+    let required = [
+        ("Title", args.title.trim().is_empty()),
+        ("Slug", args.slug.trim().is_empty()),
+        ("Date", args.date.trim().is_empty()),
+        ("Description", args.description.trim().is_empty()),
+        ("Tags (at least one)", args.tags.is_empty()),
+    ];
+    for (field, missing) in required {
+        if missing {
+            let message = format!("Missing required field: {}", field);
+            report("required-fields", rules.required_fields, message, None);
+        }
+    }
+
+    let description_length = args.description.trim().chars().count();
+    if description_length > 0
+        && (description_length < rules.min_description_length
+            || description_length > rules.max_description_length)
+    {
+        let message = format!(
+            "Description is {} characters; search engines show {}–{}",
+            description_length, rules.min_description_length, rules.max_description_length
+        );
+        report(
+            "description-length",
+            rules.description_length,
+            message,
+            None,
+        );
+    }
+
+    if !args.slug.is_empty() && !is_valid_slug(&args.slug) {
+        let message = format!(
+            "Slug \"{}\" should only use lowercase letters, digits and single hyphens",
+            args.slug
+        );
+        report("slug-format", rules.slug_format, message, None);
+    }
+
+    if !args.date.trim().is_empty() && parse_timestamp(&args.date).is_none() {
+        let message = format!("Date \"{}\" is not YYYY-MM-DD or RFC 3339", args.date);
+        report("date-format", rules.date_format, message, None);
+    }
+
+    if args.tags.len() < rules.min_tags || args.tags.len() > rules.max_tags {
+        let message = format!(
+            "{} tag{}; use between {} and {}",
+            args.tags.len(),
+            if args.tags.len() == 1 { "" } else { "s" },
+            rules.min_tags,
+            rules.max_tags
+        );
+        report("tag-count", rules.tag_count, message, None);
+    }
+
+    let regex = |pattern: &str| {
+        Regex::new(pattern)
+            .map_err(|e| AppError::invalid("Failed to build validation pattern").with_details(e))
+    };
+    // Same shapes `prepare_inline_images` turns into files
+    let image_re = regex(r#"!\[[^\]]*\]\(([^)\s]+)[^)]*\)|<img[^>]*?src="([^"]+)"[^>]*>"#)?;
+    let link_re = regex(r#"(?:^|[^!\]])\[[^\]]*\]\(([^)\s]+)[^)]*\)|<a\s[^>]*?href="([^"]+)""#)?;
+    let data_re = regex(r"data:[a-zA-Z]+/[a-zA-Z0-9.+-]+")?;
+
+    let max_image_bytes = rules.max_image_mb.saturating_mul(1024 * 1024);
+    let oversized_image = |src: &str| {
+        let (label, size) = if src.starts_with("data:image") {
+            ("Pasted image", data_url_size(src)?)
+        } else {
+            let image = read_drafts_image(layout.drafts_image_name(src)?)?;
+            ("Image", image.len() as u64)
+        };
+        (size > max_image_bytes).then(|| {
+            format!(
+                "{} is {:.1} MB, over the {} MB limit",
+                label,
+                size as f64 / (1024.0 * 1024.0),
+                rules.max_image_mb
+            )
+        })
+    };
+
+    if args.cover.starts_with("data:") && !args.cover.starts_with("data:image") {
+        let message = "Cover is a data URL but not an image".to_string();
+        report("data-urls", rules.data_urls, message, None);
+    }
+    if let Some(message) = oversized_image(&args.cover) {
+        report("image-size", rules.image_size, message, None);
+    }
+
+    let slugs = published.filter(|_| rules.internal_links != RuleMode::Off);
+    let mut in_code = false;
+    for (index, line) in args.content.lines().enumerate() {
+        let number = Some(index + 1);
+        let trimmed = line.trim();
+        if trimmed.starts_with("```") || trimmed.starts_with("~~~") {
+            in_code = !in_code;
+            continue;
+        }
+        if in_code {
+            continue;
+        }
+        // Code spans are shown literally, so nothing in them is a link or URL
+        let line = strip_code_spans(line);
+        let line = line.as_str();
+
+        let mut converted = String::with_capacity(line.len());
+        let mut last = 0;
+        for caps in image_re.captures_iter(line) {
+            let src = caps.get(1).or_else(|| caps.get(2)).unwrap().as_str();
+            if let Some(message) = oversized_image(src) {
+                report("image-size", rules.image_size, message, number);
+            }
+            if src.starts_with("data:image") {
+                let full = caps.get(0).unwrap();
+                converted.push_str(&line[last..full.start()]);
+                last = full.end();
+            }
+        }
+        converted.push_str(&line[last..]);
+        for data in data_re.find_iter(&converted) {
+            let message = format!("{} URL will be published inline", data.as_str());
+            report("data-urls", rules.data_urls, message, number);
+        }
+
+        if let Some(slugs) = slugs {
+            for caps in link_re.captures_iter(line) {
+                let href = caps.get(1).or_else(|| caps.get(2)).unwrap().as_str();
+                let Some(slug) = linked_slug(href, layout) else {
+                    continue;
+                };
+                if slug != args.slug && !slugs.contains(slug) {
+                    let message = format!("Link to {} doesn't match any article", href);
+                    report("internal-links", rules.internal_links, message, number);
+                }
+            }
+        }
+    }
+
+    let blocked = findings.iter().any(|f| f.severity == Severity::Error);
+    Ok(ValidationReport { findings, blocked })
+}
+
+/// `line` without its inline code spans: a run of backticks up to the next
+/// run of the same length. An unmatched run is kept as literal text.
+fn strip_code_spans(line: &str) -> String {
+    let mut stripped = String::with_capacity(line.len());
+    let mut rest = line;
+    while let Some(start) = rest.find('`') {
+        let ticks = rest[start..].len() - rest[start..].trim_start_matches('`').len();
+        let after = &rest[start + ticks..];
+        let mut search = 0;
+        let close = loop {
+            let Some(offset) = after[search..].find('`') else {
+                break None;
+            };
+            let at = search + offset;
+            let run = after[at..].len() - after[at..].trim_start_matches('`').len();
+            if run == ticks {
+                break Some(at + run);
+            }
+            search = at + run;
+        };
+        match close {
+            Some(end) => {
+                stripped.push_str(&rest[..start]);
+                rest = &after[end..];
+            }
+            None => {
+                stripped.push_str(&rest[..start + ticks]);
+                rest = after;
+            }
+        }
+    }
+    stripped.push_str(rest);
+    stripped
+}
+
+fn is_valid_slug(slug: &str) -> bool {
+    !slug.starts_with('-')
+        && !slug.ends_with('-')
+        && !slug.contains("--")
+        && slug
+            .chars()
+            .all(|c| c.is_ascii_lowercase() || c.is_ascii_digit() || c == '-')
+}
+
+/// Decoded size of a base64 data URL
+fn data_url_size(data_url: &str) -> Option<u64> {
+    let (_, data) = data_url.split_once(',')?;
+    let data = data.trim_end_matches('=');
+    Some(data.len() as u64 * 3 / 4)
+}
+
+/// The article slug a link points to, if it is a link to an article: under
+/// the layout's articles URL, or a relative link to a Markdown file
+fn linked_slug<'a>(href: &'a str, layout: &RepoLayout) -> Option<&'a str> {
+    let path = href.split(['#', '?']).next()?;
+    let articles_url = layout.articles_url.trim_end_matches('/');
+    let rest = if !articles_url.is_empty() && path.starts_with(articles_url) {
+        path[articles_url.len()..].strip_prefix('/')?
+    } else if !path.contains("://") && !path.starts_with('/') && path.ends_with(".md") {
+        path
+    } else {
+        return None;
+    };
+    let segment = rest
+        .trim_end_matches('/')
+        .trim_end_matches("/index.md")
+        .rsplit('/')
+        .next()?;
+    let slug = segment
+        .strip_suffix(".md")
+        .or_else(|| segment.strip_suffix(".html"))
+        .unwrap_or(segment);
+    (!slug.is_empty() && slug != ".").then_some(slug)
+}
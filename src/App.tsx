@@ -12,6 +12,7 @@ import { PreviewDialog } from './components/PreviewDialog';
 import { SettingsDialog } from './components/SettingsDialog';
 import { useDraftStore } from './stores/draftStore';
 import { lexicalToMarkdown } from './lib/markdown';
+import { errorMessage, recoveryHint } from './lib/errors';
 
 // Import editor theme CSS
 import './components/editor/themes/editor-theme.css';
@@ -107,15 +108,16 @@ function App() {
 
     try {
       // Generate slug from title
-      const slug = draft.title
-        .toLowerCase()
-        .replace(/[^a-z0-9]+/g, '-')
-        .replace(/^-|-$/g, '') || 'untitled';
+      const slug = draft.slug || await invoke<string>('generate_slug', {
+        title: draft.title,
+        repoPath: null,
+        current: null,
+      });
 
       // Export content to markdown
       const markdownContent = draft.content ? lexicalToMarkdown(draft.content) : '';
 
-      const result = await invoke<{ success: boolean; message: string; branch: string }>('sync_to_drafts', {
+      const result = await invoke<{ success: boolean; message: string; branch: string; warnings: string[] }>('sync_to_drafts', {
         args: {
           slug,
           title: draft.title || 'Untitled',
@@ -124,18 +126,28 @@ function App() {
           description: draft.description || '',
           cover: draft.cover || '',
           coverPosition: draft.coverPosition ?? 50,
+          coverAlt: draft.coverAlt ?? '',
           updatedAt: draft.updatedAt,
           content: markdownContent,
           repoPath,
           draftId: draft.id || '',
+          extraFields: draft.extraFields ?? {},
         },
       });
 
       if (result.success) {
-        toast.success('Synced to drafts branch', {
-          id: toastId,
-          description: result.message,
-        });
+        if (result.warnings.length > 0) {
+          // Some images were dropped; say which instead of a plain success
+          toast.warning('Synced to drafts branch with image problems', {
+            id: toastId,
+            description: result.warnings.join('\n'),
+          });
+        } else {
+          toast.success('Synced to drafts branch', {
+            id: toastId,
+            description: result.message,
+          });
+        }
         // Update draft status to 'synced'
         updateDraft({ status: 'synced', syncedAt: new Date().toISOString() });
         // Save the updated status
@@ -149,7 +161,7 @@ function App() {
     } catch (error) {
       toast.error('Sync failed', {
         id: toastId,
-        description: String(error),
+        description: recoveryHint(error) ?? errorMessage(error),
       });
     } finally {
       setIsSyncing(false);
@@ -1,9 +1,11 @@
-import { useState } from 'react';
+import { useEffect, useState } from 'react';
 import { invoke } from '@tauri-apps/api/core';
 import { X, GitBranch, FileText, ImageIcon, Loader2 } from 'lucide-react';
-import { useDraftStore, generateSlug } from '../stores/draftStore';
+import { useDraftStore } from '../stores/draftStore';
 import { lexicalToMarkdown } from '../lib/markdown';
+import { errorMessage, recoveryHint } from '../lib/errors';
 import { Button } from '@/components/ui/button';
+import { Input } from '@/components/ui/input';
 import { Textarea } from '@/components/ui/textarea';
 import {
   Dialog,
@@ -35,6 +37,88 @@ function countImagesInContent(content: any): number {
   return count;
 }
 
+type FileAction = 'create' | 'overwrite' | 'unchanged';
+
+interface PlannedFile {
+  path: string;
+  action: FileAction;
+}
+
+interface PublishPlan {
+  article: PlannedFile;
+  frontmatter: string;
+  images: (PlannedFile & { isCover: boolean; publicCopy: PlannedFile | null })[];
+  branch: string;
+  base: string;
+  steps: string[];
+  warnings: string[];
+  audit: AuditFinding[];
+  auditBlocks: boolean;
+}
+
+type Severity = 'info' | 'warning' | 'error';
+
+interface AuditFinding {
+  rule: string;
+  severity: Severity;
+  message: string;
+  line: number | null;
+}
+
+interface SlugRename {
+  oldPath: string;
+  newPath: string;
+  images: string[];
+  redirect: string | null;
+  message: string;
+}
+
+interface UnpublishResult {
+  article: string;
+  deletedImages: string[];
+  landed: boolean;
+  pullRequest: string | null;
+  message: string;
+  draft: { retractedAt: string | null; retractionReason: string | null } | null;
+}
+
+interface ValidationReport {
+  findings: AuditFinding[];
+  blocked: boolean;
+}
+
+const SEVERITY_COLOR: Record<Severity, string> = {
+  info: 'text-muted-foreground',
+  warning: 'text-yellow-500',
+  error: 'text-red-500',
+};
+
+interface ImageReport {
+  filename: string;
+  encoding: 'lossless' | 'lossy' | 'avif';
+  originalBytes: number;
+  writtenBytes: number;
+  savedBytes: number;
+}
+
+interface PublishResult {
+  success: boolean;
+  message: string;
+  file_path?: string;
+  images: ImageReport[];
+  warnings: string[];
+}
+
+const formatSize = (bytes: number) =>
+  bytes < 1024 * 1024 ? `${(bytes / 1024).toFixed(1)} KB` : `${(bytes / 1024 / 1024).toFixed(1)} MB`;
+
+const ACTION_PREFIX: Record<FileAction, string> = { create: '+', overwrite: '~', unchanged: '=' };
+const ACTION_COLOR: Record<FileAction, string> = {
+  create: 'text-green-500',
+  overwrite: 'text-yellow-500',
+  unchanged: 'text-muted-foreground',
+};
+
 interface PublishDialogPropsExtended extends PublishDialogProps {
   onOpenSettings?: () => void;
 }
@@ -43,13 +127,82 @@ export function PublishDialog({ onClose, onOpenSettings }: PublishDialogPropsExt
   const { draft, updateDraft, repoPath } = useDraftStore();
   const [isPublishing, setIsPublishing] = useState(false);
   const [publishError, setPublishError] = useState<string | null>(null);
+  const [publishHint, setPublishHint] = useState<string | null>(null);
+  const [plan, setPlan] = useState<PublishPlan | null>(null);
+  const [validation, setValidation] = useState<ValidationReport | null>(null);
   const [commitMessage, setCommitMessage] = useState(
     draft?.title ? `Add: ${draft.title}` : 'Add new article'
   );
 
-  if (!draft) return null;
+  const [slug, setSlug] = useState(draft?.slug ?? '');
+  const [slugInput, setSlugInput] = useState(draft?.slug ?? '');
+  // A published draft whose slug is edited gets renamed instead of republished
+  const publishedSlug = draft?.status === 'published' ? draft.slug : '';
+  const isRename = Boolean(publishedSlug) && slug !== publishedSlug;
+  const [retractionReason, setRetractionReason] = useState('');
 
-  const slug = draft.slug || generateSlug(draft.title) || 'untitled';
+  const buildArgs = () => ({
+    slug,
+    title: draft!.title,
+    date: draft!.date,
+    tags: draft!.tags,
+    description: draft!.description,
+    cover: draft!.cover || '',
+    coverPosition: draft!.coverPosition ?? 50,
+    coverAlt: draft!.coverAlt ?? '',
+    updatedAt: draft!.updatedAt,
+    content: lexicalToMarkdown(draft!.content),
+    commitMessage,
+    repoPath: repoPath,
+    extraFields: draft!.extraFields ?? {},
+  });
+
+  // Suggest a slug from the title that no other article uses
+  useEffect(() => {
+    if (!draft || draft.slug) return;
+    invoke<string>('generate_slug', { title: draft.title, repoPath: repoPath || null, current: null })
+      .then((generated) => {
+        setSlug(generated);
+        setSlugInput(generated);
+      })
+      .catch((error) => console.error('Failed to generate slug:', error));
+    // eslint-disable-next-line react-hooks/exhaustive-deps
+  }, [draft?.id]);
+
+  const confirmSlug = async () => {
+    if (slugInput === slug) return;
+    try {
+      const cleaned = await invoke<string>('generate_slug', { title: slugInput, repoPath: null, current: null });
+      setSlug(cleaned);
+      setSlugInput(cleaned);
+    } catch (error) {
+      console.error('Failed to clean slug:', error);
+      setSlugInput(slug);
+    }
+  };
+
+  // Dry run to list exactly what publishing will change
+  useEffect(() => {
+    if (!draft || !repoPath || !slug || isRename) return;
+    let cancelled = false;
+    const args = buildArgs();
+    invoke<PublishPlan>('plan_publish', { args })
+      .then((result) => {
+        if (!cancelled) setPlan(result);
+      })
+      .catch((error) => console.error('Failed to plan publish:', error));
+    invoke<ValidationReport>('validate_draft', { args })
+      .then((result) => {
+        if (!cancelled) setValidation(result);
+      })
+      .catch((error) => console.error('Failed to validate draft:', error));
+    return () => {
+      cancelled = true;
+    };
+    // eslint-disable-next-line react-hooks/exhaustive-deps
+  }, [draft?.id, repoPath, slug]);
+
+  if (!draft) return null;
 
   // Count images in content + cover
   const imageCount = countImagesInContent(draft.content) + (draft.cover ? 1 : 0);
@@ -57,45 +210,115 @@ export function PublishDialog({ onClose, onOpenSettings }: PublishDialogPropsExt
   const handlePublish = async () => {
     setIsPublishing(true);
     setPublishError(null);
+    setPublishHint(null);
 
     try {
       // Update draft with slug
       updateDraft({ slug, status: 'published', publishedAt: new Date().toISOString() });
 
-      // Convert Lexical editor state to markdown
-      const markdown = lexicalToMarkdown(draft.content);
-
       // Call Tauri command to publish
-      const result = await invoke<{ success: boolean; message: string; file_path?: string }>('publish_draft', {
-        args: {
-          slug,
-          title: draft.title,
-          date: draft.date,
-          tags: draft.tags,
-          description: draft.description,
-          cover: draft.cover || '',
-          coverPosition: draft.coverPosition ?? 50,
-          updatedAt: draft.updatedAt,
-          content: markdown,
-          commitMessage,
-          repoPath: repoPath,
-        }
+      const result = await invoke<PublishResult>('publish_draft', {
+        args: buildArgs(),
       });
 
       if (result.success) {
-        toast.success('Published successfully!', {
-          description: `Article saved to ${result.file_path?.split('/').pop() || slug + '.md'}`,
-        });
+        const saved = result.images.reduce((total, image) => total + image.savedBytes, 0);
+        const savings = saved > 0 ? ` Images are ${formatSize(saved)} smaller.` : '';
+        const description = `Article saved to ${result.file_path?.split('/').pop() || slug + '.md'}.${savings}`;
+        if (result.warnings.length > 0) {
+          // The article is live, but without some of its images
+          toast.warning('Published with image problems', {
+            description: [description, ...result.warnings].join('\n'),
+          });
+        } else {
+          toast.success('Published successfully!', { description });
+        }
         onClose();
       } else {
         throw new Error(result.message);
       }
     } catch (error) {
-      const errorMessage = error instanceof Error ? error.message : String(error);
+      const message = errorMessage(error);
+      const hint = recoveryHint(error);
       console.error('Publish failed:', error);
-      setPublishError(errorMessage);
+      setPublishError(message);
+      setPublishHint(hint);
       toast.error('Publish failed', {
-        description: errorMessage,
+        description: hint ?? message,
+      });
+    } finally {
+      setIsPublishing(false);
+    }
+  };
+
+  const handleRename = async () => {
+    setIsPublishing(true);
+    setPublishError(null);
+    setPublishHint(null);
+
+    try {
+      const result = await invoke<SlugRename>('rename_slug', {
+        repoPath,
+        oldSlug: publishedSlug,
+        newSlug: slug,
+      });
+      updateDraft({ slug });
+      toast.success('Slug renamed', { description: result.redirect ?? result.message });
+      onClose();
+    } catch (error) {
+      const message = errorMessage(error);
+      const hint = recoveryHint(error);
+      console.error('Rename failed:', error);
+      setPublishError(message);
+      setPublishHint(hint);
+      toast.error('Rename failed', {
+        description: hint ?? message,
+      });
+    } finally {
+      setIsPublishing(false);
+    }
+  };
+
+  const handleUnpublish = async () => {
+    setIsPublishing(true);
+    setPublishError(null);
+    setPublishHint(null);
+
+    try {
+      const result = await invoke<UnpublishResult>('unpublish_draft', {
+        args: {
+          draftId: draft.id,
+          slug: publishedSlug,
+          repoPath,
+          reason: retractionReason,
+        },
+      });
+      // Until the takedown PR is merged the article is still live
+      if (!result.landed) {
+        toast.info('Unpublish pending', { description: result.message });
+        onClose();
+        return;
+      }
+      updateDraft({
+        status: 'draft',
+        retractedAt: result.draft?.retractedAt ?? new Date().toISOString(),
+        retractionReason: result.draft?.retractionReason ?? (retractionReason.trim() || null),
+      });
+      const images = result.deletedImages.length;
+      toast.success('Unpublished', {
+        description: images > 0
+          ? `${result.message} (${images} image${images > 1 ? 's' : ''} removed)`
+          : result.message,
+      });
+      onClose();
+    } catch (error) {
+      const message = errorMessage(error);
+      const hint = recoveryHint(error);
+      console.error('Unpublish failed:', error);
+      setPublishError(message);
+      setPublishHint(hint);
+      toast.error('Unpublish failed', {
+        description: hint ?? message,
       });
     } finally {
       setIsPublishing(false);
@@ -103,9 +326,10 @@ export function PublishDialog({ onClose, onOpenSettings }: PublishDialogPropsExt
   };
 
   // Validation
-  const hasRequiredFields = draft.title && draft.tags.length > 0 && draft.description;
   const hasRepoPath = Boolean(repoPath);
-  const canPublish = hasRequiredFields && hasRepoPath;
+  const canPublish = isRename
+    ? hasRepoPath
+    : hasRepoPath && Boolean(slug) && !validation?.blocked && !plan?.auditBlocks;
 
   return (
     <Dialog open onOpenChange={() => onClose()}>
@@ -129,9 +353,13 @@ export function PublishDialog({ onClose, onOpenSettings }: PublishDialogPropsExt
               </div>
               <div className="flex justify-between items-center">
                 <span className="text-muted-foreground">Slug</span>
-                <code className="px-2 py-0.5 bg-muted rounded text-xs text-primary font-mono">
-                  {slug}.md
-                </code>
+                <Input
+                  value={slugInput}
+                  onChange={(e) => setSlugInput(e.target.value)}
+                  onBlur={confirmSlug}
+                  onKeyDown={(e) => e.key === 'Enter' && confirmSlug()}
+                  className="h-7 ml-4 max-w-[200px] font-mono text-xs text-primary"
+                />
               </div>
               <div className="flex justify-between items-center">
                 <span className="text-muted-foreground">Tags</span>
@@ -147,20 +375,97 @@ export function PublishDialog({ onClose, onOpenSettings }: PublishDialogPropsExt
             <h3 className="text-xs font-medium text-muted-foreground uppercase tracking-wider mb-2">
               Changes to Publish
             </h3>
-            <div className="bg-muted rounded-lg p-3 font-mono text-xs space-y-1.5">
-              <div className="flex items-center gap-2 text-green-500">
-                <FileText size={13} strokeWidth={1.5} />
-                <span>+ content/articles/{slug}.md</span>
+            {plan ? (
+              <div className="bg-muted rounded-lg p-3 font-mono text-xs space-y-1.5">
+                <div className={`flex items-center gap-2 ${ACTION_COLOR[plan.article.action]}`}>
+                  <FileText size={13} strokeWidth={1.5} />
+                  <span>{ACTION_PREFIX[plan.article.action]} {plan.article.path}</span>
+                </div>
+                {plan.images.flatMap((image) => (image.publicCopy ? [image, image.publicCopy] : [image])).map((file) => (
+                  <div key={file.path} className={`flex items-center gap-2 ${ACTION_COLOR[file.action]}`}>
+                    <ImageIcon size={13} strokeWidth={1.5} />
+                    <span>{ACTION_PREFIX[file.action]} {file.path}</span>
+                  </div>
+                ))}
+                {plan.steps.map((step) => (
+                  <div key={step} className="flex items-center gap-2 text-muted-foreground">
+                    <GitBranch size={13} strokeWidth={1.5} />
+                    <span>{step}</span>
+                  </div>
+                ))}
               </div>
-              {imageCount > 0 && (
+            ) : (
+              <div className="bg-muted rounded-lg p-3 font-mono text-xs space-y-1.5">
                 <div className="flex items-center gap-2 text-green-500">
-                  <ImageIcon size={13} strokeWidth={1.5} />
-                  <span>+ content/images/ ({imageCount} image{imageCount > 1 ? 's' : ''})</span>
+                  <FileText size={13} strokeWidth={1.5} />
+                  <span>+ content/articles/{slug}.md</span>
                 </div>
-              )}
-            </div>
+                {imageCount > 0 && (
+                  <div className="flex items-center gap-2 text-green-500">
+                    <ImageIcon size={13} strokeWidth={1.5} />
+                    <span>+ content/images/ ({imageCount} image{imageCount > 1 ? 's' : ''})</span>
+                  </div>
+                )}
+              </div>
+            )}
+            {plan && plan.warnings.length > 0 && (
+              <div className="mt-2 text-xs text-yellow-500 space-y-1 bg-yellow-500/10 rounded-lg p-3">
+                {plan.warnings.map((warning) => (
+                  <p key={warning}>• {warning}</p>
+                ))}
+              </div>
+            )}
           </div>
 
+          {/* Accessibility audit */}
+          {plan && plan.audit.length > 0 && (
+            <div>
+              <h3 className="text-xs font-medium text-muted-foreground uppercase tracking-wider mb-2">
+                Accessibility
+              </h3>
+              <div className="text-xs space-y-1 bg-muted/40 rounded-lg p-3">
+                {plan.audit.map((finding, index) => (
+                  <p key={`${finding.rule}-${index}`} className={SEVERITY_COLOR[finding.severity]}>
+                    • {finding.line ? `Line ${finding.line}: ` : ''}{finding.message}
+                  </p>
+                ))}
+              </div>
+            </div>
+          )}
+
+          {isRename && (
+            <div className="text-xs text-yellow-500 bg-yellow-500/10 rounded-lg p-3">
+              This article is published as <code>{publishedSlug}</code>. Renaming moves it and its
+              images to <code>{slug}</code> and redirects the old URL; publish again afterwards to
+              update its content.
+            </div>
+          )}
+
+          {/* Unpublish */}
+          {publishedSlug && hasRepoPath && !isRename && (
+            <div>
+              <h3 className="text-xs font-medium text-muted-foreground uppercase tracking-wider mb-2">
+                Unpublish
+              </h3>
+              <div className="flex items-center gap-2">
+                <Input
+                  value={retractionReason}
+                  onChange={(e) => setRetractionReason(e.target.value)}
+                  placeholder="Reason (optional)"
+                  className="h-8 text-xs"
+                />
+                <Button
+                  variant="outline"
+                  size="sm"
+                  onClick={handleUnpublish}
+                  disabled={isPublishing}
+                >
+                  Unpublish
+                </Button>
+              </div>
+            </div>
+          )}
+
           {/* Commit Message */}
           <div>
             <h3 className="text-xs font-medium text-muted-foreground uppercase tracking-wider mb-2">
@@ -196,11 +501,13 @@ export function PublishDialog({ onClose, onOpenSettings }: PublishDialogPropsExt
           )}
 
           {/* Validation warnings */}
-          {hasRepoPath && !hasRequiredFields && (
-            <div className="text-xs text-yellow-500 space-y-1 bg-yellow-500/10 rounded-lg p-3">
-              {!draft.title && <p>• Missing required field: Title</p>}
-              {draft.tags.length === 0 && <p>• Missing required field: Tags (at least one)</p>}
-              {!draft.description && <p>• Missing required field: Description</p>}
+          {hasRepoPath && validation && validation.findings.length > 0 && (
+            <div className="text-xs space-y-1 bg-yellow-500/10 rounded-lg p-3">
+              {validation.findings.map((finding, index) => (
+                <p key={`${finding.rule}-${index}`} className={SEVERITY_COLOR[finding.severity]}>
+                  • {finding.line ? `Line ${finding.line}: ` : ''}{finding.message}
+                </p>
+              ))}
             </div>
           )}
 
@@ -209,6 +516,7 @@ export function PublishDialog({ onClose, onOpenSettings }: PublishDialogPropsExt
             <div className="text-xs text-red-500 space-y-1 bg-red-500/10 rounded-lg p-3">
               <p className="font-medium">Publish Error:</p>
               <p className="break-all">{publishError}</p>
+              {publishHint && <p className="text-foreground">{publishHint}</p>}
             </div>
           )}
         </div>
@@ -218,18 +526,18 @@ export function PublishDialog({ onClose, onOpenSettings }: PublishDialogPropsExt
             Cancel
           </Button>
           <Button
-            onClick={handlePublish}
+            onClick={isRename ? handleRename : handlePublish}
             disabled={!canPublish || isPublishing}
           >
             {isPublishing ? (
               <>
                 <Loader2 size={14} className="mr-2 animate-spin" />
-                Publishing...
+                {isRename ? 'Renaming...' : 'Publishing...'}
               </>
             ) : (
               <>
                 <GitBranch size={14} strokeWidth={2} className="mr-2" />
-                Publish to Main
+                {isRename ? 'Rename' : 'Publish'}
               </>
             )}
           </Button>
@@ -15,14 +15,37 @@ import { Button } from '@/components/ui/button';
 import { Input } from '@/components/ui/input';
 import { Label } from '@/components/ui/label';
 import { useDraftStore, EditorWidth } from '../stores/draftStore';
+import { errorMessage } from '../lib/errors';
 
 interface RepoValidation {
   isValid: boolean;
   isGitRepo: boolean;
   hasContentDir: boolean;
   error: string | null;
+  warnings: string[];
 }
 
+interface OrphanScan {
+  branch: string;
+  images: { path: string; sizeBytes: number }[];
+  totalBytes: number;
+  filesScanned: number;
+}
+
+const formatSize = (bytes: number) =>
+  bytes < 1024 * 1024 ? `${(bytes / 1024).toFixed(1)} KB` : `${(bytes / 1024 / 1024).toFixed(1)} MB`;
+
+type SiteGenerator = 'nextjs' | 'hugo' | 'jekyll' | 'astro' | 'zola' | 'eleventy';
+
+const SITE_GENERATORS: { value: SiteGenerator; label: string }[] = [
+  { value: 'nextjs', label: 'Next.js' },
+  { value: 'hugo', label: 'Hugo' },
+  { value: 'jekyll', label: 'Jekyll' },
+  { value: 'astro', label: 'Astro' },
+  { value: 'zola', label: 'Zola' },
+  { value: 'eleventy', label: 'Eleventy' },
+];
+
 interface SettingsDialogProps {
   open: boolean;
   onOpenChange: (open: boolean) => void;
@@ -34,10 +57,15 @@ export function SettingsDialog({ open, onOpenChange }: SettingsDialogProps) {
   const [localRepoPath, setLocalRepoPath] = useState(repoPath);
   const [localTheme, setLocalTheme] = useState(theme);
   const [localEditorWidth, setLocalEditorWidth] = useState<EditorWidth>(editorWidth);
+  const [localSiteGenerator, setLocalSiteGenerator] = useState<SiteGenerator>('nextjs');
   const [validation, setValidation] = useState<RepoValidation | null>(null);
   const [isValidating, setIsValidating] = useState(false);
   const [isSaving, setIsSaving] = useState(false);
   const [isSyncingImages, setIsSyncingImages] = useState(false);
+  const [orphanScan, setOrphanScan] = useState<OrphanScan | null>(null);
+  const [isScanningImages, setIsScanningImages] = useState(false);
+  const [confirmDelete, setConfirmDelete] = useState(false);
+  const [isDeletingImages, setIsDeletingImages] = useState(false);
 
   // Sync local state when dialog opens
   useEffect(() => {
@@ -45,6 +73,11 @@ export function SettingsDialog({ open, onOpenChange }: SettingsDialogProps) {
       setLocalRepoPath(repoPath);
       setLocalTheme(theme);
       setLocalEditorWidth(editorWidth);
+      setOrphanScan(null);
+      setConfirmDelete(false);
+      invoke<{ siteGenerator?: SiteGenerator }>('get_settings')
+        .then((current) => setLocalSiteGenerator(current.siteGenerator ?? 'nextjs'))
+        .catch((error) => console.error('Failed to load settings:', error));
       if (repoPath) {
         validatePath(repoPath);
       }
@@ -66,7 +99,8 @@ export function SettingsDialog({ open, onOpenChange }: SettingsDialogProps) {
         isValid: false,
         isGitRepo: false,
         hasContentDir: false,
-        error: String(error),
+        error: errorMessage(error),
+        warnings: [],
       });
     } finally {
       setIsValidating(false);
@@ -100,12 +134,15 @@ export function SettingsDialog({ open, onOpenChange }: SettingsDialogProps) {
   const handleSave = async () => {
     setIsSaving(true);
     try {
-      // Save to Tauri backend
+      // Save to Tauri backend, keeping settings this dialog doesn't edit
+      const current = await invoke<Record<string, unknown>>('get_settings');
       await invoke('save_settings', {
         settings: {
+          ...current,
           repoPath: localRepoPath,
           theme: localTheme,
           editorWidth: localEditorWidth,
+          siteGenerator: localSiteGenerator,
         },
       });
 
@@ -125,7 +162,7 @@ export function SettingsDialog({ open, onOpenChange }: SettingsDialogProps) {
     } catch (error) {
       console.error('Failed to save settings:', error);
       toast.error('Failed to save settings', {
-        description: String(error),
+        description: errorMessage(error),
       });
     } finally {
       setIsSaving(false);
@@ -151,13 +188,52 @@ export function SettingsDialog({ open, onOpenChange }: SettingsDialogProps) {
     } catch (error) {
       console.error('Failed to sync images:', error);
       toast.error('Failed to sync images', {
-        description: String(error),
+        description: errorMessage(error),
       });
     } finally {
       setIsSyncingImages(false);
     }
   };
 
+  const handleScanImages = async () => {
+    setIsScanningImages(true);
+    setConfirmDelete(false);
+    try {
+      const result = await invoke<OrphanScan>('scan_orphan_images', { repoPath: localRepoPath });
+      setOrphanScan(result);
+    } catch (error) {
+      console.error('Failed to scan images:', error);
+      toast.error('Failed to scan images', {
+        description: errorMessage(error),
+      });
+    } finally {
+      setIsScanningImages(false);
+    }
+  };
+
+  const handleDeleteImages = async () => {
+    if (!orphanScan) return;
+    setIsDeletingImages(true);
+    try {
+      const result = await invoke<{ deleted: string[]; freedBytes: number; message: string }>(
+        'delete_orphan_images',
+        { repoPath: localRepoPath, paths: orphanScan.images.map((image) => image.path) }
+      );
+      toast.success('Unused images removed', {
+        description: result.message,
+      });
+      setOrphanScan(null);
+    } catch (error) {
+      console.error('Failed to delete images:', error);
+      toast.error('Failed to delete images', {
+        description: errorMessage(error),
+      });
+    } finally {
+      setIsDeletingImages(false);
+      setConfirmDelete(false);
+    }
+  };
+
   const canSave = !isValidating && (!localRepoPath || validation?.isValid);
   const selectedThemeStyle =
     localTheme === 'light'
@@ -216,11 +292,6 @@ export function SettingsDialog({ open, onOpenChange }: SettingsDialogProps) {
                   <>
                     <Check size={14} className="text-green-500" />
                     <span className="text-green-500">Valid git repository</span>
-                    {!validation.hasContentDir && (
-                      <span className="text-muted-foreground">
-                        (content/ directory will be created)
-                      </span>
-                    )}
                   </>
                 ) : (
                   <>
@@ -232,6 +303,13 @@ export function SettingsDialog({ open, onOpenChange }: SettingsDialogProps) {
                 )}
               </div>
             )}
+            {!isValidating && validation && validation.warnings.length > 0 && (
+              <div className="text-xs text-muted-foreground mt-1 space-y-0.5">
+                {validation.warnings.map((warning) => (
+                  <p key={warning}>{warning}</p>
+                ))}
+              </div>
+            )}
           </div>
 
           {/* Theme */}
@@ -280,6 +358,90 @@ export function SettingsDialog({ open, onOpenChange }: SettingsDialogProps) {
               ))}
             </div>
           </div>
+
+          {/* Site Generator */}
+          <div className="space-y-2">
+            <Label>Site Generator</Label>
+            <p className="text-xs text-muted-foreground">
+              Frontmatter, file naming and image paths used when publishing.
+            </p>
+            <div className="grid grid-cols-3 gap-2">
+              {SITE_GENERATORS.map(({ value, label }) => (
+                <Button
+                  key={value}
+                  variant={localSiteGenerator === value ? 'default' : 'outline'}
+                  size="sm"
+                  onClick={() => setLocalSiteGenerator(value)}
+                  style={localSiteGenerator === value ? selectedWidthStyle : undefined}
+                >
+                  {label}
+                </Button>
+              ))}
+            </div>
+          </div>
+
+          {/* Unused Images */}
+          <div className="space-y-2">
+            <Label>Unused Images</Label>
+            <p className="text-xs text-muted-foreground">
+              Find images in the repository that no article or page refers to.
+            </p>
+            <Button
+              variant="outline"
+              size="sm"
+              onClick={handleScanImages}
+              disabled={!localRepoPath || isScanningImages || isDeletingImages}
+            >
+              {isScanningImages ? (
+                <>
+                  <Loader2 size={14} className="mr-2 animate-spin" />
+                  Scanning...
+                </>
+              ) : (
+                'Scan for unused images'
+              )}
+            </Button>
+            {orphanScan && orphanScan.images.length === 0 && (
+              <p className="text-xs text-muted-foreground">
+                No unused images on {orphanScan.branch}.
+              </p>
+            )}
+            {orphanScan && orphanScan.images.length > 0 && (
+              <div className="space-y-2">
+                <div className="bg-muted rounded-lg p-3 font-mono text-xs space-y-1 max-h-40 overflow-y-auto">
+                  {orphanScan.images.map((image) => (
+                    <div key={image.path} className="flex justify-between gap-4">
+                      <span className="truncate">{image.path}</span>
+                      <span className="text-muted-foreground shrink-0">{formatSize(image.sizeBytes)}</span>
+                    </div>
+                  ))}
+                </div>
+                <div className="flex items-center justify-between text-xs text-muted-foreground">
+                  <span>
+                    {orphanScan.images.length} image{orphanScan.images.length > 1 ? 's' : ''},{' '}
+                    {formatSize(orphanScan.totalBytes)} on {orphanScan.branch}
+                  </span>
+                  <Button
+                    variant={confirmDelete ? 'destructive' : 'outline'}
+                    size="sm"
+                    onClick={confirmDelete ? handleDeleteImages : () => setConfirmDelete(true)}
+                    disabled={isDeletingImages}
+                  >
+                    {isDeletingImages ? (
+                      <>
+                        <Loader2 size={14} className="mr-2 animate-spin" />
+                        Deleting...
+                      </>
+                    ) : confirmDelete ? (
+                      'Confirm delete'
+                    ) : (
+                      'Delete and commit'
+                    )}
+                  </Button>
+                </div>
+              </div>
+            )}
+          </div>
         </div>
 
         <DialogFooter>
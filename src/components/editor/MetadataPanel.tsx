@@ -1,7 +1,19 @@
 import { useState, useRef, useEffect } from 'react';
-import { X, Plus, ImageIcon, Calendar, Tag } from 'lucide-react';
+import { X, Plus, ImageIcon, Calendar, Tag, Braces } from 'lucide-react';
 import { useDraftStore, EditorWidth } from '../../stores/draftStore';
 
+// Custom field values are JSON when they parse as JSON (true, 3, ["a"]), text otherwise
+const parseFieldValue = (raw: string): unknown => {
+  try {
+    return JSON.parse(raw);
+  } catch {
+    return raw;
+  }
+};
+
+const formatFieldValue = (value: unknown) =>
+  typeof value === 'string' ? value : JSON.stringify(value);
+
 const WIDTH_CLASSES: Record<EditorWidth, string> = {
   narrow: 'max-w-2xl',
   medium: 'max-w-3xl',
@@ -12,6 +24,7 @@ const WIDTH_CLASSES: Record<EditorWidth, string> = {
 export function MetadataPanel() {
   const { draft, updateDraft, editorWidth } = useDraftStore();
   const [tagInput, setTagInput] = useState('');
+  const [fieldInput, setFieldInput] = useState('');
   const widthClass = WIDTH_CLASSES[editorWidth];
   const [isRepositioning, setIsRepositioning] = useState(false);
   const [isDraggingCover, setIsDraggingCover] = useState(false);
@@ -49,6 +62,24 @@ export function MetadataPanel() {
     }
   };
 
+  const extraFields = draft.extraFields ?? {};
+
+  // "key: value" adds or replaces a custom frontmatter field
+  const handleAddField = () => {
+    const separator = fieldInput.indexOf(':');
+    const key = (separator === -1 ? fieldInput : fieldInput.slice(0, separator)).trim();
+    if (key) {
+      const raw = separator === -1 ? '' : fieldInput.slice(separator + 1).trim();
+      updateDraft({ extraFields: { ...extraFields, [key]: parseFieldValue(raw) } });
+    }
+    setFieldInput('');
+  };
+
+  const handleRemoveField = (key: string) => {
+    const { [key]: _removed, ...rest } = extraFields;
+    updateDraft({ extraFields: rest });
+  };
+
   const handleCoverUpload = async (file: File) => {
     return new Promise<string>((resolve) => {
       const reader = new FileReader();
@@ -170,7 +201,7 @@ export function MetadataPanel() {
         >
           <img
             src={draft.cover}
-            alt="Cover"
+            alt={draft.coverAlt || 'Cover'}
             className="w-full h-full object-cover"
             style={{ objectPosition: `50% ${draft.coverPosition ?? 50}%` }}
           />
@@ -294,6 +325,16 @@ export function MetadataPanel() {
 
       {/* Page Header Content */}
       <div className={`${widthClass} mx-auto px-12 py-8 transition-all duration-200`}>
+        {/* Cover alt text, written to the frontmatter */}
+        {draft.cover && (
+          <input
+            type="text"
+            value={draft.coverAlt ?? ''}
+            onChange={(e) => updateDraft({ coverAlt: e.target.value })}
+            placeholder="Describe the cover image (alt text)..."
+            className="w-full mb-4 bg-transparent text-xs text-muted-foreground placeholder:text-muted-foreground/50 outline-none"
+          />
+        )}
         {/* Title - Large, editable */}
         <textarea
           ref={titleRef}
@@ -351,6 +392,40 @@ export function MetadataPanel() {
             </div>
           </div>
 
+          {/* Custom frontmatter fields */}
+          <div className="flex items-center gap-2 text-sm">
+            <Braces size={14} className="text-muted-foreground opacity-50" />
+            <div className="flex flex-wrap items-center gap-1.5">
+              {Object.entries(extraFields).map(([key, value]) => (
+                <span
+                  key={key}
+                  className="inline-flex items-center gap-1 px-2.5 py-1 bg-muted rounded-md text-xs text-muted-foreground hover:text-foreground group font-mono"
+                >
+                  {key}: {formatFieldValue(value)}
+                  <button
+                    onClick={() => handleRemoveField(key)}
+                    className="opacity-0 group-hover:opacity-100 transition-opacity"
+                  >
+                    <X size={10} />
+                  </button>
+                </span>
+              ))}
+              <input
+                type="text"
+                value={fieldInput}
+                onChange={(e) => setFieldInput(e.target.value)}
+                onKeyDown={(e) => {
+                  if (e.key === 'Enter') {
+                    e.preventDefault();
+                    handleAddField();
+                  }
+                }}
+                placeholder={Object.keys(extraFields).length === 0 ? 'Add field (key: value)...' : '+'}
+                className="w-40 px-1 py-0.5 bg-transparent text-xs text-muted-foreground placeholder:text-muted-foreground/50 focus:outline-none"
+              />
+            </div>
+          </div>
+
           {/* Description (subtle) */}
           <div className="pt-2">
             <textarea
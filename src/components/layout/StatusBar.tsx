@@ -126,14 +126,14 @@ export function StatusBar({ onPreview, onPublish, onSync, isSyncing }: StatusBar
           className="h-6 px-3 text-xs"
           onClick={onPublish}
           disabled={!draft?.title || !repoPath}
-          title={!repoPath ? 'Configure repository in Settings first' : 'Commit and push to main branch'}
+          title={!repoPath ? 'Configure repository in Settings first' : 'Publish using the repository's publish strategy'}
           style={{
             backgroundColor: (!draft?.title || !repoPath) ? 'rgba(22, 163, 74, 0.7)' : '#16a34a',
             color: 'white',
           }}
         >
           <Upload size={13} strokeWidth={2} className="mr-1.5" />
-          Publish to Main
+          Publish
         </Button>
       </div>
     </footer>
@@ -0,0 +1,75 @@
+/** Error kinds returned by Tauri commands (mirrors `ErrorKind` in error.rs) */
+export type AppErrorKind =
+  | 'repoNotFound'
+  | 'notAGitRepo'
+  | 'settings'
+  | 'database'
+  | 'io'
+  | 'notFound'
+  | 'invalidInput'
+  | 'image'
+  | 'network'
+  | 'git'
+  | 'nothingToCommit'
+  | 'pushRejected'
+  | 'mergeConflict'
+  | 'gitAuthFailed'
+  | 'ghNotInstalled'
+  | 'ghNotAuthenticated'
+  | 'pullRequest'
+  | 'auditFailed'
+  | 'validationFailed';
+
+export interface AppError {
+  kind: AppErrorKind;
+  message: string;
+  details: string | null;
+}
+
+export function isAppError(error: unknown): error is AppError {
+  return (
+    typeof error === 'object' &&
+    error !== null &&
+    'kind' in error &&
+    'message' in error
+  );
+}
+
+/** Human-readable message for anything thrown by `invoke` */
+export function errorMessage(error: unknown): string {
+  if (isAppError(error)) {
+    return error.details ? `${error.message}: ${error.details}` : error.message;
+  }
+  if (error instanceof Error) {
+    return error.message;
+  }
+  return String(error);
+}
+
+/** A suggested next step for errors the user can fix themselves */
+export function recoveryHint(error: unknown): string | null {
+  if (!isAppError(error)) return null;
+  switch (error.kind) {
+    case 'repoNotFound':
+    case 'notAGitRepo':
+      return 'Choose a valid repository in Settings.';
+    case 'ghNotInstalled':
+      return 'Install the GitHub CLI (gh) to create pull requests.';
+    case 'ghNotAuthenticated':
+      return 'Run `gh auth login` in a terminal, then try again.';
+    case 'pushRejected':
+      return 'The remote has newer commits. Pull the latest changes and try again.';
+    case 'gitAuthFailed':
+      return 'Check your git credentials for this remote.';
+    case 'network':
+      return 'Check your internet connection and try again.';
+    case 'mergeConflict':
+      return 'Resolve the conflicting changes in the repository, then try again.';
+    case 'auditFailed':
+      return 'Fix the accessibility errors listed in the publish dialog, then try again.';
+    case 'validationFailed':
+      return 'Fix the failed checks listed in the publish dialog, or relax them under validation in settings.json.';
+    default:
+      return null;
+  }
+}
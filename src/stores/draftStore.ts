@@ -44,6 +44,7 @@ export interface Draft {
   description: string;
   cover: string;
   coverPosition: number;
+  coverAlt: string;
   content: SerializedEditorState;
   textContent: string;
   createdAt: string;
@@ -51,6 +52,11 @@ export interface Draft {
   syncedAt: string | null;
   publishedAt: string | null;
   status: DraftStatus;
+  // Custom frontmatter fields (canonical_url, series, ...)
+  extraFields: Record<string, unknown>;
+  // When and why the published article was last unpublished
+  retractedAt: string | null;
+  retractionReason: string | null;
 }
 
 // Draft for backend communication (content as JSON string)
@@ -63,6 +69,7 @@ interface DraftForBackend {
   description: string;
   cover: string;
   coverPosition: number;
+  coverAlt: string;
   content: string; // JSON string
   textContent: string;
   createdAt: string;
@@ -70,6 +77,9 @@ interface DraftForBackend {
   syncedAt: string | null;
   publishedAt: string | null;
   status: DraftStatus;
+  extraFields: Record<string, unknown>;
+  retractedAt: string | null;
+  retractionReason: string | null;
 }
 
 // Summary for draft list
@@ -115,15 +125,6 @@ interface DraftStore {
   loadSettings: () => Promise<void>;
 }
 
-export const generateSlug = (title: string) => {
-  return title
-    .toLowerCase()
-    .replace(/[^a-z0-9\s-]/g, '')
-    .replace(/\s+/g, '-')
-    .replace(/-+/g, '-')
-    .trim();
-};
-
 // Convert frontend Draft to backend format
 const draftToBackend = (draft: Draft): DraftForBackend => ({
   ...draft,
@@ -138,6 +139,10 @@ const draftFromBackend = (data: DraftForBackend): Draft => ({
     ? JSON.parse(data.content)
     : data.content,
   coverPosition: typeof data.coverPosition === 'number' ? data.coverPosition : 50,
+  coverAlt: data.coverAlt ?? '',
+  extraFields: data.extraFields ?? {},
+  retractedAt: data.retractedAt ?? null,
+  retractionReason: data.retractionReason ?? null,
 });
 
 export const useDraftStore = create<DraftStore>((set, get) => ({
@@ -270,6 +275,7 @@ export const useDraftStore = create<DraftStore>((set, get) => ({
       description: '',
       cover: '',
       coverPosition: 50,
+      coverAlt: '',
       content: EMPTY_EDITOR_STATE,
       textContent: '',
       createdAt: now,
@@ -277,6 +283,9 @@ export const useDraftStore = create<DraftStore>((set, get) => ({
       syncedAt: null,
       publishedAt: null,
       status: 'draft',
+      extraFields: {},
+      retractedAt: null,
+      retractionReason: null,
     };
     set({ draft, saveStatus: 'unsaved' });
   },